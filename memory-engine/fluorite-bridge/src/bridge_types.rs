@@ -121,6 +121,14 @@ pub struct SearchOptions {
     pub sort_order: Option<String>, // "asc", "desc"
 }
 
+/// Hits of a query string search
+#[napi(object)]
+pub struct QueryResults {
+    pub chunks: Vec<super::MemoryChunk>,
+    pub scores: Vec<f64>, // Relevance score per chunk
+    pub total_hits: u32, // Matches before the limit
+}
+
 /// Advanced search result with enhanced metadata
 #[napi(object)]
 pub struct EnhancedSearchResult {
@@ -305,32 +305,32 @@ impl FluoriteBridge {
         let mut chunks = Vec::new();
         for chunk_id in results {
             if let Ok(Some(chunk)) = self.memory_engine.get_chunk(&chunk_id).await {
-                let content_str = match &chunk.content {
-                    ChunkContent::Text(text) => text.clone(),
-                    ChunkContent::Data { data, .. } => data.to_string(),
-                    ChunkContent::Binary(_) => "[Binary Data]".to_string(),
-                };
-
-                let metadata: HashMap<String, String> = chunk.metadata.properties
-                    .into_iter()
-                    .map(|(k, v)| (k, v.to_string()))
-                    .collect();
-
-                chunks.push(MemoryChunk {
-                    id: chunk.id.to_string(),
-                    chunk_type: format!("{:?}", chunk.chunk_type),
-                    content: content_str,
-                    metadata,
-                    quality_score: chunk.quality_score as f64,
-                    frameworks: chunk.metadata.frameworks,
-                    tags: chunk.metadata.tags,
-                });
+                chunks.push(to_memory_chunk(chunk));
             }
         }
 
         Ok(chunks)
     }
 
+    /// Search with a query string such as `framework:nextjs type:component quality>0.8 -tag:deprecated "server action"`
+    ///
+    /// Malformed queries are rejected with `InvalidArg` and the offending position.
+    #[napi]
+    pub async fn query(&self, query: String) -> napi::Result<QueryResults> {
+        let query = fluorite_memory::parse_query(&query)
+            .map_err(|e| napi::Error::new(Status::InvalidArg, format!("Invalid query: {}", e)))?;
+
+        let results = self.memory_engine.query(&query).await
+            .map_err(|e| napi::Error::from_reason(format!("Failed to query: {}", e)))?;
+
+        let total_hits = results.total_hits as u32;
+        let (chunks, scores): (Vec<_>, Vec<_>) = results.hits.into_iter()
+            .map(|hit| (to_memory_chunk(hit.chunk), hit.score as f64))
+            .unzip();
+
+        Ok(QueryResults { chunks, scores, total_hits })
+    }
+
     /// Run learning from Spike templates
     #[napi]
     pub async fn learn_from_spikes(&self) -> napi::Result<LearningStats> {
@@ -456,6 +456,11 @@ impl FluoriteBridge {
     #[napi]
     pub async fn shutdown(&self) -> napi::Result<()> {
         info!("Shutting down Fluorite Bridge");
+
+        // Persist the memory engine hot set for the next startup
+        if let Err(e) = self.memory_engine.shutdown().await {
+            error!("Error shutting down memory engine: {}", e);
+        }
         
         // Shutdown ML engine
         if let Err(e) = self.ml_engine.shutdown().await {
@@ -466,6 +471,30 @@ impl FluoriteBridge {
     }
 }
 
+/// Convert a learning chunk into its Node.js representation
+fn to_memory_chunk(chunk: LearningChunk) -> MemoryChunk {
+    let content_str = match &chunk.content {
+        ChunkContent::Text(text) => text.clone(),
+        ChunkContent::Data { data, .. } => data.to_string(),
+        ChunkContent::Binary(_) => "[Binary Data]".to_string(),
+    };
+
+    let metadata: HashMap<String, String> = chunk.metadata.properties
+        .into_iter()
+        .map(|(k, v)| (k, v.to_string()))
+        .collect();
+
+    MemoryChunk {
+        id: chunk.id.to_string(),
+        chunk_type: format!("{:?}", chunk.chunk_type),
+        content: content_str,
+        metadata,
+        quality_score: chunk.quality_score as f64,
+        frameworks: chunk.metadata.frameworks,
+        tags: chunk.metadata.tags,
+    }
+}
+
 /// Initialize the Node.js module
 #[napi]
 pub fn init() -> napi::Result<()> {
@@ -133,7 +133,7 @@ impl LearningPipeline {
     pub async fn new(config: LearningConfig, memory_engine: Arc<MemoryEngine>) -> Result<Self> {
         info!("Initializing Fluorite Learning Pipeline");
 
-        let spike_parser = Arc::new(SpikeParser::new());
+        let spike_parser = Arc::new(SpikeParser::new().with_aliases(memory_engine.aliases()));
         let pattern_extractor = Arc::new(PatternExtractor::new(&config.target_frameworks));
         let template_analyzer = Arc::new(TemplateAnalyzer::new(config.learning_rate));
         let learning_algorithms = Arc::new(LearningAlgorithms::new(&config));
@@ -403,9 +403,9 @@ impl LearningPipeline {
 
         // Only store high-quality templates
         if validation.quality_score >= self.config.quality_threshold {
-            // Convert to learning chunk and store
+            // Convert to learning chunk and store it with a part per file and function
             let chunk = self.convert_to_learning_chunk(&spike_template, &analysis).await?;
-            self.memory_engine.store_chunk(chunk).await
+            self.memory_engine.store_chunk_with_parts(chunk).await
                 .context("Failed to store learning chunk")?;
 
             // Update learning state
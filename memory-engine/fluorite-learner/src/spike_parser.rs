@@ -5,8 +5,10 @@
 
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
+use fluorite_memory::AliasTable;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 
@@ -124,6 +126,8 @@ pub struct SpikeExample {
 pub struct SpikeParser {
     /// Parser configuration
     config: ParserConfig,
+    /// Canonical framework names
+    aliases: Arc<AliasTable>,
 }
 
 /// Parser configuration
@@ -150,14 +154,21 @@ impl Default for ParserConfig {
 impl SpikeParser {
     /// Create a new Spike parser
     pub fn new() -> Self {
-        Self {
-            config: ParserConfig::default(),
-        }
+        Self::with_config(ParserConfig::default())
     }
 
     /// Create parser with custom configuration
     pub fn with_config(config: ParserConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            aliases: Arc::new(AliasTable::builtin()),
+        }
+    }
+
+    /// Use an alias table to normalize framework names
+    pub fn with_aliases(mut self, aliases: Arc<AliasTable>) -> Self {
+        self.aliases = aliases;
+        self
     }
 
     /// Parse a Spike template from file
@@ -227,42 +238,17 @@ impl SpikeParser {
 
     /// Extract framework information from template
     pub fn extract_frameworks(&self, template: &SpikeTemplate) -> Vec<String> {
-        let mut frameworks = template.frameworks.clone();
+        let mut frameworks = self.aliases.normalize_all(&template.frameworks);
+        let mut add = |framework: &str| {
+            if !frameworks.iter().any(|f| f == framework) {
+                frameworks.push(framework.to_string());
+            }
+        };
 
-        // Infer frameworks from dependencies
+        // Infer frameworks from dependencies known to the alias table
         for dep in &template.dependencies {
-            match dep.name.as_str() {
-                "next" | "@next/core" => {
-                    if !frameworks.contains(&"nextjs".to_string()) {
-                        frameworks.push("nextjs".to_string());
-                    }
-                }
-                "react" | "react-dom" => {
-                    if !frameworks.contains(&"react".to_string()) {
-                        frameworks.push("react".to_string());
-                    }
-                }
-                "express" => {
-                    if !frameworks.contains(&"express".to_string()) {
-                        frameworks.push("express".to_string());
-                    }
-                }
-                "fastify" => {
-                    if !frameworks.contains(&"fastify".to_string()) {
-                        frameworks.push("fastify".to_string());
-                    }
-                }
-                "hono" => {
-                    if !frameworks.contains(&"hono".to_string()) {
-                        frameworks.push("hono".to_string());
-                    }
-                }
-                "vue" => {
-                    if !frameworks.contains(&"vue".to_string()) {
-                        frameworks.push("vue".to_string());
-                    }
-                }
-                _ => {}
+            if let Some(framework) = self.aliases.canonicalize(&dep.name) {
+                add(framework);
             }
         }
 
@@ -270,16 +256,12 @@ impl SpikeParser {
         for file in &template.files {
             if file.path.contains("pages/") || file.path.contains("app/") {
                 if file.language.as_deref() == Some("tsx") || file.language.as_deref() == Some("jsx") {
-                    if !frameworks.contains(&"nextjs".to_string()) {
-                        frameworks.push("nextjs".to_string());
-                    }
+                    add(&self.aliases.normalize("nextjs"));
                 }
             }
 
             if file.path.ends_with(".php") || file.content.contains("<?php") {
-                if !frameworks.contains(&"laravel".to_string()) {
-                    frameworks.push("laravel".to_string());
-                }
+                add(&self.aliases.normalize("laravel"));
             }
         }
 
@@ -358,9 +340,11 @@ impl SpikeParser {
 
     /// Enhance template with additional metadata
     async fn enhance_template_metadata(&self, template: &mut SpikeTemplate) -> Result<()> {
-        // Extract frameworks if not explicitly set
+        // Extract frameworks if not explicitly set, otherwise canonicalize the declared ones
         if template.frameworks.is_empty() {
             template.frameworks = self.extract_frameworks(template);
+        } else {
+            template.frameworks = self.aliases.normalize_all(&template.frameworks);
         }
 
         // Add complexity metadata
@@ -542,6 +526,28 @@ mod tests {
         assert!(frameworks.contains(&"express".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_declared_frameworks_are_canonicalized() {
+        let spike_content = r#"{
+            "name": "aliases",
+            "description": "Aliased frameworks",
+            "frameworks": ["Next.js 14", "php-laravel", "next"],
+            "dependencies": [
+                {"name": "illuminate/support", "version": "^11.0"}
+            ],
+            "files": [],
+            "tags": [],
+            "examples": []
+        }"#;
+
+        let parser = SpikeParser::new();
+        let template = parser.parse_spike_content(spike_content, None).await.unwrap();
+        assert_eq!(template.frameworks, vec!["nextjs", "laravel"]);
+
+        let frameworks = parser.extract_frameworks(&template);
+        assert_eq!(frameworks, vec!["nextjs", "laravel"]);
+    }
+
     #[tokio::test]
     async fn test_complexity_calculation() {
         let spike_content = r#"{
@@ -0,0 +1,354 @@
+//! Framework and library alias table
+//!
+//! Maps the many spellings users and templates use for the same framework
+//! (`next`, `Next.js 14`, `nextjs`) to one canonical name. Chunks are
+//! normalized to canonical names at ingestion and framework queries are
+//! expanded to every known spelling so data indexed before normalization
+//! is still found.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::chunk::{ChunkContent, LearningChunk};
+
+/// Built-in aliases, keyed by canonical name
+pub const BUILTIN_ALIASES: &[(&str, &[&str])] = &[
+    ("nextjs", &["next", "next.js", "@next/core", "vercel/next.js"]),
+    ("react", &["react-dom", "reactjs", "react.js"]),
+    ("vue", &["vuejs", "vue.js", "vue3"]),
+    ("nuxt", &["nuxtjs", "nuxt.js", "nuxt3"]),
+    ("svelte", &["sveltekit", "@sveltejs/kit"]),
+    ("angular", &["angularjs", "@angular/core"]),
+    ("express", &["expressjs", "express.js"]),
+    ("fastify", &["fastify.js"]),
+    ("hono", &["honojs", "hono.js"]),
+    ("nestjs", &["nest.js", "@nestjs/core"]),
+    ("laravel", &["illuminate", "php-laravel", "laravel/framework", "laravel/laravel", "illuminate/support"]),
+    ("rails", &["ruby-on-rails", "ruby on rails", "rubyonrails"]),
+    ("django", &["django-rest-framework", "djangorestframework"]),
+    ("fastapi", &["fast-api"]),
+    ("flutter", &["flutter-sdk"]),
+    ("react-native", &["reactnative", "react native"]),
+    ("expo", &["expo-sdk"]),
+    ("tailwindcss", &["tailwind", "tailwind-css"]),
+    ("prisma", &["@prisma/client", "prisma-orm"]),
+    ("drizzle-orm", &["drizzle"]),
+    ("trpc", &["@trpc/server", "@trpc/client"]),
+];
+
+/// Top-level catalog keys naming the package an entry describes
+const CATALOG_PACKAGE_KEYS: &[&str] = &["npm", "composer", "package", "pip", "cargo"];
+
+/// Canonical names for framework and library aliases
+///
+/// Entries added first take precedence: an alias that already maps to a
+/// canonical name is never re-pointed by later sources.
+#[derive(Debug, Clone, Default)]
+pub struct AliasTable {
+    // Alias key -> canonical name
+    canonical: HashMap<String, String>,
+    // Canonical name -> every spelling recorded for it, including itself
+    spellings: HashMap<String, BTreeSet<String>>,
+}
+
+impl AliasTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a table containing the built-in aliases
+    pub fn builtin() -> Self {
+        let mut table = Self::new();
+        table.extend_builtin();
+        table
+    }
+
+    /// Add the built-in aliases
+    pub fn extend_builtin(&mut self) {
+        for (canonical, aliases) in BUILTIN_ALIASES {
+            self.insert(canonical, aliases.iter().copied());
+        }
+    }
+
+    /// Number of canonical names
+    pub fn len(&self) -> usize {
+        self.spellings.len()
+    }
+
+    /// Check whether the table has no entries
+    pub fn is_empty(&self) -> bool {
+        self.spellings.is_empty()
+    }
+
+    /// Register a canonical name and its aliases
+    ///
+    /// If the canonical name is itself already an alias, the aliases are
+    /// attached to the name it resolves to.
+    pub fn insert<'a, I>(&mut self, canonical: &str, aliases: I)
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let canonical = match self.canonicalize(canonical) {
+            Some(existing) => existing.to_string(),
+            None => {
+                let canonical = spelling(canonical);
+                if canonical.is_empty() || alias_key(&canonical).is_empty() {
+                    return;
+                }
+                canonical
+            }
+        };
+
+        self.add_alias(&canonical, &canonical.clone());
+        for alias in aliases {
+            self.add_alias(&canonical, alias);
+        }
+    }
+
+    /// Load aliases from a YAML or JSON file mapping canonical names to alias lists
+    ///
+    /// Returns the number of canonical names read from the file.
+    pub fn load_file(&mut self, path: &Path) -> Result<usize> {
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read alias file {:?}", path))?;
+
+        let is_json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let entries: BTreeMap<String, Vec<String>> = if is_json {
+            serde_json::from_str(&data)
+                .with_context(|| format!("Failed to parse alias file {:?}", path))?
+        } else {
+            serde_yaml::from_str(&data)
+                .with_context(|| format!("Failed to parse alias file {:?}", path))?
+        };
+
+        for (canonical, aliases) in &entries {
+            self.insert(canonical, aliases.iter().map(String::as_str));
+        }
+
+        tracing::info!("Loaded {} alias groups from {:?}", entries.len(), path);
+        Ok(entries.len())
+    }
+
+    /// Seed aliases from a directory of catalog YAML files
+    ///
+    /// Each entry's `id` becomes the canonical name unless it is already a
+    /// known alias; its display name (with versions and parentheticals split
+    /// off) and package names are added as aliases. Unreadable entries are
+    /// skipped. Returns the number of entries used.
+    pub fn seed_from_catalog(&mut self, catalog_dir: &Path) -> Result<usize> {
+        let mut paths: Vec<_> = std::fs::read_dir(catalog_dir)
+            .with_context(|| format!("Failed to read catalog directory {:?}", catalog_dir))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml"))
+            .collect();
+        paths.sort();
+
+        let mut seeded = 0;
+        for path in paths {
+            let entry = match std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|data| serde_yaml::from_str::<serde_yaml::Value>(&data).map_err(anyhow::Error::from))
+            {
+                Ok(entry) => entry,
+                Err(e) => {
+                    tracing::warn!("Skipping catalog entry {:?}: {}", path, e);
+                    continue;
+                }
+            };
+
+            let field = |key: &str| entry.get(key).and_then(|value| value.as_str()).map(str::trim);
+            let Some(id) = field("id").or_else(|| field("name")).filter(|id| !id.is_empty()) else {
+                continue;
+            };
+
+            let mut aliases = Vec::new();
+            if let Some(name) = field("name") {
+                aliases.extend(name_variants(name));
+            }
+            aliases.extend(CATALOG_PACKAGE_KEYS.iter().filter_map(|key| field(key)).map(str::to_string));
+
+            self.insert(id, aliases.iter().map(String::as_str));
+            seeded += 1;
+        }
+
+        tracing::info!("Seeded aliases from {} catalog entries in {:?}", seeded, catalog_dir);
+        Ok(seeded)
+    }
+
+    /// Resolve a name to its canonical form, if known
+    pub fn canonicalize(&self, name: &str) -> Option<&str> {
+        self.canonical.get(&alias_key(name)).map(String::as_str)
+    }
+
+    /// Resolve a name to its canonical form, keeping unknown names as given
+    pub fn normalize(&self, name: &str) -> String {
+        match self.canonicalize(name) {
+            Some(canonical) => canonical.to_string(),
+            None => name.trim().to_string(),
+        }
+    }
+
+    /// Normalize a list of names, dropping duplicates while keeping order
+    pub fn normalize_all(&self, names: &[String]) -> Vec<String> {
+        let mut seen = BTreeSet::new();
+        names.iter()
+            .map(|name| self.normalize(name))
+            .filter(|name| !name.is_empty() && seen.insert(name.clone()))
+            .collect()
+    }
+
+    /// Expand a name to its canonical form and every known spelling
+    ///
+    /// Unknown names expand to themselves.
+    pub fn expand(&self, name: &str) -> Vec<String> {
+        match self.canonicalize(name).and_then(|canonical| self.spellings.get(canonical)) {
+            Some(spellings) => spellings.iter().cloned().collect(),
+            None => vec![name.trim().to_string()],
+        }
+    }
+
+    /// Rewrite a chunk's frameworks to their canonical names
+    pub fn normalize_chunk(&self, chunk: &mut LearningChunk) {
+        chunk.metadata.frameworks = self.normalize_all(&chunk.metadata.frameworks);
+
+        if let ChunkContent::Code { framework: Some(framework), .. } = &mut chunk.content {
+            *framework = self.normalize(framework);
+        }
+    }
+
+    fn add_alias(&mut self, canonical: &str, alias: &str) {
+        let key = alias_key(alias);
+        if key.is_empty() {
+            return;
+        }
+
+        match self.canonical.get(&key) {
+            Some(existing) if existing != canonical => {
+                tracing::debug!("Alias {:?} already maps to {}, not {}", alias, existing, canonical);
+                return;
+            }
+            Some(_) => {}
+            None => {
+                self.canonical.insert(key, canonical.to_string());
+            }
+        }
+
+        self.spellings.entry(canonical.to_string()).or_default().insert(spelling(alias));
+    }
+}
+
+/// Lookup key for an alias
+///
+/// Lowercases, drops trailing versions (`Next 14`, `next@14.2`, `Laravel 11/12`)
+/// and removes punctuation, so `Next.js`, `next-js` and `nextjs` share a key.
+pub fn alias_key(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+
+    // A version suffix follows the package name's `@`, which may also start a scope
+    let name = match name.char_indices().skip(1).find(|(_, c)| *c == '@') {
+        Some((i, _)) => &name[..i],
+        None => name.as_str(),
+    };
+
+    let mut words: Vec<&str> = name.split_whitespace().collect();
+    while words.len() > 1 && words.last().is_some_and(|word| is_version(word)) {
+        words.pop();
+    }
+
+    words.concat().chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+/// Stored spelling of an alias, used for exact-match query expansion
+pub(crate) fn spelling(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+fn is_version(word: &str) -> bool {
+    let word = word.strip_prefix('v').unwrap_or(word);
+    word.starts_with(|c: char| c.is_ascii_digit())
+        && word.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | '/' | 'x' | '*' | '-'))
+}
+
+/// Split a display name such as `TanStack Query (React Query)` into its aliases
+fn name_variants(name: &str) -> Vec<String> {
+    let mut variants = Vec::new();
+    match (name.find('('), name.rfind(')')) {
+        (Some(open), Some(close)) if open < close => {
+            variants.push(name[..open].trim().to_string());
+            variants.push(name[open + 1..close].trim().to_string());
+        }
+        _ => variants.push(name.to_string()),
+    }
+    variants.retain(|variant| !variant.is_empty());
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_alias_keys_ignore_versions_and_punctuation() {
+        assert_eq!(alias_key("Next.js"), "nextjs");
+        assert_eq!(alias_key("Next 14"), "next");
+        assert_eq!(alias_key("next@14.2.1"), "next");
+        assert_eq!(alias_key("@next/core"), "nextcore");
+        assert_eq!(alias_key("Laravel Framework 11/12"), "laravelframework");
+        assert_eq!(alias_key("Vue 3"), "vue");
+    }
+
+    #[test]
+    fn test_builtin_normalization_and_expansion() {
+        let table = AliasTable::builtin();
+
+        for name in ["next", "next.js", "nextjs", "Next 14", "NEXT.JS"] {
+            assert_eq!(table.normalize(name), "nextjs", "{}", name);
+        }
+        for name in ["laravel", "illuminate", "php-laravel"] {
+            assert_eq!(table.normalize(name), "laravel", "{}", name);
+        }
+        assert_eq!(table.normalize("  htmx "), "htmx");
+
+        let expanded = table.expand("Next 14");
+        assert!(expanded.contains(&"nextjs".to_string()));
+        assert!(expanded.contains(&"next.js".to_string()));
+        assert_eq!(table.expand("htmx"), vec!["htmx".to_string()]);
+
+        let names = vec!["next".to_string(), "nextjs".to_string(), "React-DOM".to_string()];
+        assert_eq!(table.normalize_all(&names), vec!["nextjs".to_string(), "react".to_string()]);
+    }
+
+    #[test]
+    fn test_file_and_catalog_sources() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let alias_file = temp_dir.path().join("aliases.yaml");
+        std::fs::write(&alias_file, "remix:\n  - remix-run\n  - \"@remix-run/react\"\n").unwrap();
+
+        let catalog_dir = temp_dir.path().join("catalog");
+        std::fs::create_dir(&catalog_dir).unwrap();
+        std::fs::write(
+            catalog_dir.join("nextjs.yaml"),
+            "id: \"nextjs\"\nname: \"Next.js 14/15\"\nnpm: \"next\"\n",
+        ).unwrap();
+        std::fs::write(
+            catalog_dir.join("tanstack-query.yaml"),
+            "id: \"@tanstack/react-query\"\nname: \"TanStack Query (React Query)\"\nnpm: \"@tanstack/react-query\"\n",
+        ).unwrap();
+        std::fs::write(catalog_dir.join("broken.yaml"), "id: [unterminated").unwrap();
+
+        let mut table = AliasTable::new();
+        assert_eq!(table.load_file(&alias_file).unwrap(), 1);
+        assert_eq!(table.seed_from_catalog(&catalog_dir).unwrap(), 2);
+
+        assert_eq!(table.normalize("@remix-run/react"), "remix");
+        assert_eq!(table.normalize("Next.js 15"), "nextjs");
+        assert_eq!(table.normalize("next"), "nextjs");
+        assert_eq!(table.normalize("React Query"), "@tanstack/react-query");
+        assert_eq!(table.normalize("tanstack query"), "@tanstack/react-query");
+    }
+}
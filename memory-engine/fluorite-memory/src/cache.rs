@@ -10,6 +10,7 @@ use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock as AsyncRwLock;
 
 use crate::chunk::{ChunkId, LearningChunk};
@@ -80,6 +81,17 @@ pub struct LruCache {
     stats: Arc<RwLock<CacheStats>>,
 }
 
+/// Entry of a persisted hot-set snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotSetEntry {
+    /// Cached chunk ID
+    pub chunk_id: ChunkId,
+    /// Number of accesses recorded while the chunk was cached
+    pub access_count: u64,
+    /// Estimated in-memory size of the chunk in bytes
+    pub size_bytes: usize,
+}
+
 /// Cache statistics
 #[derive(Debug, Default, Clone)]
 pub struct CacheStats {
@@ -233,6 +245,55 @@ impl LruCache {
         self.chunks.read().keys().cloned().collect()
     }
 
+    /// Get the configured memory budget in bytes
+    pub fn max_size_bytes(&self) -> usize {
+        self.config.max_size_bytes
+    }
+
+    /// Get the configured maximum number of cached chunks
+    pub fn max_chunks(&self) -> usize {
+        self.config.max_chunks
+    }
+
+    /// Capture the current hot set, ordered from most to least recently used
+    pub fn snapshot_hot_set(&self) -> Vec<HotSetEntry> {
+        let chunks = self.chunks.read();
+        let mut entries = Vec::with_capacity(chunks.len());
+        let mut cursor = self.head.read().clone();
+
+        while let Some(chunk_id) = cursor {
+            // Guard against a corrupted list looping forever
+            if entries.len() >= chunks.len() {
+                break;
+            }
+
+            match chunks.get(&chunk_id) {
+                Some(node) => {
+                    entries.push(HotSetEntry {
+                        chunk_id: node.chunk_id.clone(),
+                        access_count: node.access_count,
+                        size_bytes: node.size,
+                    });
+                    cursor = node.next.clone();
+                }
+                None => break,
+            }
+        }
+
+        entries
+    }
+
+    /// Restore a chunk from a hot-set snapshot, keeping its recorded access frequency
+    pub async fn restore(&self, chunk: LearningChunk, access_count: u64) {
+        let chunk_id = chunk.id.clone();
+        self.insert(chunk_id.clone(), chunk).await;
+
+        let mut chunks = self.chunks.write();
+        if let Some(node) = chunks.get_mut(&chunk_id) {
+            node.access_count = access_count.max(1);
+        }
+    }
+
     /// Clean up expired entries based on TTL
     pub async fn cleanup_expired(&self) {
         let now = Instant::now();
@@ -534,6 +595,34 @@ mod tests {
         assert_eq!(stats.memory_usage_bytes, 0);
     }
 
+    #[tokio::test]
+    async fn test_hot_set_snapshot_and_restore() {
+        let cache = LruCache::new(100, 1024 * 1024);
+
+        let chunk1 = create_test_chunk("chunk1", 1);
+        let chunk2 = create_test_chunk("chunk2", 2);
+
+        cache.insert(chunk1.id.clone(), chunk1.clone()).await;
+        cache.insert(chunk2.id.clone(), chunk2.clone()).await;
+        cache.get(&chunk1.id).await;
+        cache.get(&chunk1.id).await;
+
+        // Most recently used chunk comes first
+        let snapshot = cache.snapshot_hot_set();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].chunk_id, chunk1.id);
+        assert_eq!(snapshot[0].access_count, 3);
+        assert_eq!(snapshot[1].chunk_id, chunk2.id);
+        assert!(snapshot[1].size_bytes > snapshot[0].size_bytes);
+
+        // Restoring into a fresh cache keeps the access frequency
+        let restored = LruCache::new(100, 1024 * 1024);
+        restored.restore(chunk2.clone(), snapshot[1].access_count).await;
+        restored.restore(chunk1.clone(), snapshot[0].access_count).await;
+
+        assert_eq!(restored.snapshot_hot_set(), snapshot);
+    }
+
     #[tokio::test]
     async fn test_memory_pressure() {
         let cache = LruCache::new(100, 1000); // Small memory limit
@@ -0,0 +1,392 @@
+//! Candidate generation for similarity search
+//!
+//! Scoring every stored chunk against a query chunk does not scale, so
+//! `find_similar` first narrows the field using cheap signals: shared
+//! frameworks, shared patterns, shared tags, MinHash/LSH collisions over the
+//! searchable text and, when embeddings are available, nearest neighbours from
+//! the vector index. Candidates are ranked by how strongly these signals agree
+//! and capped at a budget before the full similarity scoring runs.
+//!
+//! Features shared by a large part of the corpus, such as a framework most
+//! chunks use, say little about similarity and would make every lookup visit
+//! most chunks. Posting lists longer than [`MAX_POSTING_LIST`] are therefore
+//! skipped, leaving those chunks to the LSH bands and embedding neighbours.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{ChunkId, LearningChunk};
+
+/// Number of MinHash permutations per signature
+pub const MINHASH_PERMUTATIONS: usize = 64;
+
+/// Number of LSH bands; each band covers `MINHASH_PERMUTATIONS / LSH_BANDS` rows
+pub const LSH_BANDS: usize = 16;
+
+const LSH_ROWS: usize = MINHASH_PERMUTATIONS / LSH_BANDS;
+
+/// Longest posting list that still casts votes for its chunks
+pub const MAX_POSTING_LIST: usize = 1024;
+
+// Relative weight of each signal when ranking candidates
+const BAND_WEIGHT: f32 = 1.0;
+const PATTERN_WEIGHT: f32 = 2.0;
+const FRAMEWORK_WEIGHT: f32 = 1.5;
+const TAG_WEIGHT: f32 = 1.0;
+const EMBEDDING_WEIGHT: f32 = 8.0;
+
+/// Cheap features of a chunk used to find similarity candidates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkSignature {
+    /// Lowercased frameworks of the chunk
+    pub frameworks: Vec<String>,
+    /// Metadata patterns and detected code pattern signatures
+    pub patterns: Vec<String>,
+    /// Lowercased tags of the chunk
+    pub tags: Vec<String>,
+    /// MinHash of the searchable text tokens
+    pub minhash: Vec<u64>,
+}
+
+impl ChunkSignature {
+    /// Compute the signature of a chunk, adding detected code patterns
+    pub fn from_chunk(chunk: &LearningChunk, code_patterns: &[String]) -> Self {
+        let mut patterns: Vec<String> = chunk.metadata.patterns.iter()
+            .chain(code_patterns)
+            .map(|pattern| pattern.to_lowercase())
+            .collect();
+        patterns.sort();
+        patterns.dedup();
+
+        Self {
+            frameworks: normalized(&chunk.metadata.frameworks),
+            patterns,
+            tags: normalized(&chunk.metadata.tags),
+            minhash: minhash(&chunk.get_searchable_text()),
+        }
+    }
+
+    /// Estimated Jaccard similarity of the two token sets
+    pub fn estimated_jaccard(&self, other: &ChunkSignature) -> f32 {
+        if self.minhash.is_empty() || self.minhash.len() != other.minhash.len() {
+            return 0.0;
+        }
+
+        let equal = self.minhash.iter().zip(&other.minhash).filter(|(a, b)| a == b).count();
+        equal as f32 / self.minhash.len() as f32
+    }
+
+    fn band_keys(&self) -> Vec<u64> {
+        self.minhash.chunks(LSH_ROWS)
+            .enumerate()
+            .map(|(band, rows)| {
+                let mut hash = fnv1a(&(band as u64).to_le_bytes());
+                for row in rows {
+                    hash = fnv1a_extend(hash, &row.to_le_bytes());
+                }
+                hash
+            })
+            .collect()
+    }
+}
+
+/// Inverted indexes from chunk features to chunk IDs
+#[derive(Debug, Default)]
+pub struct CandidateIndex {
+    signatures: HashMap<ChunkId, ChunkSignature>,
+    frameworks: HashMap<String, HashSet<ChunkId>>,
+    patterns: HashMap<String, HashSet<ChunkId>>,
+    tags: HashMap<String, HashSet<ChunkId>>,
+    bands: HashMap<u64, HashSet<ChunkId>>,
+}
+
+impl CandidateIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of indexed chunks
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Check whether no chunk is indexed
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Signature of an indexed chunk
+    pub fn signature(&self, chunk_id: &ChunkId) -> Option<&ChunkSignature> {
+        self.signatures.get(chunk_id)
+    }
+
+    /// Every indexed chunk with its signature
+    pub fn signatures(&self) -> impl Iterator<Item = (&ChunkId, &ChunkSignature)> {
+        self.signatures.iter()
+    }
+
+    /// Add or replace the signature of a chunk
+    pub fn insert(&mut self, chunk_id: ChunkId, signature: ChunkSignature) {
+        self.remove(&chunk_id);
+
+        for framework in &signature.frameworks {
+            self.frameworks.entry(framework.clone()).or_default().insert(chunk_id.clone());
+        }
+        for pattern in &signature.patterns {
+            self.patterns.entry(pattern.clone()).or_default().insert(chunk_id.clone());
+        }
+        for tag in &signature.tags {
+            self.tags.entry(tag.clone()).or_default().insert(chunk_id.clone());
+        }
+        for band in signature.band_keys() {
+            self.bands.entry(band).or_default().insert(chunk_id.clone());
+        }
+
+        self.signatures.insert(chunk_id, signature);
+    }
+
+    /// Remove a chunk, returning whether it was indexed
+    pub fn remove(&mut self, chunk_id: &ChunkId) -> bool {
+        let Some(signature) = self.signatures.remove(chunk_id) else {
+            return false;
+        };
+
+        unlink(&mut self.frameworks, &signature.frameworks, chunk_id);
+        unlink(&mut self.patterns, &signature.patterns, chunk_id);
+        unlink(&mut self.tags, &signature.tags, chunk_id);
+        unlink(&mut self.bands, &signature.band_keys(), chunk_id);
+        true
+    }
+
+    /// Remove every chunk
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Rank candidate chunks for a signature, best first
+    ///
+    /// `neighbours` are nearest neighbours from the vector index with their
+    /// cosine similarity. The chunk itself is never returned, and at most
+    /// `budget` candidates are produced.
+    pub fn candidates(
+        &self,
+        chunk_id: &ChunkId,
+        signature: &ChunkSignature,
+        neighbours: &[(ChunkId, f32)],
+        budget: usize,
+    ) -> Vec<ChunkId> {
+        let mut scores: HashMap<&ChunkId, f32> = HashMap::new();
+
+        vote(&mut scores, &self.frameworks, &signature.frameworks, FRAMEWORK_WEIGHT);
+        vote(&mut scores, &self.patterns, &signature.patterns, PATTERN_WEIGHT);
+        vote(&mut scores, &self.tags, &signature.tags, TAG_WEIGHT);
+        vote(&mut scores, &self.bands, &signature.band_keys(), BAND_WEIGHT);
+
+        for (candidate, similarity) in neighbours {
+            *scores.entry(candidate).or_default() += similarity.max(0.0) * EMBEDDING_WEIGHT;
+        }
+
+        scores.remove(chunk_id);
+
+        let mut ranked: Vec<(&ChunkId, f32)> = scores.into_iter()
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.as_str().cmp(b.0.as_str())));
+        ranked.truncate(budget);
+
+        ranked.into_iter().map(|(candidate, _)| candidate.clone()).collect()
+    }
+}
+
+fn normalized(values: &[String]) -> Vec<String> {
+    let mut values: Vec<String> = values.iter().map(|value| value.to_lowercase()).collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+fn vote<'a, K>(
+    scores: &mut HashMap<&'a ChunkId, f32>,
+    index: &'a HashMap<K, HashSet<ChunkId>>,
+    keys: &[K],
+    weight: f32,
+) where
+    K: std::hash::Hash + Eq,
+{
+    for key in keys {
+        let Some(members) = index.get(key).filter(|members| members.len() <= MAX_POSTING_LIST) else {
+            continue;
+        };
+        for candidate in members {
+            *scores.entry(candidate).or_default() += weight;
+        }
+    }
+}
+
+fn unlink<K>(index: &mut HashMap<K, HashSet<ChunkId>>, keys: &[K], chunk_id: &ChunkId)
+where
+    K: std::hash::Hash + Eq,
+{
+    for key in keys {
+        if let Some(members) = index.get_mut(key) {
+            members.remove(chunk_id);
+            if members.is_empty() {
+                index.remove(key);
+            }
+        }
+    }
+}
+
+/// MinHash of the lowercased whitespace tokens of a text
+///
+/// Uses FNV-1a token hashes mixed with a fixed seed per permutation, so
+/// signatures are stable across processes and can be persisted.
+pub fn minhash(text: &str) -> Vec<u64> {
+    let tokens: HashSet<u64> = text.split_whitespace()
+        .map(|token| fnv1a(token.to_lowercase().as_bytes()))
+        .collect();
+
+    if tokens.is_empty() {
+        return Vec::new();
+    }
+
+    (0..MINHASH_PERMUTATIONS as u64)
+        .map(|permutation| {
+            let seed = splitmix64(permutation);
+            tokens.iter().map(|token| splitmix64(token ^ seed)).min().unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    fnv1a_extend(FNV_OFFSET, bytes)
+}
+
+fn fnv1a_extend(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::{ChunkContent, ChunkMetadata};
+
+    fn chunk(id: &str, code: &str, frameworks: &[&str], tags: &[&str]) -> LearningChunk {
+        LearningChunk {
+            id: ChunkId::new(id),
+            content: ChunkContent::Code {
+                language: "typescript".to_string(),
+                code: code.to_string(),
+                framework: None,
+            },
+            metadata: ChunkMetadata {
+                frameworks: frameworks.iter().map(|s| s.to_string()).collect(),
+                tags: tags.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn index(chunks: &[LearningChunk]) -> CandidateIndex {
+        let mut index = CandidateIndex::new();
+        for chunk in chunks {
+            index.insert(chunk.id.clone(), ChunkSignature::from_chunk(chunk, &[]));
+        }
+        index
+    }
+
+    #[test]
+    fn test_minhash_estimates_overlap() {
+        let a = minhash("export default function Page props return div");
+        let b = minhash("export default function Page props return div");
+        let c = minhash("class UserController extends Controller");
+        assert_eq!(a.len(), MINHASH_PERMUTATIONS);
+        assert_eq!(a, b);
+
+        let sig = |minhash| ChunkSignature { frameworks: vec![], patterns: vec![], tags: vec![], minhash };
+        assert_eq!(sig(a.clone()).estimated_jaccard(&sig(b)), 1.0);
+        assert!(sig(a).estimated_jaccard(&sig(c)) < 0.2);
+        assert!(minhash("   ").is_empty());
+    }
+
+    #[test]
+    fn test_candidates_rank_shared_signals_first() {
+        let query = chunk("query", "export default function Page() { return null }", &["nextjs"], &["routing"]);
+        let chunks = vec![
+            query.clone(),
+            chunk("near-copy", "export default function Page() { return null }", &["nextjs"], &["routing"]),
+            chunk("same-framework", "const x = 1", &["NextJS"], &[]),
+            chunk("same-tag", "<?php echo 1;", &["laravel"], &["routing"]),
+            chunk("unrelated", "SELECT 1", &["postgres"], &[]),
+        ];
+        let index = index(&chunks);
+        let signature = index.signature(&query.id).unwrap().clone();
+
+        let candidates = index.candidates(&query.id, &signature, &[], 10);
+        let ids: Vec<&str> = candidates.iter().map(|id| id.as_str()).collect();
+        assert_eq!(ids[0], "near-copy");
+        assert!(ids.contains(&"same-framework"));
+        assert!(ids.contains(&"same-tag"));
+        assert!(!ids.contains(&"unrelated"));
+        assert!(!ids.contains(&"query"));
+
+        // The budget caps the candidate list
+        assert_eq!(index.candidates(&query.id, &signature, &[], 1), vec![ChunkId::new("near-copy")]);
+
+        // Embedding neighbours become candidates even without shared features
+        let neighbours = vec![(ChunkId::new("unrelated"), 0.9)];
+        let candidates = index.candidates(&query.id, &signature, &neighbours, 10);
+        assert!(candidates.contains(&ChunkId::new("unrelated")));
+    }
+
+    #[test]
+    fn test_frequent_keys_do_not_vote() {
+        let query = chunk("query", "export default function Page() { return null }", &["nextjs"], &[]);
+        let mut chunks = vec![
+            query.clone(),
+            chunk("near-copy", "export default function Page() { return null }", &["nextjs"], &[]),
+        ];
+        chunks.extend((0..MAX_POSTING_LIST).map(|i| {
+            chunk(&format!("filler-{}", i), &format!("const v{} = {}", i, i), &["nextjs"], &[])
+        }));
+        let index = index(&chunks);
+        let signature = index.signature(&query.id).unwrap().clone();
+
+        // Sharing the ubiquitous framework no longer makes every chunk a candidate
+        let candidates = index.candidates(&query.id, &signature, &[], 10);
+        assert_eq!(candidates, vec![ChunkId::new("near-copy")]);
+    }
+
+    #[test]
+    fn test_remove_unlinks_chunk() {
+        let chunks = vec![
+            chunk("a", "export function a", &["nextjs"], &["x"]),
+            chunk("b", "export function a", &["nextjs"], &["x"]),
+        ];
+        let mut index = index(&chunks);
+        assert!(index.remove(&ChunkId::new("b")));
+        assert!(!index.remove(&ChunkId::new("b")));
+
+        let signature = index.signature(&ChunkId::new("a")).unwrap().clone();
+        assert!(index.candidates(&ChunkId::new("a"), &signature, &[], 10).is_empty());
+        assert!(index.frameworks.is_empty() || !index.frameworks["nextjs"].contains(&ChunkId::new("b")));
+        assert_eq!(index.len(), 1);
+    }
+}
@@ -97,6 +97,7 @@ pub enum ChunkContent {
     /// Structured data (JSON, etc.)
     Data {
         format: String,
+        #[serde(with = "json_encoded")]
         data: serde_json::Value,
     },
     /// Binary or opaque content
@@ -106,6 +107,39 @@ pub enum ChunkContent {
     },
 }
 
+/// Serde adapter storing JSON values as JSON text in binary formats
+///
+/// bincode cannot deserialize self-describing values such as
+/// `serde_json::Value`, so chunks are written to storage with such fields as
+/// strings. Human-readable formats keep the plain JSON structure. An empty
+/// string reads as the default value, which is how an empty map written
+/// before this encoding decodes.
+mod json_encoded {
+    use serde::de::{DeserializeOwned, Error as _};
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T: Serialize, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            value.serialize(serializer)
+        } else {
+            serde_json::to_string(value).map_err(S::Error::custom)?.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, T: DeserializeOwned + Default, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        if deserializer.is_human_readable() {
+            T::deserialize(deserializer)
+        } else {
+            let text = String::deserialize(deserializer)?;
+            if text.is_empty() {
+                return Ok(T::default());
+            }
+            serde_json::from_str(&text).map_err(D::Error::custom)
+        }
+    }
+}
+
 /// Metadata associated with a learning chunk
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChunkMetadata {
@@ -130,6 +164,7 @@ pub struct ChunkMetadata {
     /// Dependencies on other chunks
     pub dependencies: Vec<ChunkId>,
     /// Custom properties
+    #[serde(with = "json_encoded")]
     pub properties: HashMap<String, serde_json::Value>,
 }
 
@@ -163,6 +198,22 @@ pub struct ChunkRelation {
     pub strength: f32,
     /// Additional metadata about the relationship
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Whether the relationship was declared or inferred by pattern analysis
+    pub provenance: RelationProvenance,
+    /// Confidence in the relationship (0.0 - 1.0); 1.0 for declared ones
+    pub confidence: f32,
+    /// Maintained automatically as the mirror of the target's relationship
+    pub inverse: bool,
+}
+
+/// Origin of a relationship
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelationProvenance {
+    /// Added explicitly by a user or ingestion pipeline
+    #[default]
+    Declared,
+    /// Discovered by pattern analysis
+    Inferred,
 }
 
 /// Types of relationships between chunks
@@ -184,6 +235,34 @@ pub enum RelationType {
     Replaces,
     /// Custom relationship type
     Custom(String),
+    /// Another chunk depends on this one (inverse of `DependsOn`)
+    DependedOnBy,
+    /// Another chunk is a component of this one (inverse of `PartOf`)
+    HasPart,
+    /// Another chunk extends this one (inverse of `Extends`)
+    ExtendedBy,
+    /// Another chunk replaces this one (inverse of `Replaces`)
+    ReplacedBy,
+}
+
+impl RelationType {
+    /// Relationship seen from the target's side, if it has one
+    ///
+    /// Symmetric relationships are their own inverse; custom ones have none.
+    pub fn inverse(&self) -> Option<RelationType> {
+        match self {
+            RelationType::DependsOn => Some(RelationType::DependedOnBy),
+            RelationType::DependedOnBy => Some(RelationType::DependsOn),
+            RelationType::PartOf => Some(RelationType::HasPart),
+            RelationType::HasPart => Some(RelationType::PartOf),
+            RelationType::Extends => Some(RelationType::ExtendedBy),
+            RelationType::ExtendedBy => Some(RelationType::Extends),
+            RelationType::Replaces => Some(RelationType::ReplacedBy),
+            RelationType::ReplacedBy => Some(RelationType::Replaces),
+            RelationType::Similar | RelationType::Alternative | RelationType::UsedWith => Some(self.clone()),
+            RelationType::Custom(_) => None,
+        }
+    }
 }
 
 /// Main learning chunk structure
@@ -336,9 +415,48 @@ impl LearningChunk {
             relation_type,
             strength,
             metadata: HashMap::new(),
+            provenance: RelationProvenance::Declared,
+            confidence: 1.0,
+            inverse: false,
         });
     }
 
+    /// Add or replace a relationship discovered by pattern analysis
+    pub fn add_inferred_relationship(&mut self, target: ChunkId, relation_type: RelationType, strength: f32, confidence: f32) {
+        self.set_relation(ChunkRelation {
+            target,
+            relation_type,
+            strength,
+            metadata: HashMap::new(),
+            provenance: RelationProvenance::Inferred,
+            confidence,
+            inverse: false,
+        });
+    }
+
+    /// Get the relationship of a type to a target
+    pub fn relation(&self, target: &ChunkId, relation_type: &RelationType) -> Option<&ChunkRelation> {
+        self.relationships.iter()
+            .find(|r| &r.target == target && &r.relation_type == relation_type)
+    }
+
+    /// Insert a relationship, replacing any existing one with the same target and type
+    pub fn set_relation(&mut self, relation: ChunkRelation) {
+        match self.relationships.iter_mut()
+            .find(|r| r.target == relation.target && r.relation_type == relation.relation_type)
+        {
+            Some(existing) => *existing = relation,
+            None => self.relationships.push(relation),
+        }
+    }
+
+    /// Remove every relationship pointing at a chunk, returning how many were removed
+    pub fn remove_relations_to(&mut self, target: &ChunkId) -> usize {
+        let before = self.relationships.len();
+        self.relationships.retain(|r| &r.target != target);
+        before - self.relationships.len()
+    }
+
     /// Get all chunks this chunk depends on
     pub fn get_dependencies(&self) -> Vec<&ChunkId> {
         self.relationships.iter()
@@ -499,6 +617,28 @@ mod tests {
         assert_eq!(chunk.get_similar().len(), 1);
     }
 
+    #[test]
+    fn test_inferred_relationships_and_inverses() {
+        let mut chunk = LearningChunk::default();
+        let target = ChunkId::new("similar");
+
+        chunk.add_inferred_relationship(target.clone(), RelationType::Similar, 0.7, 0.7);
+        chunk.add_inferred_relationship(target.clone(), RelationType::Similar, 0.9, 0.8);
+        assert_eq!(chunk.relationships.len(), 1);
+
+        let relation = chunk.relation(&target, &RelationType::Similar).unwrap();
+        assert_eq!(relation.provenance, RelationProvenance::Inferred);
+        assert_eq!(relation.confidence, 0.8);
+
+        assert_eq!(RelationType::DependsOn.inverse(), Some(RelationType::DependedOnBy));
+        assert_eq!(RelationType::HasPart.inverse(), Some(RelationType::PartOf));
+        assert_eq!(RelationType::Similar.inverse(), Some(RelationType::Similar));
+        assert_eq!(RelationType::Custom("x".to_string()).inverse(), None);
+
+        assert_eq!(chunk.remove_relations_to(&target), 1);
+        assert!(chunk.relationships.is_empty());
+    }
+
     #[test]
     fn test_framework_detection() {
         let chunk = LearningChunk::new(
@@ -514,4 +654,24 @@ mod tests {
         assert!(chunk.is_framework_related("nextjs"));
         assert!(!chunk.is_framework_related("vue"));
     }
+
+    #[test]
+    fn test_json_values_round_trip_through_bincode() {
+        let mut chunk = LearningChunk {
+            content: ChunkContent::Data {
+                format: "json".to_string(),
+                data: serde_json::json!({ "files": [{ "path": "a.ts" }] }),
+            },
+            ..Default::default()
+        };
+        chunk.metadata.properties.insert("unit_kind".to_string(), serde_json::json!("route"));
+
+        let bytes = bincode::serialize(&chunk).unwrap();
+        let decoded: LearningChunk = bincode::deserialize(&bytes).unwrap();
+        assert!(matches!(&decoded.content, ChunkContent::Data { data, .. } if data["files"][0]["path"] == "a.ts"));
+        assert_eq!(decoded.metadata.properties["unit_kind"], "route");
+
+        let json = serde_json::to_value(&chunk).unwrap();
+        assert_eq!(json["metadata"]["properties"]["unit_kind"], "route");
+    }
 }
\ No newline at end of file
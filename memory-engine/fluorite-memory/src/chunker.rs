@@ -0,0 +1,820 @@
+//! Sub-chunking of code into semantic units
+//!
+//! Whole files and Spike templates are too coarse for retrieval when only
+//! one function matters. The chunker splits code into top-level units —
+//! functions, classes and other type definitions, UI components and route
+//! handlers — and turns each into a child [`LearningChunk`] with a `PartOf`
+//! relationship to the chunk it came from. Spike templates are first split
+//! into one child per file, whose units in turn become children of the file.
+//!
+//! Children inherit the parent's metadata, record the file and the 1-based,
+//! inclusive `line_range` of the unit and carry `unit_kind` and `unit_name`
+//! properties. Their IDs extend the parent's (`spike-auth#src/api.ts#login`)
+//! so re-splitting a changed parent yields the same IDs for unchanged units.
+//!
+//! Detection is line based rather than a full parse: brace-delimited
+//! languages are split at top-level declarations and matched brackets,
+//! Python by indentation and Ruby by indentation and `end`. Leading comments,
+//! doc comments, attributes and decorators stay with the unit they precede.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{ChunkContent, ChunkId, ChunkType, LearningChunk, RelationType};
+
+/// Property recording the kind of unit a child chunk holds
+pub const UNIT_KIND_KEY: &str = "unit_kind";
+
+/// Property recording the name of the unit a child chunk holds
+pub const UNIT_NAME_KEY: &str = "unit_name";
+
+/// Property marking a chunk stored together with the parts it splits into
+pub const SPLIT_KEY: &str = "split_into_parts";
+
+/// Separator between a parent chunk ID and the part of a child ID naming the unit
+pub const PART_ID_SEPARATOR: char = '#';
+
+/// HTTP methods recognized as route handler names and route registrations
+const HTTP_METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+/// Configuration for sub-chunking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkerConfig {
+    /// Units shorter than this many lines are not stored separately
+    pub min_unit_lines: usize,
+    /// Maximum number of units taken from one file
+    pub max_units_per_file: usize,
+}
+
+impl Default for ChunkerConfig {
+    fn default() -> Self {
+        Self {
+            min_unit_lines: 1,
+            max_units_per_file: 200,
+        }
+    }
+}
+
+/// Kind of semantic unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CodeUnitKind {
+    /// A whole file of a multi-file template
+    File,
+    /// Function or method
+    Function,
+    /// Class, struct, interface or other type definition
+    Class,
+    /// UI component
+    Component,
+    /// Route or request handler
+    Route,
+}
+
+impl CodeUnitKind {
+    /// Name used in the `unit_kind` property
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Function => "function",
+            Self::Class => "class",
+            Self::Component => "component",
+            Self::Route => "route",
+        }
+    }
+
+    /// Chunk type of a child chunk holding a unit of this kind
+    pub fn chunk_type(&self) -> ChunkType {
+        match self {
+            Self::File | Self::Function | Self::Class => ChunkType::Pattern,
+            Self::Component => ChunkType::Component,
+            Self::Route => ChunkType::ApiIntegration,
+        }
+    }
+}
+
+/// A semantic unit found in a piece of code
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeUnit {
+    pub kind: CodeUnitKind,
+    pub name: String,
+    /// First line of the unit, 1-based
+    pub start_line: u32,
+    /// Last line of the unit, inclusive
+    pub end_line: u32,
+    pub code: String,
+}
+
+/// Language of a file, by extension
+pub fn language_for_path(path: &str) -> Option<&'static str> {
+    let extension = path.rsplit_once('.')?.1.to_ascii_lowercase();
+    let language = match extension.as_str() {
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "php" => "php",
+        "py" => "python",
+        "rb" => "ruby",
+        "rs" => "rust",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "cs" => "csharp",
+        "dart" => "dart",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "hpp" => "cpp",
+        _ => return None,
+    };
+    Some(language)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Braces,
+    Python,
+    Ruby,
+}
+
+fn syntax(language: &str) -> Option<Syntax> {
+    match language.to_ascii_lowercase().as_str() {
+        "python" | "py" => Some(Syntax::Python),
+        "ruby" | "rb" => Some(Syntax::Ruby),
+        "typescript" | "ts" | "tsx" | "javascript" | "js" | "jsx" | "vue" | "svelte" | "php" | "rust" | "go"
+        | "java" | "kotlin" | "swift" | "csharp" | "c#" | "dart" | "c" | "cpp" | "c++" => Some(Syntax::Braces),
+        _ => None,
+    }
+}
+
+fn is_jsx_language(language: &str) -> bool {
+    matches!(
+        language.to_ascii_lowercase().as_str(),
+        "typescript" | "ts" | "tsx" | "javascript" | "js" | "jsx" | "vue" | "svelte"
+    )
+}
+
+/// Split code into its top-level semantic units
+///
+/// Returns nothing for languages the chunker does not know.
+pub fn split_code(code: &str, language: &str) -> Vec<CodeUnit> {
+    let Some(syntax) = syntax(language) else {
+        return Vec::new();
+    };
+
+    let lines: Vec<&str> = code.lines().collect();
+    let spans = match syntax {
+        Syntax::Braces => braced_units(&lines, is_jsx_language(language)),
+        Syntax::Python => indented_units(&lines, Syntax::Python),
+        Syntax::Ruby => indented_units(&lines, Syntax::Ruby),
+    };
+
+    spans.into_iter()
+        .map(|(kind, name, start, end)| CodeUnit {
+            kind,
+            name,
+            start_line: start as u32 + 1,
+            end_line: end as u32 + 1,
+            code: lines[start..=end].join("\n"),
+        })
+        .collect()
+}
+
+// (kind, name, first line, last line), 0-based and inclusive
+type Span = (CodeUnitKind, String, usize, usize);
+
+fn regexes() -> &'static [(CodeUnitKind, Regex)] {
+    static REGEXES: OnceLock<Vec<(CodeUnitKind, Regex)>> = OnceLock::new();
+    REGEXES.get_or_init(|| {
+        let compile = |pattern: &str| Regex::new(pattern).expect("unit pattern is valid");
+        vec![
+            // app.get('/path', ...), router.post(...), Route::get('/path', ...)
+            (CodeUnitKind::Route, compile(
+                r#"^(?:[\w$]+\.)*(?i:app|router|route|server|fastify|api)\s*(?:\.|::)\s*(get|post|put|patch|delete|head|options|all|any|match|resource|apiResource)\s*\(\s*['"`]([^'"`]*)['"`]"#,
+            )),
+            (CodeUnitKind::Class, compile(
+                r"^(?:export\s+)?(?:default\s+)?(?:(?:pub(?:\([^)]*\))?|public|private|protected|internal|abstract|final|sealed|open|data|static|partial|declare)\s+)*(?:class|interface|struct|enum|trait|object|record|protocol)\s+([A-Za-z_$][\w$]*)",
+            )),
+            (CodeUnitKind::Class, compile(r"^impl(?:<[^>]*>)?\s+([^{]+?)\s*(?:where\b[^{]*)?\{?\s*$")),
+            (CodeUnitKind::Function, compile(
+                r"^(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*&?\s*([A-Za-z_$][\w$]*)",
+            )),
+            (CodeUnitKind::Function, compile(
+                r"^(?:export\s+)?(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:(?:\([^)]*\)|[A-Za-z_$][\w$]*)\s*(?::[^=]+)?=>|\(\s*$|function\b)",
+            )),
+            (CodeUnitKind::Function, compile(
+                r"^(?:(?:public|private|protected|static|final|abstract)\s+)*function\s+&?([A-Za-z_]\w*)",
+            )),
+            (CodeUnitKind::Function, compile(
+                r#"^(?:pub(?:\([^)]*\))?\s+)?(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?(?:extern\s+"[^"]*"\s+)?fn\s+([A-Za-z_]\w*)"#,
+            )),
+            (CodeUnitKind::Function, compile(r"^func\s+(?:\([^)]*\)\s*)?([A-Za-z_]\w*)")),
+            (CodeUnitKind::Function, compile(
+                r"^(?:(?:public|private|internal|protected|open|override|static|suspend|inline)\s+)*fun\s+(?:<[^>]*>\s*)?([A-Za-z_]\w*)",
+            )),
+        ]
+    })
+}
+
+fn class_component() -> &'static Regex {
+    static COMPONENT: OnceLock<Regex> = OnceLock::new();
+    COMPONENT.get_or_init(|| Regex::new(r"\bextends\s+(?:React\.)?(?:Pure)?Component\b").expect("valid"))
+}
+
+/// Kind and name of a unit starting on a top-level line of brace-delimited code
+fn detect_braced_unit(line: &str, jsx: bool) -> Option<(CodeUnitKind, String)> {
+    let line = line.trim_start();
+    for (kind, regex) in regexes() {
+        let Some(captures) = regex.captures(line) else {
+            continue;
+        };
+
+        let unit = match kind {
+            CodeUnitKind::Route => {
+                let method = captures[1].to_uppercase();
+                (CodeUnitKind::Route, format!("{} {}", method, &captures[2]))
+            }
+            CodeUnitKind::Class => {
+                let name = captures[1].trim().to_string();
+                let component = jsx && class_component().is_match(line);
+                (if component { CodeUnitKind::Component } else { CodeUnitKind::Class }, name)
+            }
+            _ => {
+                let name = captures[1].to_string();
+                let kind = if HTTP_METHODS.contains(&name.as_str()) {
+                    CodeUnitKind::Route
+                } else if jsx && name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    CodeUnitKind::Component
+                } else {
+                    CodeUnitKind::Function
+                };
+                (kind, name)
+            }
+        };
+        return Some(unit);
+    }
+    None
+}
+
+/// Lexer state carried across lines of brace-delimited code
+#[derive(Debug, Clone, Copy, Default)]
+struct Lexer {
+    // Quote character of an open string literal that may span lines (template literal)
+    string: Option<char>,
+    block_comment: bool,
+}
+
+impl Lexer {
+    /// Bracket depth change over a line, and whether it opened a brace
+    fn scan(&mut self, line: &str) -> (i32, bool) {
+        let mut delta = 0;
+        let mut opened_brace = false;
+        let mut chars = line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if self.block_comment {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    self.block_comment = false;
+                }
+                continue;
+            }
+            if let Some(quote) = self.string {
+                if c == '\\' {
+                    chars.next();
+                } else if c == quote {
+                    self.string = None;
+                }
+                continue;
+            }
+
+            match c {
+                '/' if chars.peek() == Some(&'/') => break,
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    self.block_comment = true;
+                }
+                '"' | '\'' | '`' => self.string = Some(c),
+                '{' => {
+                    delta += 1;
+                    opened_brace = true;
+                }
+                '(' | '[' => delta += 1,
+                '}' | ')' | ']' => delta -= 1,
+                _ => {}
+            }
+        }
+
+        // Only template literals span lines
+        if matches!(self.string, Some('"') | Some('\'')) {
+            self.string = None;
+        }
+        (delta, opened_brace)
+    }
+}
+
+fn is_comment_or_annotation(line: &str, syntax: Syntax) -> bool {
+    let line = line.trim_start();
+    match syntax {
+        Syntax::Braces => {
+            line.starts_with("//") || line.starts_with("/*") || line.starts_with('*')
+                || line.starts_with('@') || line.starts_with("#[")
+        }
+        Syntax::Python => line.starts_with('#') || line.starts_with('@'),
+        Syntax::Ruby => line.starts_with('#'),
+    }
+}
+
+/// Move a unit's start up over the comments and annotations directly above it
+fn leading_start(lines: &[&str], start: usize, floor: usize, syntax: Syntax) -> usize {
+    let mut first = start;
+    while first > floor && is_comment_or_annotation(lines[first - 1], syntax) {
+        first -= 1;
+    }
+    first
+}
+
+fn is_continuation(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.len() != line.len()
+        || [".", "?", ":", "=>", "&&", "||", "+", "-", "*", "{", ")", "]", "}", ","]
+            .iter()
+            .any(|prefix| trimmed.starts_with(prefix))
+}
+
+fn braced_units(lines: &[&str], jsx: bool) -> Vec<Span> {
+    let mut units = Vec::new();
+    let mut lexer = Lexer::default();
+    let mut depth = 0;
+    let mut floor = 0;
+    let mut index = 0;
+
+    while index < lines.len() {
+        let detected = (depth == 0 && lexer.string.is_none() && !lexer.block_comment)
+            .then(|| detect_braced_unit(lines[index], jsx))
+            .flatten();
+        let Some((kind, name)) = detected else {
+            depth = (depth + lexer.scan(lines[index]).0).max(0);
+            index += 1;
+            continue;
+        };
+
+        let start = leading_start(lines, index, floor, Syntax::Braces);
+        let mut unit_depth = 0;
+        let mut opened_brace = false;
+        let mut end = index;
+        for (offset, line) in lines[index..].iter().enumerate() {
+            let (delta, opened) = lexer.scan(line);
+            unit_depth += delta;
+            opened_brace |= opened;
+            end = index + offset;
+
+            if unit_depth > 0 || lexer.string.is_some() || lexer.block_comment {
+                continue;
+            }
+            if opened_brace || line.trim_end().ends_with(';') {
+                break;
+            }
+            // An expression without braces ends where the next line stops continuing it
+            match lines.get(end + 1) {
+                Some(next) if !next.trim().is_empty() && is_continuation(next) => {}
+                _ => break,
+            }
+        }
+
+        units.push((kind, name, start, end));
+        depth = 0;
+        floor = end + 1;
+        index = end + 1;
+    }
+    units
+}
+
+fn detect_indented_unit(line: &str, syntax: Syntax) -> Option<(CodeUnitKind, String)> {
+    static PYTHON: OnceLock<[Regex; 2]> = OnceLock::new();
+    static RUBY: OnceLock<[Regex; 3]> = OnceLock::new();
+
+    match syntax {
+        Syntax::Python => {
+            let [function, class] = PYTHON.get_or_init(|| [
+                Regex::new(r"^(?:async\s+)?def\s+([A-Za-z_]\w*)").expect("valid"),
+                Regex::new(r"^class\s+([A-Za-z_]\w*)").expect("valid"),
+            ]);
+            if let Some(captures) = function.captures(line) {
+                return Some((CodeUnitKind::Function, captures[1].to_string()));
+            }
+            class.captures(line).map(|captures| (CodeUnitKind::Class, captures[1].to_string()))
+        }
+        Syntax::Ruby => {
+            let [function, class, route] = RUBY.get_or_init(|| [
+                Regex::new(r"^def\s+((?:self\.)?[A-Za-z_]\w*[?!=]?)").expect("valid"),
+                Regex::new(r"^(?:class|module)\s+([A-Z][\w:]*)").expect("valid"),
+                Regex::new(r#"^(get|post|put|patch|delete)\s+['"]([^'"]*)['"].*\bdo\b"#).expect("valid"),
+            ]);
+            if let Some(captures) = function.captures(line) {
+                return Some((CodeUnitKind::Function, captures[1].to_string()));
+            }
+            if let Some(captures) = class.captures(line) {
+                return Some((CodeUnitKind::Class, captures[1].to_string()));
+            }
+            route.captures(line)
+                .map(|captures| (CodeUnitKind::Route, format!("{} {}", captures[1].to_uppercase(), &captures[2])))
+        }
+        Syntax::Braces => None,
+    }
+}
+
+/// Route of a Python decorator such as `@app.get("/items")`
+fn python_route(decorator: &str) -> Option<String> {
+    static ROUTE: OnceLock<Regex> = OnceLock::new();
+    let route = ROUTE.get_or_init(|| {
+        Regex::new(r#"^@\w+\.(get|post|put|patch|delete|route|api_route)\s*\(\s*['"]([^'"]*)['"]"#).expect("valid")
+    });
+    route.captures(decorator.trim()).map(|captures| {
+        let method = match &captures[1] {
+            "route" | "api_route" => "ANY".to_string(),
+            method => method.to_uppercase(),
+        };
+        format!("{} {}", method, &captures[2])
+    })
+}
+
+fn indented_units(lines: &[&str], syntax: Syntax) -> Vec<Span> {
+    let mut units = Vec::new();
+    let mut floor = 0;
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        let top_level = !line.starts_with(char::is_whitespace);
+        let Some((mut kind, mut name)) = top_level.then(|| detect_indented_unit(line, syntax)).flatten() else {
+            index += 1;
+            continue;
+        };
+
+        let start = leading_start(lines, index, floor, syntax);
+        if let Some(route) = lines[start..index].iter().find_map(|decorator| python_route(decorator)) {
+            kind = CodeUnitKind::Route;
+            name = route;
+        }
+
+        // The unit runs until the next top-level line; Ruby's closing `end` belongs to it
+        let mut end = index;
+        for (offset, next) in lines[index + 1..].iter().enumerate() {
+            if next.trim().is_empty() {
+                continue;
+            }
+            if !next.starts_with(char::is_whitespace) {
+                if syntax == Syntax::Ruby && next.trim_end() == "end" {
+                    end = index + 1 + offset;
+                }
+                break;
+            }
+            end = index + 1 + offset;
+        }
+
+        units.push((kind, name, start, end));
+        floor = end + 1;
+        index = end + 1;
+    }
+    units
+}
+
+/// Splits chunks into child chunks for their files and code units
+#[derive(Debug, Clone, Default)]
+pub struct CodeChunker {
+    config: ChunkerConfig,
+}
+
+impl CodeChunker {
+    /// Create a chunker with the given configuration
+    pub fn new(config: ChunkerConfig) -> Self {
+        Self { config }
+    }
+
+    /// Child chunks of a chunk
+    ///
+    /// Code chunks are split into units. Data chunks holding a Spike template
+    /// (a `files` array of `path` plus `content` or `template`) get one child
+    /// per file and grandchildren for the units of each file. Other content
+    /// has no children.
+    pub fn split(&self, chunk: &LearningChunk) -> Vec<LearningChunk> {
+        match &chunk.content {
+            ChunkContent::Code { language, code, framework } => {
+                let offset = chunk.metadata.line_range.map_or(0, |(start, _)| start.saturating_sub(1));
+                let file_path = chunk.metadata.file_path.clone();
+                self.unit_chunks(chunk, code, language, framework.clone(), file_path, offset)
+            }
+            ChunkContent::Data { data, .. } => {
+                let Some(files) = data.get("files").and_then(|files| files.as_array()) else {
+                    return Vec::new();
+                };
+
+                let mut children = Vec::new();
+                for file in files {
+                    let Some(path) = file.get("path").and_then(|path| path.as_str()) else {
+                        continue;
+                    };
+                    let Some(code) = ["content", "template"].iter()
+                        .find_map(|key| file.get(*key).and_then(|value| value.as_str()))
+                    else {
+                        continue;
+                    };
+                    let language = file.get("language")
+                        .and_then(|language| language.as_str())
+                        .map(str::to_string)
+                        .or_else(|| language_for_path(path).map(str::to_string))
+                        .unwrap_or_else(|| "text".to_string());
+
+                    let framework = chunk.metadata.frameworks.first().cloned();
+                    let lines = code.lines().count().max(1) as u32;
+                    let file_chunk = child_chunk(
+                        chunk,
+                        path,
+                        CodeUnitKind::File,
+                        ChunkContent::Code {
+                            language: language.clone(),
+                            code: code.to_string(),
+                            framework: framework.clone(),
+                        },
+                        Some(path.to_string()),
+                        (1, lines),
+                    );
+
+                    let units = self.unit_chunks(&file_chunk, code, &language, framework, Some(path.to_string()), 0);
+                    children.push(file_chunk);
+                    children.extend(units);
+                }
+                children
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn unit_chunks(
+        &self,
+        parent: &LearningChunk,
+        code: &str,
+        language: &str,
+        framework: Option<String>,
+        file_path: Option<String>,
+        line_offset: u32,
+    ) -> Vec<LearningChunk> {
+        let mut units = split_code(code, language);
+
+        // A unit spanning the whole file would only duplicate its parent
+        let content_lines = code.lines().filter(|line| !line.trim().is_empty()).count();
+        if let [unit] = units.as_slice() {
+            let unit_lines = unit.code.lines().filter(|line| !line.trim().is_empty()).count();
+            if unit_lines == content_lines {
+                return Vec::new();
+            }
+        }
+
+        units.retain(|unit| (unit.end_line - unit.start_line + 1) as usize >= self.config.min_unit_lines);
+        units.truncate(self.config.max_units_per_file);
+
+        let mut used = HashSet::new();
+        units.into_iter()
+            .map(|unit| {
+                let mut part = unit.name.clone();
+                if !used.insert(part.clone()) {
+                    part = format!("{}~{}", unit.name, unit.start_line);
+                    used.insert(part.clone());
+                }
+
+                let mut child = child_chunk(
+                    parent,
+                    &part,
+                    unit.kind,
+                    ChunkContent::Code {
+                        language: language.to_string(),
+                        code: unit.code,
+                        framework: framework.clone(),
+                    },
+                    file_path.clone(),
+                    (unit.start_line + line_offset, unit.end_line + line_offset),
+                );
+                child.metadata.properties.insert(UNIT_NAME_KEY.to_string(), serde_json::Value::from(unit.name));
+                child
+            })
+            .collect()
+    }
+}
+
+/// Check whether a chunk ID names a part split from `parent`
+pub fn is_part_id(parent: &ChunkId, id: &ChunkId) -> bool {
+    id.as_str().strip_prefix(parent.as_str())
+        .is_some_and(|rest| rest.starts_with(PART_ID_SEPARATOR))
+}
+
+/// Mark a chunk as stored together with its parts, see [`is_split`]
+pub fn mark_split(chunk: &mut LearningChunk) {
+    chunk.metadata.properties.insert(SPLIT_KEY.to_string(), serde_json::Value::Bool(true));
+}
+
+/// Check whether a chunk was stored together with its parts
+///
+/// Chunks stored before [`SPLIT_KEY`] was recorded are recognised by their
+/// `HasPart` edges to their parts.
+pub fn is_split(chunk: &LearningChunk) -> bool {
+    chunk.metadata.properties.get(SPLIT_KEY).and_then(serde_json::Value::as_bool).unwrap_or(false)
+        || chunk.relationships.iter().any(|relation| {
+            relation.relation_type == RelationType::HasPart && is_part_id(&chunk.id, &relation.target)
+        })
+}
+
+fn child_chunk(
+    parent: &LearningChunk,
+    part: &str,
+    kind: CodeUnitKind,
+    content: ChunkContent,
+    file_path: Option<String>,
+    line_range: (u32, u32),
+) -> LearningChunk {
+    let mut metadata = parent.metadata.clone();
+    metadata.usage_count = 0;
+    metadata.file_path = file_path;
+    metadata.line_range = Some(line_range);
+    metadata.properties.insert(UNIT_KIND_KEY.to_string(), serde_json::Value::from(kind.as_str()));
+    if kind == CodeUnitKind::File {
+        metadata.properties.insert(UNIT_NAME_KEY.to_string(), serde_json::Value::from(part));
+    }
+
+    let mut child = LearningChunk {
+        id: ChunkId::new(&format!("{}{}{}", parent.id, PART_ID_SEPARATOR, part)),
+        chunk_type: kind.chunk_type(),
+        content,
+        metadata,
+        embedding: None,
+        relationships: Vec::new(),
+        quality_score: parent.quality_score,
+    };
+    child.add_relationship(parent.id.clone(), RelationType::PartOf, 1.0);
+    child
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::ChunkMetadata;
+
+    const ROUTES_TS: &str = "import express from 'express';
+
+const app = express();
+
+/** Health check */
+app.get('/health', (req, res) => {
+  res.json({ ok: true, note: '}' });
+});
+
+export async function GET(request: Request) {
+  return Response.json([]);
+}
+
+export const UserCard = ({ user }: Props) => (
+  <div className=\"card\">{user.name}</div>
+);
+
+export class UserService {
+  find(id: string) {
+    return `user-${id}`;
+  }
+}
+
+const add = (a: number, b: number) =>
+  a + b;
+";
+
+    fn units(code: &str, language: &str) -> Vec<(CodeUnitKind, String, u32, u32)> {
+        split_code(code, language).into_iter()
+            .map(|unit| (unit.kind, unit.name, unit.start_line, unit.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_split_braced_code() {
+        assert_eq!(units(ROUTES_TS, "typescript"), vec![
+            (CodeUnitKind::Route, "GET /health".to_string(), 5, 8),
+            (CodeUnitKind::Route, "GET".to_string(), 10, 12),
+            (CodeUnitKind::Component, "UserCard".to_string(), 14, 16),
+            (CodeUnitKind::Class, "UserService".to_string(), 18, 22),
+            (CodeUnitKind::Function, "add".to_string(), 24, 25),
+        ]);
+
+        let php = "<?php\n\nclass UserController extends Controller\n{\n    public function show($id)\n    {\n        return User::find($id);\n    }\n}\n\nRoute::get('/users/{id}', [UserController::class, 'show']);\n";
+        assert_eq!(units(php, "php"), vec![
+            (CodeUnitKind::Class, "UserController".to_string(), 3, 9),
+            (CodeUnitKind::Route, "GET /users/{id}".to_string(), 11, 11),
+        ]);
+
+        let rust = "/// Adds one\n#[inline]\npub fn inc(x: u32) -> u32 {\n    x + 1\n}\n\nimpl Display for Id {\n    fn fmt(&self) {}\n}\n";
+        assert_eq!(units(rust, "rust"), vec![
+            (CodeUnitKind::Function, "inc".to_string(), 1, 5),
+            (CodeUnitKind::Class, "Display for Id".to_string(), 7, 9),
+        ]);
+    }
+
+    #[test]
+    fn test_split_indented_code() {
+        let python = "import os\n\n@app.get(\"/items\")\nasync def list_items():\n    return []\n\n\nclass Item:\n    name: str\n\n    def label(self):\n        return self.name\n";
+        assert_eq!(units(python, "python"), vec![
+            (CodeUnitKind::Route, "GET /items".to_string(), 3, 5),
+            (CodeUnitKind::Class, "Item".to_string(), 8, 12),
+        ]);
+
+        let ruby = "# Greets\ndef hello(name)\n  \"Hello #{name}\"\nend\n\nget '/hi' do\n  hello('you')\nend\n";
+        assert_eq!(units(ruby, "ruby"), vec![
+            (CodeUnitKind::Function, "hello".to_string(), 1, 4),
+            (CodeUnitKind::Route, "GET /hi".to_string(), 6, 8),
+        ]);
+
+        assert!(split_code("key: value", "yaml").is_empty());
+    }
+
+    #[test]
+    fn test_split_chunks_inherit_metadata() {
+        let parent = LearningChunk {
+            id: ChunkId::new("routes"),
+            content: ChunkContent::Code {
+                language: "typescript".to_string(),
+                code: ROUTES_TS.to_string(),
+                framework: Some("express".to_string()),
+            },
+            metadata: ChunkMetadata {
+                frameworks: vec!["express".to_string()],
+                tags: vec!["api".to_string()],
+                file_path: Some("src/routes.ts".to_string()),
+                line_range: Some((11, 40)),
+                ..Default::default()
+            },
+            quality_score: 0.7,
+            ..Default::default()
+        };
+
+        let children = CodeChunker::default().split(&parent);
+        assert_eq!(children.len(), 5);
+
+        let route = &children[0];
+        assert_eq!(route.id.as_str(), "routes#GET /health");
+        assert_eq!(route.chunk_type, ChunkType::ApiIntegration);
+        assert_eq!(route.metadata.line_range, Some((15, 18)));
+        assert_eq!(route.metadata.file_path.as_deref(), Some("src/routes.ts"));
+        assert_eq!(route.metadata.tags, parent.metadata.tags);
+        assert_eq!(route.metadata.properties[UNIT_KIND_KEY], "route");
+        assert_eq!(route.quality_score, 0.7);
+        assert!(route.relation(&parent.id, &RelationType::PartOf).is_some());
+        assert!(is_part_id(&parent.id, &route.id));
+        assert!(!is_part_id(&parent.id, &ChunkId::new("routes-other")));
+
+        let min_lines = CodeChunker::new(ChunkerConfig { min_unit_lines: 3, ..Default::default() });
+        assert_eq!(min_lines.split(&parent).len(), 4);
+    }
+
+    #[test]
+    fn test_split_spike_template() {
+        let spike = LearningChunk {
+            id: ChunkId::new("spike-adonis-route"),
+            chunk_type: ChunkType::SpikeTemplate,
+            content: ChunkContent::Data {
+                format: "json".to_string(),
+                data: serde_json::json!({
+                    "name": "adonis-route",
+                    "files": [
+                        { "path": "start/routes.ts", "template": "import Route from '@ioc:Adonis/Core/Route';\nRoute.get('/hello', async () => {\n  return { ok: true };\n});\n\nfunction helper() {\n  return 1;\n}\n" },
+                        { "path": "README.md", "content": "# Routes" },
+                    ],
+                }),
+            },
+            metadata: ChunkMetadata {
+                frameworks: vec!["adonis".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let children = CodeChunker::default().split(&spike);
+        let ids: Vec<&str> = children.iter().map(|child| child.id.as_str()).collect();
+        assert_eq!(ids, [
+            "spike-adonis-route#start/routes.ts",
+            "spike-adonis-route#start/routes.ts#GET /hello",
+            "spike-adonis-route#start/routes.ts#helper",
+            "spike-adonis-route#README.md",
+        ]);
+
+        let file = &children[0];
+        assert_eq!(file.metadata.line_range, Some((1, 8)));
+        assert!(file.relation(&spike.id, &RelationType::PartOf).is_some());
+        assert!(matches!(&file.content, ChunkContent::Code { language, .. } if language == "typescript"));
+
+        let route = &children[1];
+        assert_eq!(route.metadata.line_range, Some((2, 4)));
+        assert_eq!(route.metadata.file_path.as_deref(), Some("start/routes.ts"));
+        assert!(route.relation(&file.id, &RelationType::PartOf).is_some());
+        assert!(is_part_id(&spike.id, &route.id));
+    }
+}
@@ -0,0 +1,242 @@
+//! Framework co-occurrence mining
+//!
+//! Every chunk contributes its set of frameworks. Each subset of two or more
+//! frameworks (up to [`MAX_COMBINATION_SIZE`]) is counted once per chunk, so a
+//! combination shows up however many other frameworks its chunks also use.
+//! Combinations are scored with the usual association measures:
+//!
+//! - support: share of framework-tagged chunks that use every framework in
+//!   the combination
+//! - confidence: all-confidence, the support divided by the support of its
+//!   most common member, i.e. how often the combination appears when its
+//!   most popular framework does
+//! - lift: support relative to what independent use of the frameworks would
+//!   give; above 1.0 the frameworks are used together more than chance
+
+use std::collections::{HashMap, HashSet};
+
+use crate::chunk::ChunkId;
+use crate::patterns::FrameworkCombination;
+
+/// Largest framework combination that is mined
+///
+/// A chunk with `n` frameworks contributes to every subset of them, which
+/// grows exponentially; larger combinations are too rare to be useful.
+pub const MAX_COMBINATION_SIZE: usize = 4;
+
+/// Co-occurrence counts of frameworks across chunks
+#[derive(Debug, Default, Clone)]
+pub struct FrameworkCooccurrence {
+    /// Sorted, deduplicated frameworks of each framework-tagged chunk
+    chunk_frameworks: HashMap<ChunkId, Vec<String>>,
+    /// Number of chunks using each framework
+    framework_counts: HashMap<String, usize>,
+    /// Chunks using every framework of a sorted combination
+    combinations: HashMap<Vec<String>, HashSet<ChunkId>>,
+}
+
+impl FrameworkCooccurrence {
+    /// Create an empty miner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the frameworks of a chunk, replacing what was recorded before
+    pub fn insert(&mut self, chunk_id: &ChunkId, frameworks: &[String]) {
+        let mut frameworks = frameworks.to_vec();
+        frameworks.sort();
+        frameworks.dedup();
+
+        if self.chunk_frameworks.get(chunk_id) == Some(&frameworks) {
+            return;
+        }
+        self.remove(chunk_id);
+        if frameworks.is_empty() {
+            return;
+        }
+
+        for framework in &frameworks {
+            *self.framework_counts.entry(framework.clone()).or_default() += 1;
+        }
+        for combination in subsets(&frameworks) {
+            self.combinations.entry(combination).or_default().insert(chunk_id.clone());
+        }
+        self.chunk_frameworks.insert(chunk_id.clone(), frameworks);
+    }
+
+    /// Forget a chunk, returning whether it was recorded
+    pub fn remove(&mut self, chunk_id: &ChunkId) -> bool {
+        let Some(frameworks) = self.chunk_frameworks.remove(chunk_id) else {
+            return false;
+        };
+
+        for framework in &frameworks {
+            if let Some(count) = self.framework_counts.get_mut(framework) {
+                *count -= 1;
+                if *count == 0 {
+                    self.framework_counts.remove(framework);
+                }
+            }
+        }
+        for combination in subsets(&frameworks) {
+            if let Some(chunks) = self.combinations.get_mut(&combination) {
+                chunks.remove(chunk_id);
+                if chunks.is_empty() {
+                    self.combinations.remove(&combination);
+                }
+            }
+        }
+        true
+    }
+
+    /// Number of chunks using at least one framework
+    pub fn chunk_count(&self) -> usize {
+        self.chunk_frameworks.len()
+    }
+
+    /// Number of distinct combinations of two or more frameworks
+    pub fn len(&self) -> usize {
+        self.combinations.len()
+    }
+
+    /// Check whether no combination has been seen
+    pub fn is_empty(&self) -> bool {
+        self.combinations.is_empty()
+    }
+
+    /// Combinations containing all of `frameworks` with at least `min_support`
+    ///
+    /// An empty `frameworks` slice matches every combination. Framework names
+    /// are compared case-insensitively. Results are ordered by lift, then
+    /// support, then name.
+    pub fn combinations<S: AsRef<str>>(&self, frameworks: &[S], min_support: f32) -> Vec<FrameworkCombination> {
+        let total = self.chunk_count();
+        if total == 0 {
+            return Vec::new();
+        }
+
+        let mut results: Vec<FrameworkCombination> = self.combinations.iter()
+            .filter(|(combination, _)| {
+                frameworks.iter().all(|wanted| {
+                    combination.iter().any(|framework| framework.eq_ignore_ascii_case(wanted.as_ref()))
+                })
+            })
+            .filter_map(|(combination, chunks)| {
+                let support = share(chunks.len(), total);
+                if support < min_support {
+                    return None;
+                }
+
+                let member_supports: Vec<f32> = combination.iter()
+                    .map(|framework| share(self.framework_counts.get(framework).copied().unwrap_or(0), total))
+                    .collect();
+                let max_member = member_supports.iter().copied().fold(0.0f32, f32::max);
+                let expected: f32 = member_supports.iter().product();
+
+                let mut chunks: Vec<ChunkId> = chunks.iter().cloned().collect();
+                chunks.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+                Some(FrameworkCombination {
+                    frameworks: combination.clone(),
+                    pattern_name: format!("{} integration", combination.join("-")),
+                    usage_frequency: chunks.len() as u64,
+                    chunks,
+                    support,
+                    confidence: if max_member > 0.0 { support / max_member } else { 0.0 },
+                    lift: if expected > 0.0 { support / expected } else { 0.0 },
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.lift.total_cmp(&a.lift)
+                .then(b.support.total_cmp(&a.support))
+                .then_with(|| a.frameworks.cmp(&b.frameworks))
+        });
+        results
+    }
+}
+
+fn share(count: usize, total: usize) -> f32 {
+    count as f32 / total as f32
+}
+
+/// All subsets of two to [`MAX_COMBINATION_SIZE`] items, keeping their order
+fn subsets(items: &[String]) -> Vec<Vec<String>> {
+    fn extend(items: &[String], start: usize, current: &mut Vec<String>, out: &mut Vec<Vec<String>>) {
+        if current.len() >= 2 {
+            out.push(current.clone());
+        }
+        if current.len() == MAX_COMBINATION_SIZE {
+            return;
+        }
+        for (index, item) in items.iter().enumerate().skip(start) {
+            current.push(item.clone());
+            extend(items, index + 1, current, out);
+            current.pop();
+        }
+    }
+
+    let mut out = Vec::new();
+    extend(items, 0, &mut Vec::new(), &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frameworks(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_counts_every_subset_once_per_chunk() {
+        let mut miner = FrameworkCooccurrence::new();
+        miner.insert(&ChunkId::new("a"), &frameworks(&["nextjs", "laravel", "tailwind"]));
+        miner.insert(&ChunkId::new("b"), &frameworks(&["laravel", "nextjs"]));
+        miner.insert(&ChunkId::new("c"), &frameworks(&["react"]));
+        // Re-inserting the same chunk does not double count
+        miner.insert(&ChunkId::new("b"), &frameworks(&["nextjs", "laravel", "nextjs"]));
+
+        assert_eq!(miner.chunk_count(), 3);
+        // {laravel, nextjs}, {laravel, tailwind}, {nextjs, tailwind}, {all three}
+        assert_eq!(miner.len(), 4);
+
+        let pairs = miner.combinations(&["NextJS", "laravel"], 0.0);
+        assert_eq!(pairs.len(), 2);
+        let pair = pairs.iter().find(|combination| combination.frameworks.len() == 2).unwrap();
+        assert_eq!(pair.frameworks, frameworks(&["laravel", "nextjs"]));
+        assert_eq!(pair.usage_frequency, 2);
+        assert!((pair.support - 2.0 / 3.0).abs() < 1e-6);
+        assert!((pair.confidence - 1.0).abs() < 1e-6);
+        assert!((pair.lift - 1.5).abs() < 1e-6);
+
+        assert_eq!(miner.combinations(&["nextjs"], 0.5).len(), 1);
+    }
+
+    #[test]
+    fn test_remove_and_replace_update_counts() {
+        let mut miner = FrameworkCooccurrence::new();
+        let chunk = ChunkId::new("a");
+        miner.insert(&chunk, &frameworks(&["nextjs", "laravel"]));
+        miner.insert(&chunk, &frameworks(&["nextjs", "prisma"]));
+
+        assert!(miner.combinations(&["laravel"], 0.0).is_empty());
+        assert_eq!(miner.combinations(&["prisma"], 0.0).len(), 1);
+
+        assert!(miner.remove(&chunk));
+        assert!(!miner.remove(&chunk));
+        assert!(miner.is_empty());
+        assert_eq!(miner.chunk_count(), 0);
+    }
+
+    #[test]
+    fn test_subsets_are_capped() {
+        let items = frameworks(&["a", "b", "c", "d", "e"]);
+        let all = subsets(&items);
+        // C(5,2) + C(5,3) + C(5,4)
+        assert_eq!(all.len(), 10 + 10 + 5);
+        assert!(all.iter().all(|subset| (2..=MAX_COMBINATION_SIZE).contains(&subset.len())));
+    }
+}
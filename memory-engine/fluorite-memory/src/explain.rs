@@ -0,0 +1,104 @@
+//! Ranking explanations for search hits
+//!
+//! Breaks a hit's score down into per-field BM25 contributions taken from
+//! tantivy's `Explanation`, the filters the query applied and the ranking
+//! stages (lexical scoring, fusion, feedback boosting) that produced the final order.
+
+use serde::{Deserialize, Serialize};
+
+use crate::hybrid::FusionStrategy;
+
+/// Why a hit received its score and rank
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HitExplanation {
+    /// Final score of the hit after every stage
+    pub score: f32,
+    /// BM25 contributions of the full-text query per index field, largest first
+    pub fields: Vec<FieldContribution>,
+    /// Filters of the query and how many text matches each removed
+    pub filters: Vec<FilterEffect>,
+    /// Ranking stages applied to the hit, in order
+    pub stages: Vec<RankingStage>,
+    /// Raw tantivy explanation tree of the index query
+    pub tantivy: Option<serde_json::Value>,
+}
+
+impl HitExplanation {
+    /// Sum of the per-field BM25 contributions
+    pub fn bm25_total(&self) -> f32 {
+        self.fields.iter().map(|field| field.score).sum()
+    }
+
+    /// Contribution of a single index field, if it matched
+    pub fn field(&self, name: &str) -> Option<&FieldContribution> {
+        self.fields.iter().find(|field| field.field == name)
+    }
+}
+
+/// BM25 score contributed by one index field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldContribution {
+    /// Index field name
+    pub field: String,
+    /// Total contribution of the field
+    pub score: f32,
+    /// Contributions of the individual terms and phrases, largest first
+    pub terms: Vec<TermContribution>,
+}
+
+/// BM25 score contributed by one term or phrase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TermContribution {
+    /// Indexed term, or the space-separated terms of a phrase
+    pub term: String,
+    /// BM25 score of the term
+    pub score: f32,
+}
+
+/// Effect of an exact filter on the result set
+///
+/// Filters do not change scores; `removed` counts the full-text matches
+/// (or all chunks, without text) that this filter alone rejects.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterEffect {
+    /// Readable form of the filter, e.g. `tag: auth`
+    pub filter: String,
+    /// Whether the filter rejects matching chunks instead of requiring them
+    pub excluded: bool,
+    /// Number of candidates rejected by this filter
+    pub removed: u64,
+}
+
+/// Stage of the ranking pipeline that touched a hit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RankingStage {
+    /// Scored and ranked by the index query
+    Lexical {
+        /// 1-based rank among the index hits
+        rank: usize,
+        /// Index score (BM25 of the text query)
+        score: f32,
+    },
+    /// Lexical and vector rankings combined
+    Fusion {
+        /// Strategy used to combine the legs
+        strategy: FusionStrategy,
+        /// Weighted contribution of the lexical leg
+        lexical: f32,
+        /// Weighted contribution of the vector leg
+        vector: f32,
+        /// Fused score
+        score: f32,
+        /// 1-based rank after fusion
+        rank: usize,
+    },
+    /// Score scaled by the chunk's feedback-adjusted quality
+    Feedback {
+        /// Multiplier applied to the score, 1.0 for chunks without feedback
+        boost: f32,
+        /// Score after the boost
+        score: f32,
+        /// 1-based rank after reranking
+        rank: usize,
+    },
+}
@@ -0,0 +1,434 @@
+//! Knowledge graph export for external visualization tools
+//!
+//! The relationship graph is flattened into typed nodes (chunks, code
+//! patterns, frameworks) and weighted edges, then rendered as GraphML,
+//! Graphviz DOT or a JSON node-link document (`{"nodes": [...], "links": [...]}`,
+//! the layout read by d3 and networkx).
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{ChunkId, RelationType};
+
+/// Relation of a chunk to a framework it uses
+pub const USES_FRAMEWORK: &str = "UsesFramework";
+/// Relation of a chunk to a code pattern found in it
+pub const HAS_PATTERN: &str = "HasPattern";
+
+/// Output format of a graph export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphExportFormat {
+    GraphMl,
+    Dot,
+    Json,
+}
+
+impl std::str::FromStr for GraphExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "graphml" => Ok(Self::GraphMl),
+            "dot" | "graphviz" => Ok(Self::Dot),
+            "json" => Ok(Self::Json),
+            other => Err(anyhow::anyhow!("Unknown graph export format: {}", other)),
+        }
+    }
+}
+
+/// Kind of entity a node stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeKind {
+    Chunk,
+    Pattern,
+    Framework,
+}
+
+impl NodeKind {
+    /// Name used in exported attributes and node IDs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Chunk => "chunk",
+            Self::Pattern => "pattern",
+            Self::Framework => "framework",
+        }
+    }
+
+    /// Node ID of an entity of this kind, unique across kinds
+    pub fn node_id(&self, name: &str) -> String {
+        format!("{}:{}", self.as_str(), name)
+    }
+}
+
+/// Node of an exported graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportNode {
+    pub id: String,
+    pub kind: NodeKind,
+    /// Chunk ID, pattern or framework name
+    pub label: String,
+    /// Frameworks used by a chunk node
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frameworks: Vec<String>,
+}
+
+/// Directed, weighted edge of an exported graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportEdge {
+    pub source: String,
+    pub target: String,
+    /// `RelationType` name, `UsesFramework` or `HasPattern`
+    pub relation: String,
+    pub weight: f32,
+}
+
+/// Which parts of the graph to export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphExportFilter {
+    /// Keep chunks using any of these frameworks (all chunks if empty)
+    pub frameworks: Vec<String>,
+    /// Keep chunks whose ID starts with this prefix
+    pub namespace: Option<String>,
+    /// Drop edges weaker than this
+    pub min_weight: f32,
+}
+
+impl GraphExportFilter {
+    fn keeps_chunk(&self, node: &ExportNode) -> bool {
+        let in_namespace = self.namespace.as_deref()
+            .is_none_or(|namespace| node.label.starts_with(namespace));
+        let in_frameworks = self.frameworks.is_empty() || node.frameworks.iter().any(|framework| {
+            self.frameworks.iter().any(|wanted| wanted.eq_ignore_ascii_case(framework))
+        });
+        in_namespace && in_frameworks
+    }
+}
+
+/// Node-link view of the knowledge graph
+///
+/// Serializes directly to the JSON node-link format.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphExport {
+    pub nodes: Vec<ExportNode>,
+    #[serde(rename = "links")]
+    pub edges: Vec<ExportEdge>,
+}
+
+impl GraphExport {
+    /// Create an empty graph
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chunk node, recording the frameworks it uses
+    pub fn add_chunk(&mut self, chunk_id: &ChunkId, frameworks: Vec<String>) {
+        self.add_node(NodeKind::Chunk, chunk_id.as_str(), frameworks);
+    }
+
+    /// Add a pattern or framework node
+    pub fn add_entity(&mut self, kind: NodeKind, name: &str) {
+        self.add_node(kind, name, Vec::new());
+    }
+
+    /// Add an edge between two nodes
+    pub fn add_edge(&mut self, source: String, target: String, relation: String, weight: f32) {
+        self.edges.push(ExportEdge { source, target, relation, weight });
+    }
+
+    /// Add a typed relationship between two chunks
+    pub fn add_relation(&mut self, source: &ChunkId, target: &ChunkId, relation_type: &RelationType, weight: f32) {
+        self.add_edge(
+            NodeKind::Chunk.node_id(source.as_str()),
+            NodeKind::Chunk.node_id(target.as_str()),
+            relation_name(relation_type),
+            weight,
+        );
+    }
+
+    fn add_node(&mut self, kind: NodeKind, name: &str, frameworks: Vec<String>) {
+        self.nodes.push(ExportNode { id: kind.node_id(name), kind, label: name.to_string(), frameworks });
+    }
+
+    /// Subgraph matching a filter, with nodes and edges in a stable order
+    ///
+    /// Nodes added more than once are merged, as are duplicate edges, which
+    /// keep the strongest weight.
+    ///
+    /// Chunks are kept if they pass the framework and namespace filters;
+    /// pattern and framework nodes are kept while an edge still links them
+    /// to a kept chunk. Framework combination edges between two kept
+    /// frameworks survive as well.
+    pub fn filtered(&self, filter: &GraphExportFilter) -> GraphExport {
+        let chunks: HashSet<&str> = self.nodes.iter()
+            .filter(|node| node.kind == NodeKind::Chunk && filter.keeps_chunk(node))
+            .map(|node| node.id.as_str())
+            .collect();
+        let is_chunk = |id: &str| id.starts_with("chunk:");
+
+        let strong_enough = |edge: &&ExportEdge| edge.weight >= filter.min_weight;
+        let linked: Vec<&ExportEdge> = self.edges.iter()
+            .filter(strong_enough)
+            .filter(|edge| {
+                (is_chunk(&edge.source) || is_chunk(&edge.target))
+                    && (!is_chunk(&edge.source) || chunks.contains(edge.source.as_str()))
+                    && (!is_chunk(&edge.target) || chunks.contains(edge.target.as_str()))
+            })
+            .collect();
+
+        let mut kept: HashSet<&str> = chunks;
+        for edge in &linked {
+            kept.insert(&edge.source);
+            kept.insert(&edge.target);
+        }
+
+        let mut export = GraphExport {
+            nodes: self.nodes.iter()
+                .filter(|node| kept.contains(node.id.as_str()))
+                .cloned()
+                .collect(),
+            edges: self.edges.iter()
+                .filter(strong_enough)
+                .filter(|edge| kept.contains(edge.source.as_str()) && kept.contains(edge.target.as_str()))
+                .cloned()
+                .collect(),
+        };
+        export.normalize();
+        export
+    }
+
+    fn normalize(&mut self) {
+        self.nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        self.nodes.dedup_by(|duplicate, kept| {
+            if duplicate.id != kept.id {
+                return false;
+            }
+            for framework in duplicate.frameworks.drain(..) {
+                if !kept.frameworks.contains(&framework) {
+                    kept.frameworks.push(framework);
+                }
+            }
+            true
+        });
+
+        self.edges.sort_by(|a, b| {
+            a.source.cmp(&b.source)
+                .then_with(|| a.target.cmp(&b.target))
+                .then_with(|| a.relation.cmp(&b.relation))
+        });
+        self.edges.dedup_by(|duplicate, kept| {
+            let same = duplicate.source == kept.source
+                && duplicate.target == kept.target
+                && duplicate.relation == kept.relation;
+            if same {
+                kept.weight = kept.weight.max(duplicate.weight);
+            }
+            same
+        });
+    }
+
+    /// Render the graph in the given format
+    pub fn render(&self, format: GraphExportFormat) -> Result<String> {
+        match format {
+            GraphExportFormat::GraphMl => Ok(self.to_graphml()),
+            GraphExportFormat::Dot => Ok(self.to_dot()),
+            GraphExportFormat::Json => self.to_json(),
+        }
+    }
+
+    /// JSON node-link document
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize graph export")
+    }
+
+    /// GraphML document with node kind, label and frameworks, and edge relation and weight
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"frameworks\" for=\"node\" attr.name=\"frameworks\" attr.type=\"string\"/>\n",
+            "  <key id=\"relation\" for=\"edge\" attr.name=\"relation\" attr.type=\"string\"/>\n",
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n",
+            "  <graph id=\"knowledge\" edgedefault=\"directed\">\n",
+        ));
+
+        for node in &self.nodes {
+            out.push_str(&format!("    <node id=\"{}\">\n", xml_escape(&node.id)));
+            out.push_str(&format!("      <data key=\"kind\">{}</data>\n", node.kind.as_str()));
+            out.push_str(&format!("      <data key=\"label\">{}</data>\n", xml_escape(&node.label)));
+            if !node.frameworks.is_empty() {
+                out.push_str(&format!(
+                    "      <data key=\"frameworks\">{}</data>\n",
+                    xml_escape(&node.frameworks.join(","))
+                ));
+            }
+            out.push_str("    </node>\n");
+        }
+
+        for edge in &self.edges {
+            out.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\">\n",
+                xml_escape(&edge.source),
+                xml_escape(&edge.target)
+            ));
+            out.push_str(&format!("      <data key=\"relation\">{}</data>\n", xml_escape(&edge.relation)));
+            out.push_str(&format!("      <data key=\"weight\">{}</data>\n", edge.weight));
+            out.push_str("    </edge>\n");
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Graphviz DOT digraph, with one node shape per kind and edge width following weight
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph knowledge {\n");
+
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Chunk => "box",
+                NodeKind::Pattern => "ellipse",
+                NodeKind::Framework => "hexagon",
+            };
+            out.push_str(&format!(
+                "  \"{}\" [label=\"{}\", kind=\"{}\", shape={}];\n",
+                dot_escape(&node.id),
+                dot_escape(&node.label),
+                node.kind.as_str(),
+                shape
+            ));
+        }
+
+        for edge in &self.edges {
+            out.push_str(&format!(
+                "  \"{}\" -> \"{}\" [label=\"{}\", weight={:.3}, penwidth={:.2}];\n",
+                dot_escape(&edge.source),
+                dot_escape(&edge.target),
+                dot_escape(&edge.relation),
+                edge.weight,
+                1.0 + edge.weight * 2.0
+            ));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Exported name of a relation type
+pub fn relation_name(relation_type: &RelationType) -> String {
+    match relation_type {
+        RelationType::Custom(name) => name.clone(),
+        other => format!("{:?}", other),
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_graph() -> GraphExport {
+        let page = ChunkId::new("shop/orders-page");
+        let api = ChunkId::new("shop/orders-api");
+        let other = ChunkId::new("blog/post-model");
+
+        let mut graph = GraphExport::new();
+        graph.add_chunk(&page, vec!["nextjs".to_string()]);
+        graph.add_chunk(&api, vec!["laravel".to_string()]);
+        graph.add_chunk(&other, vec!["laravel".to_string()]);
+        graph.add_entity(NodeKind::Framework, "nextjs");
+        graph.add_entity(NodeKind::Framework, "laravel");
+        graph.add_entity(NodeKind::Pattern, "export function");
+
+        graph.add_relation(&page, &api, &RelationType::DependsOn, 0.9);
+        graph.add_relation(&other, &api, &RelationType::Similar, 0.3);
+        for (chunk, framework) in [(&page, "nextjs"), (&api, "laravel"), (&other, "laravel")] {
+            graph.add_edge(
+                NodeKind::Chunk.node_id(chunk.as_str()),
+                NodeKind::Framework.node_id(framework),
+                USES_FRAMEWORK.to_string(),
+                1.0,
+            );
+        }
+        graph.add_edge(
+            NodeKind::Chunk.node_id(page.as_str()),
+            NodeKind::Pattern.node_id("export function"),
+            HAS_PATTERN.to_string(),
+            1.0,
+        );
+        graph.add_edge(
+            NodeKind::Framework.node_id("laravel"),
+            NodeKind::Framework.node_id("nextjs"),
+            relation_name(&RelationType::UsedWith),
+            0.8,
+        );
+        graph
+    }
+
+    #[test]
+    fn test_filters_by_framework_namespace_and_weight() {
+        let graph = sample_graph();
+
+        let nextjs = graph.filtered(&GraphExportFilter {
+            frameworks: vec!["NextJS".to_string()],
+            ..Default::default()
+        });
+        let ids: Vec<&str> = nextjs.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(ids, vec!["chunk:shop/orders-page", "framework:nextjs", "pattern:export function"]);
+        assert_eq!(nextjs.edges.len(), 2);
+
+        let shop = graph.filtered(&GraphExportFilter {
+            namespace: Some("shop/".to_string()),
+            ..Default::default()
+        });
+        assert!(shop.nodes.iter().all(|node| node.id != "chunk:blog/post-model"));
+        // Both frameworks are used by shop chunks, so their combination stays
+        assert!(shop.edges.iter().any(|edge| edge.relation == "UsedWith"));
+
+        let strong = graph.filtered(&GraphExportFilter { min_weight: 0.5, ..Default::default() });
+        assert!(strong.edges.iter().all(|edge| edge.relation != "Similar"));
+        assert_eq!(strong.nodes.len(), graph.nodes.len());
+    }
+
+    #[test]
+    fn test_renders_graphml_dot_and_json() {
+        let mut graph = sample_graph();
+        graph.add_chunk(&ChunkId::new("quote\"<chunk>"), Vec::new());
+        let graph = graph.filtered(&GraphExportFilter::default());
+
+        let graphml = graph.render(GraphExportFormat::GraphMl).unwrap();
+        assert!(graphml.contains("<node id=\"chunk:quote&quot;&lt;chunk&gt;\">"));
+        assert!(graphml.contains("<data key=\"relation\">DependsOn</data>"));
+        assert_eq!(graphml.matches("<edge ").count(), graph.edges.len());
+
+        let dot = graph.render(GraphExportFormat::Dot).unwrap();
+        assert!(dot.starts_with("digraph knowledge {"));
+        assert!(dot.contains("\"chunk:quote\\\"<chunk>\""));
+        assert!(dot.contains("\"chunk:shop/orders-page\" -> \"chunk:shop/orders-api\" [label=\"DependsOn\""));
+
+        let json: GraphExport = serde_json::from_str(&graph.render(GraphExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json, graph);
+        let value: serde_json::Value = serde_json::from_str(&graph.to_json().unwrap()).unwrap();
+        assert_eq!(value["links"].as_array().unwrap().len(), graph.edges.len());
+
+        assert_eq!("GraphML".parse::<GraphExportFormat>().unwrap(), GraphExportFormat::GraphMl);
+        assert!("svg".parse::<GraphExportFormat>().is_err());
+    }
+}
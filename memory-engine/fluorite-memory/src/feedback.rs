@@ -0,0 +1,644 @@
+//! Feedback-adjusted chunk quality
+//!
+//! Each chunk's feedback is folded into a Beta posterior over "this chunk is
+//! helpful". The chunk's own `quality_score` sets the prior mean, helpful
+//! votes add to alpha and negative ones (not helpful, outdated, errors) to
+//! beta. Votes lose half their weight every `half_life_days`, and only the
+//! latest vote of each user counts. The posterior mean is persisted, written
+//! back to the chunk's `quality_score` and used to boost search and
+//! similarity rankings.
+//!
+//! Ranking boosts apply the decay at query time. The chunk's `quality_score`,
+//! in storage and in the search index, is only recomputed when the chunk
+//! receives feedback or is rebased, so quality filters see the posterior as
+//! of that moment rather than one that keeps decaying.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+
+use crate::chunk::{ChunkId, FeedbackType, LearningChunk, UserFeedback};
+use crate::explain::{HitExplanation, RankingStage};
+use crate::hybrid::HybridHit;
+use crate::search::SearchHit;
+
+/// Configuration of the feedback model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackConfig {
+    /// Weight of the chunk's own quality score, in votes
+    pub prior_strength: f64,
+    /// Age in days at which a vote counts half
+    pub half_life_days: f64,
+    /// Largest relative change feedback makes to a search score
+    pub search_boost: f32,
+    /// Largest relative change feedback makes to a similarity ranking score
+    pub similarity_boost: f32,
+    /// Feedback records kept per chunk; only votes replaced by a later vote
+    /// of the same user are dropped
+    pub max_history: usize,
+}
+
+impl Default for FeedbackConfig {
+    fn default() -> Self {
+        Self {
+            prior_strength: 4.0,
+            half_life_days: 90.0,
+            search_boost: 0.5,
+            similarity_boost: 0.3,
+            max_history: 256,
+        }
+    }
+}
+
+/// Hits ranked per requested hit, so feedback can lift chunks from beyond the limit
+pub const RERANK_DEPTH: usize = 4;
+
+/// Stored form of a single piece of user feedback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackRecord {
+    pub user_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub feedback_type: FeedbackType,
+    pub comment: Option<String>,
+}
+
+impl From<&UserFeedback> for FeedbackRecord {
+    fn from(feedback: &UserFeedback) -> Self {
+        Self {
+            user_id: feedback.user_id.clone(),
+            timestamp: feedback.timestamp,
+            feedback_type: feedback.feedback_type.clone(),
+            comment: feedback.comment.clone(),
+        }
+    }
+}
+
+/// Metadata key naming the chunk a piece of feedback was matched against
+///
+/// Feedback on a `find_similar` match that carries the ID of the chunk it was
+/// found for becomes a labelled pair for fitting similarity weights.
+pub const SIMILAR_TO_KEY: &str = "similar_to";
+
+/// Helpful or NotHelpful verdict on a suggested pair of chunks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarityFeedback {
+    /// Chunk the match was found for
+    pub query: ChunkId,
+    /// Suggested match
+    pub candidate: ChunkId,
+    pub user_id: String,
+    pub helpful: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl SimilarityFeedback {
+    /// Pair described by feedback on `candidate`, if it names one and is a verdict
+    pub fn from_feedback(candidate: &ChunkId, feedback: &UserFeedback) -> Option<Self> {
+        let helpful = match feedback.feedback_type {
+            FeedbackType::Helpful => true,
+            FeedbackType::NotHelpful => false,
+            _ => return None,
+        };
+        let query = feedback.metadata.get(SIMILAR_TO_KEY)?.as_str()?;
+
+        Some(Self {
+            query: ChunkId::new(query),
+            candidate: candidate.clone(),
+            user_id: feedback.user_id.clone(),
+            helpful,
+            timestamp: feedback.timestamp,
+        })
+    }
+
+    /// Storage key; a user's later verdict on the same pair replaces the earlier one
+    fn key(&self) -> String {
+        format!("{}\0{}\0{}", self.query, self.candidate, self.user_id)
+    }
+
+    fn involves(&self, chunk_id: &ChunkId) -> bool {
+        self.query == *chunk_id || self.candidate == *chunk_id
+    }
+}
+
+/// Helpful and unhelpful evidence carried by one vote
+fn evidence(feedback_type: &FeedbackType) -> (f64, f64) {
+    match feedback_type {
+        FeedbackType::Helpful => (1.0, 0.0),
+        FeedbackType::NotHelpful | FeedbackType::Outdated => (0.0, 1.0),
+        FeedbackType::NeedsImprovement => (0.0, 0.5),
+        FeedbackType::HasErrors => (0.0, 2.0),
+        FeedbackType::Custom(_) => (0.0, 0.0),
+    }
+}
+
+/// Feedback received by one chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkFeedback {
+    pub chunk_id: ChunkId,
+    /// Quality score of the chunk before feedback, the prior mean
+    pub base_quality: f32,
+    /// Feedback-adjusted quality last written to the chunk
+    pub quality: f32,
+    /// Feedback records, oldest first
+    pub records: Vec<FeedbackRecord>,
+}
+
+impl ChunkFeedback {
+    /// Feedback state of a chunk with no votes yet
+    pub fn new(chunk_id: ChunkId, base_quality: f32) -> Self {
+        let base_quality = base_quality.clamp(0.0, 1.0);
+        Self {
+            chunk_id,
+            base_quality,
+            quality: base_quality,
+            records: Vec::new(),
+        }
+    }
+
+    /// Add a vote, dropping the oldest replaced votes beyond `max_history`
+    pub fn add(&mut self, record: FeedbackRecord, max_history: usize) {
+        let position = self.records.partition_point(|existing| existing.timestamp <= record.timestamp);
+        self.records.insert(position, record);
+
+        let mut excess = self.records.len().saturating_sub(max_history);
+        if excess == 0 {
+            return;
+        }
+        let counted = self.counted();
+        let mut index = 0;
+        self.records.retain(|_| {
+            let keep = excess == 0 || counted[index];
+            if !keep {
+                excess -= 1;
+            }
+            index += 1;
+            keep
+        });
+    }
+
+    /// Whether each record is its user's latest vote
+    fn counted(&self) -> Vec<bool> {
+        let mut seen = HashSet::new();
+        let mut counted: Vec<bool> = self.records.iter().rev()
+            .map(|record| seen.insert(record.user_id.as_str()))
+            .collect();
+        counted.reverse();
+        counted
+    }
+
+    /// Decayed weight of a vote cast at `timestamp`
+    fn decay(timestamp: DateTime<Utc>, config: &FeedbackConfig, now: DateTime<Utc>) -> f64 {
+        let age_days = (now - timestamp).num_seconds().max(0) as f64 / 86_400.0;
+        0.5f64.powf(age_days / config.half_life_days.max(f64::EPSILON))
+    }
+
+    /// Beta posterior `(alpha, beta)` as of `now`
+    pub fn posterior(&self, config: &FeedbackConfig, now: DateTime<Utc>) -> (f64, f64) {
+        let prior = config.prior_strength.max(f64::EPSILON);
+        let mut alpha = self.base_quality as f64 * prior;
+        let mut beta = (1.0 - self.base_quality as f64) * prior;
+
+        for (record, counted) in self.records.iter().zip(self.counted()) {
+            if !counted {
+                continue;
+            }
+            let weight = Self::decay(record.timestamp, config, now);
+            let (helpful, unhelpful) = evidence(&record.feedback_type);
+            alpha += weight * helpful;
+            beta += weight * unhelpful;
+        }
+
+        (alpha, beta)
+    }
+
+    /// Feedback-adjusted quality as of `now`, the posterior mean
+    pub fn score(&self, config: &FeedbackConfig, now: DateTime<Utc>) -> f32 {
+        let (alpha, beta) = self.posterior(config, now);
+        (alpha / (alpha + beta)) as f32
+    }
+
+    /// Ranking multiplier in `1 - weight ..= 1 + weight`, 1.0 when feedback
+    /// left the quality unchanged
+    pub fn boost(&self, config: &FeedbackConfig, weight: f32, now: DateTime<Utc>) -> f32 {
+        1.0 + weight * (self.score(config, now) - self.base_quality)
+    }
+
+    /// Readable history of the chunk's feedback as of `now`
+    pub fn history(&self, config: &FeedbackConfig, now: DateTime<Utc>) -> FeedbackHistory {
+        let (alpha, beta) = self.posterior(config, now);
+        let entries = self.records.iter()
+            .zip(self.counted())
+            .rev()
+            .map(|(record, counted)| FeedbackEntry {
+                record: record.clone(),
+                counted,
+                weight: if counted { Self::decay(record.timestamp, config, now) as f32 } else { 0.0 },
+            })
+            .collect();
+
+        FeedbackHistory {
+            chunk_id: self.chunk_id.clone(),
+            base_quality: self.base_quality,
+            score: (alpha / (alpha + beta)) as f32,
+            alpha,
+            beta,
+            entries,
+        }
+    }
+}
+
+/// Feedback received by a chunk and its effect on the chunk's quality
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackHistory {
+    pub chunk_id: ChunkId,
+    /// Quality score before feedback
+    pub base_quality: f32,
+    /// Current feedback-adjusted quality
+    pub score: f32,
+    /// Beta posterior parameters
+    pub alpha: f64,
+    pub beta: f64,
+    /// Feedback records, newest first
+    pub entries: Vec<FeedbackEntry>,
+}
+
+/// One feedback record and how much it currently counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackEntry {
+    pub record: FeedbackRecord,
+    /// False when the same user voted again later
+    pub counted: bool,
+    /// Decayed weight of the vote, 0.0 when not counted
+    pub weight: f32,
+}
+
+/// Sled trees holding per-chunk feedback and feedback on suggested pairs
+#[derive(Debug, Clone)]
+pub struct FeedbackStore {
+    tree: Tree,
+    pairs: Tree,
+}
+
+impl FeedbackStore {
+    /// Open the feedback trees of a database
+    pub fn open(db: &Db) -> Result<Self> {
+        let tree = db.open_tree("chunk_feedback").context("Failed to open chunk_feedback tree")?;
+        let pairs = db.open_tree("similarity_feedback").context("Failed to open similarity_feedback tree")?;
+        Ok(Self { tree, pairs })
+    }
+
+    /// Load the feedback of every chunk
+    pub fn load(&self) -> Result<HashMap<ChunkId, ChunkFeedback>> {
+        let mut feedback = HashMap::new();
+        for entry in self.tree.iter() {
+            let (_, value) = entry.context("Failed to iterate chunk feedback")?;
+            let chunk: ChunkFeedback = bincode::deserialize(&value)
+                .context("Failed to deserialize chunk feedback")?;
+            feedback.insert(chunk.chunk_id.clone(), chunk);
+        }
+        Ok(feedback)
+    }
+
+    /// Store the feedback of a chunk
+    pub fn put(&self, feedback: &ChunkFeedback) -> Result<()> {
+        let serialized = bincode::serialize(feedback).context("Failed to serialize chunk feedback")?;
+        self.tree.insert(feedback.chunk_id.as_str(), serialized)
+            .context("Failed to write chunk feedback")?;
+        Ok(())
+    }
+
+    /// Remove the feedback of a chunk
+    pub fn remove(&self, chunk_id: &ChunkId) -> Result<()> {
+        self.tree.remove(chunk_id.as_str()).context("Failed to remove chunk feedback")?;
+        Ok(())
+    }
+
+    /// Load the feedback on every suggested pair, keyed by pair and user
+    pub fn load_pairs(&self) -> Result<HashMap<String, SimilarityFeedback>> {
+        let mut pairs = HashMap::new();
+        for entry in self.pairs.iter() {
+            let (_, value) = entry.context("Failed to iterate similarity feedback")?;
+            let pair: SimilarityFeedback = bincode::deserialize(&value)
+                .context("Failed to deserialize similarity feedback")?;
+            pairs.insert(pair.key(), pair);
+        }
+        Ok(pairs)
+    }
+
+    /// Store feedback on a suggested pair
+    pub fn put_pair(&self, pair: &SimilarityFeedback) -> Result<()> {
+        let serialized = bincode::serialize(pair).context("Failed to serialize similarity feedback")?;
+        self.pairs.insert(pair.key(), serialized)
+            .context("Failed to write similarity feedback")?;
+        Ok(())
+    }
+
+    /// Remove feedback on a suggested pair
+    pub fn remove_pair(&self, pair: &SimilarityFeedback) -> Result<()> {
+        self.pairs.remove(pair.key()).context("Failed to remove similarity feedback")?;
+        Ok(())
+    }
+}
+
+/// Hit that can be reranked by feedback
+pub trait FeedbackRanked {
+    fn chunk_id(&self) -> &ChunkId;
+    fn score(&self) -> f32;
+    fn set_score(&mut self, score: f32);
+    fn explanation_mut(&mut self) -> Option<&mut HitExplanation>;
+}
+
+impl FeedbackRanked for SearchHit {
+    fn chunk_id(&self) -> &ChunkId {
+        &self.chunk.id
+    }
+
+    fn score(&self) -> f32 {
+        self.score
+    }
+
+    fn set_score(&mut self, score: f32) {
+        self.score = score;
+    }
+
+    fn explanation_mut(&mut self) -> Option<&mut HitExplanation> {
+        self.explanation.as_mut()
+    }
+}
+
+impl FeedbackRanked for HybridHit {
+    fn chunk_id(&self) -> &ChunkId {
+        &self.chunk.id
+    }
+
+    fn score(&self) -> f32 {
+        self.score
+    }
+
+    fn set_score(&mut self, score: f32) {
+        self.score = score;
+    }
+
+    fn explanation_mut(&mut self) -> Option<&mut HitExplanation> {
+        self.explanation.as_mut()
+    }
+}
+
+/// Per-chunk feedback scores, persisted as they change
+#[derive(Debug)]
+pub struct FeedbackTracker {
+    config: FeedbackConfig,
+    chunks: RwLock<HashMap<ChunkId, ChunkFeedback>>,
+    pairs: RwLock<HashMap<String, SimilarityFeedback>>,
+    store: Option<FeedbackStore>,
+}
+
+impl FeedbackTracker {
+    /// Create an in-memory tracker
+    pub fn new(config: FeedbackConfig) -> Self {
+        Self {
+            config,
+            chunks: RwLock::new(HashMap::new()),
+            pairs: RwLock::new(HashMap::new()),
+            store: None,
+        }
+    }
+
+    /// Create a tracker backed by a store, loading its feedback
+    pub fn with_store(config: FeedbackConfig, store: FeedbackStore) -> Result<Self> {
+        let chunks = store.load()?;
+        let pairs = store.load_pairs()?;
+        tracing::debug!("Loaded feedback for {} chunks and {} suggested pairs", chunks.len(), pairs.len());
+
+        Ok(Self {
+            config,
+            chunks: RwLock::new(chunks),
+            pairs: RwLock::new(pairs),
+            store: Some(store),
+        })
+    }
+
+    /// Configuration of the feedback model
+    pub fn config(&self) -> &FeedbackConfig {
+        &self.config
+    }
+
+    /// Record feedback on a chunk and return its new quality
+    ///
+    /// `base_quality` becomes the prior of chunks without earlier feedback.
+    /// Verdicts naming the chunk the match was found for ([`SIMILAR_TO_KEY`])
+    /// are also kept as labelled pairs.
+    pub fn record(&self, chunk_id: &ChunkId, base_quality: f32, feedback: &UserFeedback) -> Result<f32> {
+        if let Some(pair) = SimilarityFeedback::from_feedback(chunk_id, feedback) {
+            if let Some(store) = &self.store {
+                store.put_pair(&pair)?;
+            }
+            self.pairs.write().insert(pair.key(), pair);
+        }
+
+        let mut chunks = self.chunks.write();
+        let state = chunks.entry(chunk_id.clone())
+            .or_insert_with(|| ChunkFeedback::new(chunk_id.clone(), base_quality));
+        state.add(FeedbackRecord::from(feedback), self.config.max_history);
+        state.quality = state.score(&self.config, Utc::now());
+
+        self.persist(state)?;
+        Ok(state.quality)
+    }
+
+    /// Keep a stored chunk's quality in line with its feedback
+    ///
+    /// A chunk carrying its feedback-adjusted quality is left alone; any other
+    /// quality was set on purpose and becomes the new prior.
+    pub fn rebase(&self, chunk: &mut LearningChunk) -> Result<()> {
+        let mut chunks = self.chunks.write();
+        let Some(state) = chunks.get_mut(&chunk.id) else {
+            return Ok(());
+        };
+
+        if chunk.quality_score != state.quality {
+            state.base_quality = chunk.quality_score.clamp(0.0, 1.0);
+            state.quality = state.score(&self.config, Utc::now());
+            self.persist(state)?;
+            chunk.quality_score = state.quality;
+        }
+        Ok(())
+    }
+
+    /// Feedback on suggested pairs, oldest first
+    pub fn similarity_feedback(&self) -> Vec<SimilarityFeedback> {
+        let mut pairs: Vec<SimilarityFeedback> = self.pairs.read().values().cloned().collect();
+        pairs.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.key().cmp(&b.key())));
+        pairs
+    }
+
+    /// Feedback history of a chunk, if it received any
+    pub fn history(&self, chunk_id: &ChunkId) -> Option<FeedbackHistory> {
+        self.chunks.read().get(chunk_id).map(|state| state.history(&self.config, Utc::now()))
+    }
+
+    /// Multiplier applied to similarity ranking scores of a chunk
+    pub fn similarity_boost(&self, chunk_id: &ChunkId) -> f32 {
+        self.boost(chunk_id, self.config.similarity_boost)
+    }
+
+    fn boost(&self, chunk_id: &ChunkId, weight: f32) -> f32 {
+        self.chunks.read().get(chunk_id)
+            .map_or(1.0, |state| state.boost(&self.config, weight, Utc::now()))
+    }
+
+    /// Scale hit scores by feedback and restore descending order
+    ///
+    /// Hits without feedback keep their score. Explained hits get a
+    /// `Feedback` ranking stage when any hit was boosted.
+    pub fn rerank<H: FeedbackRanked>(&self, hits: &mut [H]) {
+        let mut boosts: HashMap<ChunkId, f32> = HashMap::new();
+        for hit in hits.iter_mut() {
+            let boost = self.boost(hit.chunk_id(), self.config.search_boost);
+            if boost != 1.0 {
+                hit.set_score(hit.score() * boost);
+                boosts.insert(hit.chunk_id().clone(), boost);
+            }
+        }
+        if boosts.is_empty() {
+            return;
+        }
+
+        hits.sort_by(|a, b| b.score().total_cmp(&a.score()));
+
+        for (rank, hit) in hits.iter_mut().enumerate() {
+            let boost = boosts.get(hit.chunk_id()).copied().unwrap_or(1.0);
+            let score = hit.score();
+            if let Some(explanation) = hit.explanation_mut() {
+                explanation.score = score;
+                explanation.stages.push(RankingStage::Feedback { boost, score, rank: rank + 1 });
+            }
+        }
+    }
+
+    /// Forget the feedback of a deleted chunk, including pairs it is part of
+    pub fn remove(&self, chunk_id: &ChunkId) -> Result<()> {
+        if self.chunks.write().remove(chunk_id).is_some() {
+            if let Some(store) = &self.store {
+                store.remove(chunk_id)?;
+            }
+        }
+
+        let mut pairs = self.pairs.write();
+        let removed: Vec<String> = pairs.iter()
+            .filter(|(_, pair)| pair.involves(chunk_id))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in removed {
+            if let (Some(pair), Some(store)) = (pairs.remove(&key), &self.store) {
+                store.remove_pair(&pair)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn persist(&self, state: &ChunkFeedback) -> Result<()> {
+        match &self.store {
+            Some(store) => store.put(state),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn feedback(user_id: &str, feedback_type: FeedbackType, timestamp: DateTime<Utc>) -> UserFeedback {
+        UserFeedback {
+            user_id: user_id.to_string(),
+            timestamp,
+            feedback_type,
+            comment: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_latest_vote_per_user_counts() {
+        let config = FeedbackConfig::default();
+        let now = Utc::now();
+        let mut state = ChunkFeedback::new(ChunkId::new("chunk"), 0.5);
+        assert_eq!(state.score(&config, now), 0.5);
+
+        for _ in 0..5 {
+            state.add(FeedbackRecord::from(&feedback("alice", FeedbackType::Helpful, now)), 256);
+        }
+        // Repeated votes of one user count once: (2 + 1) / (4 + 1)
+        assert!((state.score(&config, now) - 0.6).abs() < 1e-6);
+
+        state.add(FeedbackRecord::from(&feedback("alice", FeedbackType::HasErrors, now + Duration::seconds(1))), 256);
+        state.add(FeedbackRecord::from(&feedback("bob", FeedbackType::NotHelpful, now)), 256);
+        assert!((state.score(&config, now) - 2.0 / 7.0).abs() < 1e-6);
+        assert!(state.boost(&config, 0.5, now) < 1.0);
+
+        let history = state.history(&config, now);
+        assert_eq!(history.entries.len(), 7);
+        assert!(matches!(history.entries[0].record.feedback_type, FeedbackType::HasErrors));
+        assert_eq!(history.entries.iter().filter(|entry| entry.counted).count(), 2);
+    }
+
+    #[test]
+    fn test_votes_decay_and_history_is_capped() {
+        let config = FeedbackConfig { half_life_days: 30.0, ..Default::default() };
+        let now = Utc::now();
+        let mut state = ChunkFeedback::new(ChunkId::new("chunk"), 0.5);
+
+        state.add(FeedbackRecord::from(&feedback("old", FeedbackType::Helpful, now - Duration::days(30))), 3);
+        let (alpha, _) = state.posterior(&config, now);
+        assert!((alpha - 2.5).abs() < 1e-3);
+
+        for minute in 0..4 {
+            state.add(FeedbackRecord::from(&feedback("carol", FeedbackType::Helpful, now + Duration::minutes(minute))), 3);
+        }
+        // Replaced votes go first; the lone vote of another user is kept
+        assert_eq!(state.records.len(), 3);
+        assert!(state.records.iter().any(|record| record.user_id == "old"));
+        assert_eq!(state.records.last().unwrap().timestamp, now + Duration::minutes(3));
+    }
+
+    #[test]
+    fn test_tracker_persists_and_rebases() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunk_id = ChunkId::new("chunk");
+
+        {
+            let db = crate::storage::open_db(temp_dir.path()).unwrap();
+            let tracker = FeedbackTracker::with_store(FeedbackConfig::default(), FeedbackStore::open(&db).unwrap()).unwrap();
+            let quality = tracker.record(&chunk_id, 0.8, &feedback("alice", FeedbackType::NotHelpful, Utc::now())).unwrap();
+            assert!(quality < 0.8);
+
+            // A chunk carrying its adjusted quality is left alone
+            let mut chunk = LearningChunk { id: chunk_id.clone(), quality_score: quality, ..Default::default() };
+            tracker.rebase(&mut chunk).unwrap();
+            assert_eq!(chunk.quality_score, quality);
+        }
+
+        let db = crate::storage::open_db(temp_dir.path()).unwrap();
+        let tracker = FeedbackTracker::with_store(FeedbackConfig::default(), FeedbackStore::open(&db).unwrap()).unwrap();
+        let history = tracker.history(&chunk_id).unwrap();
+        assert_eq!(history.base_quality, 0.8);
+        assert_eq!(history.entries.len(), 1);
+
+        // A deliberately changed quality becomes the new prior
+        let mut chunk = LearningChunk { id: chunk_id.clone(), quality_score: 0.2, ..Default::default() };
+        tracker.rebase(&mut chunk).unwrap();
+        assert_eq!(tracker.history(&chunk_id).unwrap().base_quality, 0.2);
+        assert!(chunk.quality_score < 0.2);
+
+        tracker.remove(&chunk_id).unwrap();
+        assert!(tracker.history(&chunk_id).is_none());
+        assert_eq!(tracker.similarity_boost(&chunk_id), 1.0);
+    }
+}
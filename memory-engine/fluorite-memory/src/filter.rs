@@ -0,0 +1,184 @@
+//! Chunk filtering predicates
+//!
+//! Provides a serializable filter over chunk metadata that storage scans and
+//! bulk engine operations use to select learning chunks.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{ChunkType, LearningChunk};
+
+/// Predicate over chunk type, metadata and quality
+///
+/// Every populated criterion must match. List criteria behave as follows:
+/// `chunk_types` and `frameworks` match any listed value, `tags` must all be present.
+/// Chunks matching any of the `excluded_*` values are rejected.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChunkFilter {
+    /// Accepted chunk types (any of)
+    pub chunk_types: Vec<ChunkType>,
+    /// Accepted frameworks (any of)
+    pub frameworks: Vec<String>,
+    /// Required tags (all of)
+    pub tags: Vec<String>,
+    /// Required chunk source
+    pub source: Option<String>,
+    /// Minimum quality score (inclusive)
+    ///
+    /// Compared with the stored score, which reflects feedback decay only as
+    /// of the chunk's last feedback.
+    pub min_quality: Option<f32>,
+    /// Maximum quality score (inclusive), compared like `min_quality`
+    pub max_quality: Option<f32>,
+    /// Only chunks created at or after this time
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only chunks created before this time
+    pub created_before: Option<DateTime<Utc>>,
+    /// Rejected chunk types
+    #[serde(default)]
+    pub excluded_chunk_types: Vec<ChunkType>,
+    /// Rejected frameworks
+    #[serde(default)]
+    pub excluded_frameworks: Vec<String>,
+    /// Rejected tags
+    #[serde(default)]
+    pub excluded_tags: Vec<String>,
+    /// Rejected chunk sources
+    #[serde(default)]
+    pub excluded_sources: Vec<String>,
+}
+
+impl ChunkFilter {
+    /// Create an empty filter that matches every chunk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether no criteria are set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check whether a chunk satisfies every criterion of this filter
+    pub fn matches(&self, chunk: &LearningChunk) -> bool {
+        if !self.chunk_types.is_empty() && !self.chunk_types.contains(&chunk.chunk_type) {
+            return false;
+        }
+
+        if !self.frameworks.is_empty()
+            && !self.frameworks.iter().any(|fw| chunk.is_framework_related(fw))
+        {
+            return false;
+        }
+
+        if !self.tags.iter().all(|tag| chunk.metadata.tags.contains(tag)) {
+            return false;
+        }
+
+        if let Some(source) = &self.source {
+            if &chunk.metadata.source != source {
+                return false;
+            }
+        }
+
+        if self.min_quality.is_some_and(|min| chunk.quality_score < min) {
+            return false;
+        }
+
+        if self.max_quality.is_some_and(|max| chunk.quality_score > max) {
+            return false;
+        }
+
+        if self.created_after.is_some_and(|after| chunk.metadata.created_at < after) {
+            return false;
+        }
+
+        if self.created_before.is_some_and(|before| chunk.metadata.created_at >= before) {
+            return false;
+        }
+
+        if self.excluded_chunk_types.contains(&chunk.chunk_type)
+            || self.excluded_frameworks.iter().any(|fw| chunk.is_framework_related(fw))
+            || self.excluded_tags.iter().any(|tag| chunk.metadata.tags.contains(tag))
+            || self.excluded_sources.contains(&chunk.metadata.source)
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::{ChunkContent, ChunkId, ChunkMetadata};
+
+    fn create_test_chunk(framework: &str, tags: &[&str], quality: f32) -> LearningChunk {
+        LearningChunk {
+            id: ChunkId::generate(),
+            chunk_type: ChunkType::Component,
+            content: ChunkContent::Code {
+                language: "typescript".to_string(),
+                code: "export default function Page() {}".to_string(),
+                framework: Some(framework.to_string()),
+            },
+            metadata: ChunkMetadata {
+                frameworks: vec![framework.to_string()],
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            },
+            quality_score: quality,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        let filter = ChunkFilter::new();
+        assert!(filter.is_empty());
+        assert!(filter.matches(&create_test_chunk("nextjs", &[], 0.1)));
+    }
+
+    #[test]
+    fn test_filter_criteria() {
+        let chunk = create_test_chunk("nextjs", &["auth", "server"], 0.85);
+
+        let filter = ChunkFilter {
+            chunk_types: vec![ChunkType::Component],
+            frameworks: vec!["laravel".to_string(), "nextjs".to_string()],
+            tags: vec!["auth".to_string()],
+            min_quality: Some(0.8),
+            ..Default::default()
+        };
+        assert!(filter.matches(&chunk));
+
+        let missing_tag = ChunkFilter { tags: vec!["deprecated".to_string()], ..Default::default() };
+        assert!(!missing_tag.matches(&chunk));
+
+        let too_good = ChunkFilter { max_quality: Some(0.5), ..Default::default() };
+        assert!(!too_good.matches(&chunk));
+
+        let wrong_type = ChunkFilter { chunk_types: vec![ChunkType::Testing], ..Default::default() };
+        assert!(!wrong_type.matches(&chunk));
+    }
+
+    #[test]
+    fn test_exclusions_reject_matching_chunks() {
+        let chunk = create_test_chunk("nextjs", &["auth", "deprecated"], 0.9);
+
+        let excluded_tag = ChunkFilter { excluded_tags: vec!["deprecated".to_string()], ..Default::default() };
+        assert!(!excluded_tag.matches(&chunk));
+
+        let excluded_framework = ChunkFilter {
+            frameworks: vec!["nextjs".to_string()],
+            excluded_frameworks: vec!["laravel".to_string()],
+            excluded_chunk_types: vec![ChunkType::Testing],
+            ..Default::default()
+        };
+        assert!(excluded_framework.matches(&chunk));
+
+        let excluded_type = ChunkFilter { excluded_chunk_types: vec![ChunkType::Component], ..Default::default() };
+        assert!(!excluded_type.matches(&chunk));
+    }
+}
@@ -0,0 +1,579 @@
+//! Traversal queries over chunk relationships
+//!
+//! `ChunkGraph` is a read-only snapshot of the relationship graph that answers
+//! questions like "what does this component transitively depend on" or "how
+//! does this Next.js page reach that Laravel model". Every stored edge is also
+//! visible from its target through the inverse relation type (`DependsOn`
+//! seen backwards is `DependedOnBy`), so traversals can follow relationships
+//! in either direction by naming the type.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{ChunkId, RelationType};
+use crate::patterns::WeightedEdge;
+
+/// Directed, typed edge between two chunks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: ChunkId,
+    pub target: ChunkId,
+    pub relation_type: RelationType,
+    /// Strength of the relationship (0.0 - 1.0)
+    pub weight: f32,
+    pub confidence: f32,
+}
+
+/// Path between two chunks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphPath {
+    /// Chunks along the path, including both ends
+    pub chunks: Vec<ChunkId>,
+    /// Edges followed, one fewer than `chunks`
+    pub edges: Vec<GraphEdge>,
+    /// Total cost of the path: hop count for shortest paths, or the sum of
+    /// `-ln(weight)` for weighted paths
+    pub cost: f32,
+}
+
+/// Chunk reached by a transitive traversal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReachableChunk {
+    pub chunk_id: ChunkId,
+    /// Number of hops from the start chunk
+    pub depth: usize,
+    /// Edge through which the chunk was first reached
+    pub via: GraphEdge,
+}
+
+/// Result of a transitive traversal
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransitiveClosure {
+    /// Reached chunks in breadth-first order
+    pub reachable: Vec<ReachableChunk>,
+    /// Problems found along the way, such as dependency cycles
+    pub warnings: Vec<GraphWarning>,
+}
+
+/// Problem detected while traversing the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GraphWarning {
+    /// Chunks that depend on each other in a loop, listed in cycle order
+    DependencyCycle(Vec<ChunkId>),
+}
+
+/// Read-only snapshot of chunk relationships
+#[derive(Debug, Clone, Default)]
+pub struct ChunkGraph {
+    // Outgoing edges per chunk, including the inverse view of stored edges
+    outgoing: HashMap<ChunkId, Vec<GraphEdge>>,
+}
+
+impl ChunkGraph {
+    /// Build a graph from stored adjacency lists
+    pub fn from_adjacency(adjacency: &HashMap<ChunkId, Vec<WeightedEdge>>) -> Self {
+        let mut graph = Self::default();
+
+        for (source, edges) in adjacency {
+            for edge in edges {
+                graph.add_edge(GraphEdge {
+                    source: source.clone(),
+                    target: edge.target.clone(),
+                    relation_type: edge.relation_type.clone(),
+                    weight: edge.weight,
+                    confidence: edge.confidence,
+                });
+            }
+        }
+
+        for edges in graph.outgoing.values_mut() {
+            edges.sort_by(|a, b| {
+                a.target.as_str().cmp(b.target.as_str())
+                    .then_with(|| b.weight.total_cmp(&a.weight))
+            });
+        }
+        graph
+    }
+
+    /// Number of chunks with at least one relationship
+    pub fn len(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    /// Check whether the graph has no relationships
+    pub fn is_empty(&self) -> bool {
+        self.outgoing.is_empty()
+    }
+
+    /// Edges leaving a chunk whose type is in `types` (all types if empty)
+    pub fn neighbors(&self, chunk_id: &ChunkId, types: &[RelationType]) -> Vec<GraphEdge> {
+        self.edges(chunk_id, types).cloned().collect()
+    }
+
+    /// Every chunk reachable from `start` through edges of the given types
+    ///
+    /// Traversal stops after `max_depth` hops when set. Dependency cycles met
+    /// among the reached chunks are reported as warnings.
+    pub fn transitive_closure(
+        &self,
+        start: &ChunkId,
+        types: &[RelationType],
+        max_depth: Option<usize>,
+    ) -> TransitiveClosure {
+        let mut visited: HashSet<&ChunkId> = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        let mut reachable = Vec::new();
+
+        while let Some((chunk_id, depth)) = queue.pop_front() {
+            if max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                continue;
+            }
+            for edge in self.edges(chunk_id, types) {
+                if visited.insert(&edge.target) {
+                    reachable.push(ReachableChunk {
+                        chunk_id: edge.target.clone(),
+                        depth: depth + 1,
+                        via: edge.clone(),
+                    });
+                    queue.push_back((&edge.target, depth + 1));
+                }
+            }
+        }
+
+        let warnings = self.dependency_cycles_within(&visited)
+            .into_iter()
+            .map(GraphWarning::DependencyCycle)
+            .collect();
+
+        TransitiveClosure { reachable, warnings }
+    }
+
+    /// Path with the fewest hops from `from` to `to`
+    pub fn shortest_path(&self, from: &ChunkId, to: &ChunkId, types: &[RelationType]) -> Option<GraphPath> {
+        if from == to {
+            return Some(GraphPath { chunks: vec![from.clone()], edges: Vec::new(), cost: 0.0 });
+        }
+
+        let mut previous: HashMap<&ChunkId, &GraphEdge> = HashMap::new();
+        let mut visited: HashSet<&ChunkId> = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(chunk_id) = queue.pop_front() {
+            for edge in self.edges(chunk_id, types) {
+                if !visited.insert(&edge.target) {
+                    continue;
+                }
+                previous.insert(&edge.target, edge);
+                if &edge.target == to {
+                    let path = trace_path(&previous, from, to);
+                    let cost = path.edges.len() as f32;
+                    return Some(GraphPath { cost, ..path });
+                }
+                queue.push_back(&edge.target);
+            }
+        }
+
+        None
+    }
+
+    /// Most reliable path from `from` to `to`
+    ///
+    /// Minimizes the sum of `-ln(weight)`, i.e. maximizes the product of edge
+    /// strengths, so a few strong relationships beat many weak ones.
+    pub fn weighted_path(&self, from: &ChunkId, to: &ChunkId, types: &[RelationType]) -> Option<GraphPath> {
+        let mut costs: HashMap<&ChunkId, f32> = HashMap::from([(from, 0.0)]);
+        let mut previous: HashMap<&ChunkId, &GraphEdge> = HashMap::new();
+        let mut heap = BinaryHeap::from([Frontier { cost: 0.0, chunk_id: from }]);
+
+        while let Some(Frontier { cost, chunk_id }) = heap.pop() {
+            if chunk_id == to {
+                let path = trace_path(&previous, from, to);
+                return Some(GraphPath { cost, ..path });
+            }
+            if costs.get(chunk_id).is_some_and(|best| cost > *best) {
+                continue;
+            }
+
+            for edge in self.edges(chunk_id, types) {
+                let next = cost + edge_cost(edge);
+                if costs.get(&edge.target).is_none_or(|best| next < *best) {
+                    costs.insert(&edge.target, next);
+                    previous.insert(&edge.target, edge);
+                    heap.push(Frontier { cost: next, chunk_id: &edge.target });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Groups of chunks connected through edges of the given types
+    ///
+    /// Edge direction is ignored. Components are sorted largest first and
+    /// their members by ID; isolated chunks are not listed.
+    pub fn connected_components(&self, types: &[RelationType]) -> Vec<Vec<ChunkId>> {
+        // Follow the inverse view too so that direction does not split components
+        let mut undirected = types.to_vec();
+        for relation_type in types {
+            if let Some(inverse) = relation_type.inverse() {
+                if !undirected.contains(&inverse) {
+                    undirected.push(inverse);
+                }
+            }
+        }
+        let types = undirected.as_slice();
+
+        let mut visited: HashSet<&ChunkId> = HashSet::new();
+        let mut components = Vec::new();
+
+        let mut starts: Vec<&ChunkId> = self.outgoing.keys().collect();
+        starts.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        for start in starts {
+            if visited.contains(start) || self.edges(start, types).next().is_none() {
+                continue;
+            }
+
+            let mut component = Vec::new();
+            let mut stack = vec![start];
+            visited.insert(start);
+            while let Some(chunk_id) = stack.pop() {
+                component.push(chunk_id.clone());
+                for edge in self.edges(chunk_id, types) {
+                    if visited.insert(&edge.target) {
+                        stack.push(&edge.target);
+                    }
+                }
+            }
+
+            component.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+            components.push(component);
+        }
+
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].as_str().cmp(b[0].as_str())));
+        components
+    }
+
+    /// Every cycle of `DependsOn` relationships in the graph
+    pub fn dependency_cycles(&self) -> Vec<Vec<ChunkId>> {
+        let all: HashSet<&ChunkId> = self.outgoing.keys().collect();
+        self.dependency_cycles_within(&all)
+    }
+
+    fn add_edge(&mut self, edge: GraphEdge) {
+        if let Some(inverse) = edge.relation_type.inverse() {
+            let mirrored = GraphEdge {
+                source: edge.target.clone(),
+                target: edge.source.clone(),
+                relation_type: inverse,
+                weight: edge.weight,
+                confidence: edge.confidence,
+            };
+            self.insert_unique(mirrored);
+        }
+        self.insert_unique(edge);
+    }
+
+    fn insert_unique(&mut self, edge: GraphEdge) {
+        let edges = self.outgoing.entry(edge.source.clone()).or_default();
+        match edges.iter_mut().find(|existing| {
+            existing.target == edge.target && existing.relation_type == edge.relation_type
+        }) {
+            // Both ends may store the same relationship; keep the stronger one
+            Some(existing) => {
+                if edge.weight > existing.weight {
+                    *existing = edge;
+                }
+            }
+            None => edges.push(edge),
+        }
+    }
+
+    fn edges<'a>(&'a self, chunk_id: &ChunkId, types: &'a [RelationType]) -> impl Iterator<Item = &'a GraphEdge> {
+        self.outgoing.get(chunk_id)
+            .into_iter()
+            .flatten()
+            .filter(move |edge| types.is_empty() || types.contains(&edge.relation_type))
+    }
+
+    /// Strongly connected `DependsOn` components among the given chunks (Tarjan)
+    ///
+    /// The depth-first search keeps its own stack of chunks being visited, so
+    /// long dependency chains cannot overflow the thread stack.
+    fn dependency_cycles_within(&self, chunks: &HashSet<&ChunkId>) -> Vec<Vec<ChunkId>> {
+        struct Tarjan<'a> {
+            index: HashMap<&'a ChunkId, usize>,
+            low: HashMap<&'a ChunkId, usize>,
+            stack: Vec<&'a ChunkId>,
+            on_stack: HashSet<&'a ChunkId>,
+            cycles: Vec<Vec<ChunkId>>,
+        }
+
+        impl<'a> Tarjan<'a> {
+            fn discover(&mut self, chunk_id: &'a ChunkId) {
+                let index = self.index.len();
+                self.index.insert(chunk_id, index);
+                self.low.insert(chunk_id, index);
+                self.stack.push(chunk_id);
+                self.on_stack.insert(chunk_id);
+            }
+
+            fn lower(&mut self, chunk_id: &'a ChunkId, value: usize) {
+                let low = self.low[chunk_id].min(value);
+                self.low.insert(chunk_id, low);
+            }
+
+            fn finish(&mut self, chunk_id: &'a ChunkId, self_loop: bool) {
+                if self.low[chunk_id] != self.index[chunk_id] {
+                    return;
+                }
+
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member.clone());
+                    if member == chunk_id {
+                        break;
+                    }
+                }
+                if component.len() > 1 || self_loop {
+                    // Popped in reverse discovery order; restore dependency order
+                    component.reverse();
+                    self.cycles.push(component);
+                }
+            }
+        }
+
+        let depends_on = [RelationType::DependsOn];
+        let dependencies = |chunk_id| self.edges(chunk_id, &depends_on)
+            .map(|edge| &edge.target)
+            .filter(|target| chunks.contains(target));
+
+        let mut tarjan = Tarjan {
+            index: HashMap::new(),
+            low: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            cycles: Vec::new(),
+        };
+
+        let mut starts: Vec<&ChunkId> = chunks.iter().copied().collect();
+        starts.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        for start in starts {
+            if tarjan.index.contains_key(start) {
+                continue;
+            }
+
+            // Chunks being visited with their unexplored dependencies and whether they depend on themselves
+            tarjan.discover(start);
+            let mut visiting = vec![(start, dependencies(start), false)];
+
+            while let Some((chunk_id, targets, self_loop)) = visiting.last_mut() {
+                let chunk_id = *chunk_id;
+                if let Some(target) = targets.next() {
+                    *self_loop |= target == chunk_id;
+                    if !tarjan.index.contains_key(target) {
+                        tarjan.discover(target);
+                        visiting.push((target, dependencies(target), false));
+                    } else if tarjan.on_stack.contains(target) {
+                        tarjan.lower(chunk_id, tarjan.index[target]);
+                    }
+                    continue;
+                }
+
+                let self_loop = *self_loop;
+                visiting.pop();
+                if let Some((parent, ..)) = visiting.last() {
+                    tarjan.lower(parent, tarjan.low[chunk_id]);
+                }
+                tarjan.finish(chunk_id, self_loop);
+            }
+        }
+
+        for cycle in &tarjan.cycles {
+            let members: Vec<&str> = cycle.iter().map(|id| id.as_str()).collect();
+            tracing::warn!("Dependency cycle detected: {}", members.join(" -> "));
+        }
+        tarjan.cycles
+    }
+}
+
+/// Cost of following an edge in weighted path search
+fn edge_cost(edge: &GraphEdge) -> f32 {
+    -edge.weight.clamp(1e-6, 1.0).ln()
+}
+
+fn trace_path(previous: &HashMap<&ChunkId, &GraphEdge>, from: &ChunkId, to: &ChunkId) -> GraphPath {
+    let mut edges = Vec::new();
+    let mut current = to;
+    while current != from {
+        let edge = previous[current];
+        edges.push(edge.clone());
+        current = &edge.source;
+    }
+    edges.reverse();
+
+    let mut chunks = vec![from.clone()];
+    chunks.extend(edges.iter().map(|edge| edge.target.clone()));
+    GraphPath { chunks, edges, cost: 0.0 }
+}
+
+/// Entry of the weighted path search queue, ordered cheapest first
+struct Frontier<'a> {
+    cost: f32,
+    chunk_id: &'a ChunkId,
+}
+
+impl PartialEq for Frontier<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Frontier<'_> {}
+
+impl PartialOrd for Frontier<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Frontier<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+            .then_with(|| other.chunk_id.as_str().cmp(self.chunk_id.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(target: &str, relation_type: RelationType, weight: f32) -> WeightedEdge {
+        WeightedEdge {
+            target: ChunkId::new(target),
+            relation_type,
+            weight,
+            confidence: weight,
+        }
+    }
+
+    fn graph(edges: &[(&str, WeightedEdge)]) -> ChunkGraph {
+        let mut adjacency: HashMap<ChunkId, Vec<WeightedEdge>> = HashMap::new();
+        for (source, edge) in edges {
+            adjacency.entry(ChunkId::new(source)).or_default().push(edge.clone());
+        }
+        ChunkGraph::from_adjacency(&adjacency)
+    }
+
+    fn ids(chunks: &[ChunkId]) -> Vec<&str> {
+        chunks.iter().map(|id| id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_neighbors_include_inverse_view() {
+        let graph = graph(&[
+            ("page", edge("hook", RelationType::DependsOn, 1.0)),
+            ("controller", edge("other-controller", RelationType::Alternative, 0.8)),
+        ]);
+
+        let deps = graph.neighbors(&ChunkId::new("page"), &[RelationType::DependsOn]);
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].target.as_str(), "hook");
+
+        let dependents = graph.neighbors(&ChunkId::new("hook"), &[RelationType::DependedOnBy]);
+        assert_eq!(dependents[0].target.as_str(), "page");
+        assert!(graph.neighbors(&ChunkId::new("hook"), &[RelationType::DependsOn]).is_empty());
+
+        // Alternatives are symmetric
+        let alternatives = graph.neighbors(&ChunkId::new("other-controller"), &[RelationType::Alternative]);
+        assert_eq!(alternatives[0].target.as_str(), "controller");
+    }
+
+    #[test]
+    fn test_transitive_closure_reports_cycles() {
+        let graph = graph(&[
+            ("a", edge("b", RelationType::DependsOn, 1.0)),
+            ("b", edge("c", RelationType::DependsOn, 1.0)),
+            ("c", edge("b", RelationType::DependsOn, 1.0)),
+            ("c", edge("d", RelationType::DependsOn, 1.0)),
+            ("x", edge("a", RelationType::Similar, 0.9)),
+        ]);
+        let a = ChunkId::new("a");
+
+        let closure = graph.transitive_closure(&a, &[RelationType::DependsOn], None);
+        let reached: Vec<(&str, usize)> = closure.reachable.iter()
+            .map(|chunk| (chunk.chunk_id.as_str(), chunk.depth))
+            .collect();
+        assert_eq!(reached, vec![("b", 1), ("c", 2), ("d", 3)]);
+        assert_eq!(closure.warnings, vec![GraphWarning::DependencyCycle(vec![ChunkId::new("b"), ChunkId::new("c")])]);
+
+        let limited = graph.transitive_closure(&a, &[RelationType::DependsOn], Some(1));
+        assert_eq!(limited.reachable.len(), 1);
+        assert!(limited.warnings.is_empty());
+
+        assert_eq!(graph.dependency_cycles().len(), 1);
+    }
+
+    #[test]
+    fn test_long_dependency_chain_does_not_overflow() {
+        let names: Vec<String> = (0..50_000).map(|i| format!("chunk-{}", i)).collect();
+        let mut edges: Vec<(&str, WeightedEdge)> = names.windows(2)
+            .map(|pair| (pair[0].as_str(), edge(&pair[1], RelationType::DependsOn, 1.0)))
+            .collect();
+        edges.push((names[names.len() - 1].as_str(), edge(&names[names.len() - 2], RelationType::DependsOn, 1.0)));
+        let graph = graph(&edges);
+
+        let cycles = graph.dependency_cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(ids(&cycles[0]), vec![names[names.len() - 2].as_str(), names[names.len() - 1].as_str()]);
+    }
+
+    #[test]
+    fn test_shortest_and_weighted_paths() {
+        let graph = graph(&[
+            ("page", edge("model", RelationType::UsedWith, 0.1)),
+            ("page", edge("api", RelationType::DependsOn, 0.9)),
+            ("api", edge("model", RelationType::DependsOn, 0.9)),
+        ]);
+        let page = ChunkId::new("page");
+        let model = ChunkId::new("model");
+
+        let shortest = graph.shortest_path(&page, &model, &[]).unwrap();
+        assert_eq!(ids(&shortest.chunks), vec!["page", "model"]);
+        assert_eq!(shortest.cost, 1.0);
+
+        let weighted = graph.weighted_path(&page, &model, &[]).unwrap();
+        assert_eq!(ids(&weighted.chunks), vec!["page", "api", "model"]);
+        assert_eq!(weighted.edges.len(), 2);
+        assert!(weighted.cost < -(0.1f32.ln()));
+
+        assert!(graph.shortest_path(&page, &model, &[RelationType::Alternative]).is_none());
+        assert!(graph.weighted_path(&model, &ChunkId::new("missing"), &[]).is_none());
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let graph = graph(&[
+            ("a", edge("b", RelationType::Similar, 0.8)),
+            ("c", edge("b", RelationType::DependsOn, 1.0)),
+            ("d", edge("b", RelationType::DependsOn, 1.0)),
+            ("x", edge("y", RelationType::Similar, 0.8)),
+        ]);
+
+        let components = graph.connected_components(&[]);
+        assert_eq!(components.len(), 2);
+        assert_eq!(ids(&components[0]), vec!["a", "b", "c", "d"]);
+        assert_eq!(ids(&components[1]), vec!["x", "y"]);
+
+        let similar = graph.connected_components(&[RelationType::Similar]);
+        assert_eq!(ids(&similar[0]), vec!["a", "b"]);
+
+        // Direction does not split components
+        let dependencies = graph.connected_components(&[RelationType::DependsOn]);
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(ids(&dependencies[0]), vec!["b", "c", "d"]);
+    }
+}
@@ -0,0 +1,328 @@
+//! Hybrid lexical and vector retrieval
+//!
+//! Fuses tantivy BM25 hits with embedding nearest neighbors using reciprocal
+//! rank fusion or a weighted score combination, keeping per-leg evidence so
+//! every hit can report why it matched.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{ChunkId, LearningChunk};
+use crate::explain::{HitExplanation, RankingStage};
+use crate::search::IndexHit;
+
+/// How lexical and vector rankings are combined
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FusionStrategy {
+    /// Weighted reciprocal rank fusion: `sum(weight / (k + rank))`
+    ReciprocalRank { k: f32 },
+    /// Weighted sum of max-normalized BM25 and cosine similarity
+    WeightedScore,
+}
+
+impl Default for FusionStrategy {
+    fn default() -> Self {
+        Self::ReciprocalRank { k: 60.0 }
+    }
+}
+
+/// Hybrid retrieval request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HybridQuery {
+    /// Free-text query for the BM25 leg (skipped when empty)
+    pub text: String,
+    /// Query embedding for the vector leg (skipped when absent)
+    pub embedding: Option<Vec<f32>>,
+    /// Weight of the lexical leg
+    pub lexical_weight: f32,
+    /// Weight of the vector leg
+    pub vector_weight: f32,
+    /// Number of candidates fetched from the lexical leg
+    pub lexical_limit: usize,
+    /// Number of candidates fetched from the vector leg
+    pub vector_limit: usize,
+    /// Number of fused hits returned
+    pub limit: usize,
+    /// Fusion strategy
+    pub fusion: FusionStrategy,
+    /// Attach a ranking explanation to every hit
+    #[serde(default)]
+    pub explain: bool,
+}
+
+impl HybridQuery {
+    /// Create a query with default weights and limits
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            embedding: None,
+            lexical_weight: 1.0,
+            vector_weight: 1.0,
+            lexical_limit: 50,
+            vector_limit: 50,
+            limit: 10,
+            fusion: FusionStrategy::default(),
+            explain: false,
+        }
+    }
+
+    /// Set the query embedding
+    pub fn embedding(mut self, embedding: Vec<f32>) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+
+    /// Set the weights of the lexical and vector legs
+    pub fn weights(mut self, lexical: f32, vector: f32) -> Self {
+        self.lexical_weight = lexical;
+        self.vector_weight = vector;
+        self
+    }
+
+    /// Set the number of candidates fetched per leg
+    pub fn leg_limits(mut self, lexical: usize, vector: usize) -> Self {
+        self.lexical_limit = lexical;
+        self.vector_limit = vector;
+        self
+    }
+
+    /// Set the number of fused hits returned
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set the fusion strategy
+    pub fn fusion(mut self, fusion: FusionStrategy) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    /// Request a ranking explanation for every hit
+    pub fn explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+}
+
+/// Evidence that a retrieval leg contributed to a hit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MatchReason {
+    /// Matched the full-text query
+    Lexical {
+        /// 1-based rank within the lexical leg
+        rank: usize,
+        /// BM25 score
+        score: f32,
+        /// Index fields containing query terms
+        matched_fields: Vec<String>,
+    },
+    /// Close to the query embedding
+    Vector {
+        /// 1-based rank within the vector leg
+        rank: usize,
+        /// Cosine similarity to the query embedding
+        similarity: f32,
+    },
+}
+
+/// Fused hit before hydration from storage
+#[derive(Debug, Clone, PartialEq)]
+pub struct FusedHit {
+    /// ID of the matching chunk
+    pub chunk_id: ChunkId,
+    /// Fused relevance score
+    pub score: f32,
+    /// Contributions of each leg
+    pub reasons: Vec<MatchReason>,
+    /// Highlighted snippet from the lexical leg
+    pub snippet: Option<String>,
+    /// Ranking breakdown, present when explanations were requested
+    pub explanation: Option<HitExplanation>,
+}
+
+/// Hybrid search result with the full chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridHit {
+    /// The matching chunk, loaded from cache or storage
+    pub chunk: LearningChunk,
+    /// Fused relevance score
+    pub score: f32,
+    /// Why the chunk matched, one entry per contributing leg
+    pub reasons: Vec<MatchReason>,
+    /// Highlighted content fragment from the lexical leg
+    pub highlighted_snippet: Option<String>,
+    /// Ranking breakdown, present when explanations were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<HitExplanation>,
+}
+
+impl HybridHit {
+    /// Combine a fused hit with its hydrated chunk
+    pub fn from_fused_hit(hit: FusedHit, chunk: LearningChunk) -> Self {
+        Self {
+            chunk,
+            score: hit.score,
+            reasons: hit.reasons,
+            highlighted_snippet: hit.snippet,
+            explanation: hit.explanation,
+        }
+    }
+}
+
+/// Fuse lexical and vector rankings into a single list, best first
+pub fn fuse(lexical: Vec<IndexHit>, vector: Vec<(ChunkId, f32)>, query: &HybridQuery) -> Vec<FusedHit> {
+    let max_lexical = lexical.iter().map(|hit| hit.score).fold(0.0f32, f32::max);
+    let mut fused: HashMap<ChunkId, FusedHit> = HashMap::new();
+    // Weighted (lexical, vector) contributions per chunk, reported in explanations
+    let mut contributions: HashMap<ChunkId, (f32, f32)> = HashMap::new();
+
+    for (i, hit) in lexical.into_iter().enumerate() {
+        let rank = i + 1;
+        let contribution = match query.fusion {
+            FusionStrategy::ReciprocalRank { k } => query.lexical_weight / (k + rank as f32),
+            FusionStrategy::WeightedScore if max_lexical > 0.0 => query.lexical_weight * hit.score / max_lexical,
+            FusionStrategy::WeightedScore => 0.0,
+        };
+
+        let entry = fused.entry(hit.chunk_id.clone()).or_insert_with(|| FusedHit {
+            chunk_id: hit.chunk_id.clone(),
+            score: 0.0,
+            reasons: Vec::new(),
+            snippet: None,
+            explanation: None,
+        });
+        entry.score += contribution;
+        entry.snippet = hit.snippet;
+        entry.explanation = hit.explanation;
+        contributions.entry(hit.chunk_id).or_default().0 += contribution;
+        entry.reasons.push(MatchReason::Lexical {
+            rank,
+            score: hit.score,
+            matched_fields: hit.matched_fields,
+        });
+    }
+
+    for (i, (chunk_id, similarity)) in vector.into_iter().enumerate() {
+        let rank = i + 1;
+        let contribution = match query.fusion {
+            FusionStrategy::ReciprocalRank { k } => query.vector_weight / (k + rank as f32),
+            FusionStrategy::WeightedScore => query.vector_weight * similarity.clamp(0.0, 1.0),
+        };
+
+        contributions.entry(chunk_id.clone()).or_default().1 += contribution;
+        let entry = fused.entry(chunk_id.clone()).or_insert_with(|| FusedHit {
+            chunk_id,
+            score: 0.0,
+            reasons: Vec::new(),
+            snippet: None,
+            explanation: None,
+        });
+        entry.score += contribution;
+        entry.reasons.push(MatchReason::Vector { rank, similarity });
+    }
+
+    let mut hits: Vec<FusedHit> = fused.into_values().collect();
+    hits.sort_by(|a, b| {
+        b.score.total_cmp(&a.score).then_with(|| a.chunk_id.as_str().cmp(b.chunk_id.as_str()))
+    });
+    hits.truncate(query.limit);
+
+    if query.explain {
+        for (i, hit) in hits.iter_mut().enumerate() {
+            let (lexical, vector) = contributions.get(&hit.chunk_id).copied().unwrap_or_default();
+            let explanation = hit.explanation.get_or_insert_with(HitExplanation::default);
+            explanation.score = hit.score;
+            explanation.stages.push(RankingStage::Fusion {
+                strategy: query.fusion,
+                lexical,
+                vector,
+                score: hit.score,
+                rank: i + 1,
+            });
+        }
+    }
+
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lexical_hit(id: &str, score: f32) -> IndexHit {
+        IndexHit {
+            chunk_id: ChunkId::new(id),
+            score,
+            matched_fields: vec!["content".to_string()],
+            snippet: None,
+            explanation: None,
+        }
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let lexical = vec![lexical_hit("a", 9.0), lexical_hit("b", 4.0)];
+        let vector = vec![(ChunkId::new("b"), 0.9), (ChunkId::new("c"), 0.8)];
+
+        let hits = fuse(lexical, vector, &HybridQuery::new("query"));
+        let ids: Vec<&str> = hits.iter().map(|h| h.chunk_id.as_str()).collect();
+
+        // `b` appears in both legs and wins
+        assert_eq!(ids, vec!["b", "a", "c"]);
+        assert_eq!(hits[0].reasons.len(), 2);
+        assert!(matches!(hits[0].reasons[0], MatchReason::Lexical { rank: 2, .. }));
+        assert!(matches!(hits[0].reasons[1], MatchReason::Vector { rank: 1, .. }));
+        assert!((hits[0].score - (1.0 / 62.0 + 1.0 / 61.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_weighted_fusion_respects_weights() {
+        let lexical = vec![lexical_hit("a", 10.0), lexical_hit("b", 5.0)];
+        let vector = vec![(ChunkId::new("c"), 0.95)];
+
+        let query = HybridQuery::new("query")
+            .fusion(FusionStrategy::WeightedScore)
+            .weights(1.0, 0.0)
+            .limit(2);
+        let hits = fuse(lexical.clone(), vector.clone(), &query);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].chunk_id.as_str(), "a");
+        assert!((hits[1].score - 0.5).abs() < 1e-6);
+
+        let query = query.weights(0.2, 1.0);
+        let hits = fuse(lexical, vector, &query);
+        assert_eq!(hits[0].chunk_id.as_str(), "c");
+    }
+
+    #[test]
+    fn test_fusion_stage_explains_contributions() {
+        let mut lexical = vec![lexical_hit("a", 9.0), lexical_hit("b", 4.0)];
+        lexical[0].explanation = Some(HitExplanation {
+            score: 9.0,
+            stages: vec![RankingStage::Lexical { rank: 1, score: 9.0 }],
+            ..Default::default()
+        });
+        let vector = vec![(ChunkId::new("b"), 0.9)];
+
+        let hits = fuse(lexical.clone(), vector.clone(), &HybridQuery::new("query"));
+        let b = hits.iter().find(|hit| hit.chunk_id.as_str() == "b").unwrap();
+        assert!(b.explanation.is_none());
+
+        let hits = fuse(lexical, vector, &HybridQuery::new("query").explain(true));
+        for (i, hit) in hits.iter().enumerate() {
+            let explanation = hit.explanation.as_ref().unwrap();
+            let Some(RankingStage::Fusion { lexical, vector, score, rank, .. }) = explanation.stages.last() else {
+                panic!("missing fusion stage for {}", hit.chunk_id);
+            };
+            assert_eq!(*rank, i + 1);
+            assert_eq!(*score, hit.score);
+            assert!((lexical + vector - hit.score).abs() < 1e-6);
+        }
+
+        // The lexical stage recorded by the index is kept ahead of fusion
+        let a = hits.iter().find(|hit| hit.chunk_id.as_str() == "a").unwrap();
+        assert_eq!(a.explanation.as_ref().unwrap().stages.len(), 2);
+    }
+}
@@ -11,7 +11,7 @@
 //! - Cross-framework pattern mapping
 //! - Concurrent access with minimal locking
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -21,7 +21,7 @@ use dashmap::DashMap;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use tantivy::{collector::TopDocs, query::QueryParser, schema::*, Index, IndexReader, IndexWriter};
-use tokio::sync::RwLock as AsyncRwLock;
+use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
 use uuid::Uuid;
 
 pub mod storage;
@@ -29,12 +29,50 @@ pub mod search;
 pub mod cache;
 pub mod chunk;
 pub mod patterns;
+pub mod filter;
+pub mod query;
+pub mod tokenizer;
+pub mod vector_index;
+pub mod hybrid;
+pub mod alias;
+pub mod suggest;
+pub mod query_syntax;
+pub mod explain;
+pub mod pattern_store;
+pub mod candidates;
+pub mod graph;
+pub mod export;
+pub mod feedback;
+pub mod similarity;
+pub mod combinations;
+pub mod rules;
+pub mod pattern_usage;
+pub mod chunker;
 
 pub use chunk::*;
 pub use storage::*;
 pub use search::*;
 pub use cache::*;
 pub use patterns::*;
+pub use filter::*;
+pub use query::*;
+pub use tokenizer::*;
+pub use vector_index::*;
+pub use hybrid::*;
+pub use alias::*;
+pub use suggest::*;
+pub use query_syntax::*;
+pub use explain::*;
+pub use pattern_store::*;
+pub use candidates::*;
+pub use graph::*;
+pub use export::*;
+pub use feedback::*;
+pub use similarity::*;
+pub use combinations::*;
+pub use rules::*;
+pub use pattern_usage::*;
+pub use chunker::*;
 
 /// Configuration for the memory engine
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +89,18 @@ pub struct MemoryConfig {
     pub enable_search: bool,
     /// Embedding dimensions (for ML integration)
     pub embedding_dim: usize,
+    /// Persist the cache hot set on shutdown and restore it on startup
+    pub persist_hot_set: bool,
+    /// YAML or JSON file mapping canonical framework names to aliases
+    pub alias_file: Option<PathBuf>,
+    /// Catalog directory whose YAML entries seed additional aliases
+    pub catalog_path: Option<PathBuf>,
+    /// Pattern analysis settings; the embedding dimension is taken from `embedding_dim`
+    pub patterns: PatternConfig,
+    /// How user feedback adjusts chunk quality and ranking
+    pub feedback: FeedbackConfig,
+    /// How code and Spike templates are split into function-level parts
+    pub chunking: ChunkerConfig,
 }
 
 impl Default for MemoryConfig {
@@ -62,6 +112,12 @@ impl Default for MemoryConfig {
             compression_level: 6,
             enable_search: true,
             embedding_dim: 384, // All-MiniLM-L6-v2 default
+            persist_hot_set: true,
+            alias_file: None,
+            catalog_path: None,
+            patterns: PatternConfig::default(),
+            feedback: FeedbackConfig::default(),
+            chunking: ChunkerConfig::default(),
         }
     }
 }
@@ -74,6 +130,14 @@ pub struct MemoryEngine {
     cache: Arc<LruCache>,
     search_engine: Option<Arc<SearchEngine>>,
     pattern_analyzer: Arc<PatternAnalyzer>,
+    feedback: Arc<FeedbackTracker>,
+    vector_index: Arc<VectorIndex>,
+    suggestions: Arc<SuggestionIndex>,
+    aliases: Arc<AliasTable>,
+    // Background search index rebuild, if one was started
+    reindex_task: parking_lot::Mutex<Option<tokio::task::JoinHandle<Result<usize>>>>,
+    // Serializes mutations so storage, cache, index and patterns stay coherent
+    write_lock: AsyncMutex<()>,
     // Runtime statistics
     stats: Arc<RwLock<EngineStats>>,
 }
@@ -102,11 +166,23 @@ impl MemoryEngine {
             .await
             .context("Failed to create storage directory")?;
 
+        // Configured aliases take precedence over built-in ones, catalog entries only fill gaps
+        let mut aliases = AliasTable::new();
+        if let Some(alias_file) = &config.alias_file {
+            aliases.load_file(alias_file)?;
+        }
+        aliases.extend_builtin();
+        if let Some(catalog_path) = &config.catalog_path {
+            aliases.seed_from_catalog(catalog_path)?;
+        }
+        let aliases = Arc::new(aliases);
+
         // Initialize storage backend
         let storage = Arc::new(
             HybridStorage::new(&config.storage_path, config.compression_level)
                 .await
                 .context("Failed to initialize storage backend")?
+                .with_aliases(aliases.clone())
         );
 
         // Initialize LRU cache
@@ -116,8 +192,12 @@ impl MemoryEngine {
 
         // Initialize search engine if enabled
         let search_engine = if config.enable_search {
-            let search_path = config.storage_path.join("search_index");
-            let engine = SearchEngine::new(&search_path)
+            let search_config = SearchConfig {
+                index_path: config.storage_path.join("search_index"),
+                aliases: aliases.clone(),
+                ..Default::default()
+            };
+            let engine = SearchEngine::with_config(search_config)
                 .await
                 .context("Failed to initialize search engine")?;
             Some(Arc::new(engine))
@@ -125,10 +205,62 @@ impl MemoryEngine {
             None
         };
 
-        // Initialize pattern analyzer
-        let pattern_analyzer = Arc::new(PatternAnalyzer::new(config.embedding_dim));
+        // Initialize pattern analyzer from its persisted state
+        let pattern_config = PatternConfig {
+            embedding_dim: config.embedding_dim,
+            ..config.patterns.clone()
+        };
+        let pattern_analyzer = PatternAnalyzer::with_store(pattern_config, storage.pattern_store()?)
+            .context("Failed to load pattern state")?;
+        if config.patterns.rules_dir.is_some() {
+            pattern_analyzer.reload_rules().context("Failed to load pattern rules")?;
+        }
+        if pattern_analyzer.needs_rebuild() {
+            pattern_analyzer.rebuild(storage.scan_chunks())
+                .await
+                .context("Failed to rebuild pattern state")?;
+        }
+        let pattern_analyzer = Arc::new(pattern_analyzer);
+
+        let feedback = Arc::new(
+            FeedbackTracker::with_store(config.feedback.clone(), storage.feedback_store()?)
+                .context("Failed to load chunk feedback")?
+        );
+
+        // Load stored embeddings and suggestion terms in a single pass over storage
+        let vector_index = Arc::new(VectorIndex::new(config.embedding_dim));
+        let suggestions = Arc::new(SuggestionIndex::new());
+        let loaded = {
+            let storage = storage.clone();
+            let vector_index = vector_index.clone();
+            let suggestions = suggestions.clone();
+            tokio::task::spawn_blocking(move || -> Result<usize> {
+                let mut loaded = 0;
+                for chunk in storage.scan_chunks() {
+                    let chunk = chunk?;
+                    vector_index.upsert(&chunk);
+                    suggestions.upsert(&chunk);
+                    loaded += 1;
+                }
+                suggestions.commit()?;
+                Ok(loaded)
+            })
+                .await
+                .context("Index loading task failed")?
+                .context("Failed to load vector and suggestion indexes")?
+        };
+        tracing::info!(
+            "Loaded {} chunks with {} embeddings and {} suggestion terms",
+            loaded,
+            vector_index.len(),
+            suggestions.len()
+        );
 
-        let stats = Arc::new(RwLock::new(EngineStats::default()));
+        // The chunk count carries over from storage; the other counters are per run
+        let stats = Arc::new(RwLock::new(EngineStats {
+            total_chunks: storage.get_stats().total_chunks,
+            ..Default::default()
+        }));
 
         let engine = Self {
             config,
@@ -136,42 +268,78 @@ impl MemoryEngine {
             cache,
             search_engine,
             pattern_analyzer,
+            feedback,
+            vector_index,
+            suggestions,
+            aliases,
+            reindex_task: parking_lot::Mutex::new(None),
+            write_lock: AsyncMutex::new(()),
             stats,
         };
 
         // Load existing chunks into cache on startup
         engine.warmup_cache().await?;
 
+        // Repopulate an index that was reset because its schema changed
+        if engine.search_engine.as_ref().is_some_and(|search_engine| search_engine.needs_rebuild()) {
+            tracing::info!("Search index schema changed, reindexing from storage in the background");
+            engine.start_reindex();
+        }
+
         tracing::info!("Memory engine initialized successfully");
         Ok(engine)
     }
 
     /// Store a learning chunk in the memory engine
-    pub async fn store_chunk(&self, chunk: LearningChunk) -> Result<ChunkId> {
+    ///
+    /// Storing a chunk whose ID already exists replaces the previous version.
+    /// The search index is committed at most once per commit interval; the
+    /// next search commits any writes still pending.
+    pub async fn store_chunk(&self, mut chunk: LearningChunk) -> Result<ChunkId> {
+        self.aliases.normalize_chunk(&mut chunk);
         let chunk_id = chunk.id.clone();
         
         tracing::debug!("Storing chunk: {}", chunk_id);
 
+        let _guard = self.write_lock.lock().await;
+        let previous = self.storage.get_chunk(&chunk_id).await?;
+        let existed = previous.is_some();
+        if let Some(previous) = &previous {
+            carry_inverse_relations(previous, &mut chunk);
+        }
+        self.feedback.rebase(&mut chunk)?;
+
         // Store in persistent storage
         self.storage.store_chunk(&chunk).await
             .context("Failed to store chunk to disk")?;
 
         // Add to hot cache
         self.cache.insert(chunk_id.clone(), chunk.clone()).await;
+        self.vector_index.upsert(&chunk);
+        self.suggestions.upsert(&chunk);
 
         // Index for search if enabled
         if let Some(search_engine) = &self.search_engine {
             search_engine.index_chunk(&chunk).await
                 .context("Failed to index chunk for search")?;
+            search_engine.commit_if_due().await?;
         }
+        self.suggestions.commit()?;
 
         // Analyze patterns
+        if existed {
+            self.pattern_analyzer.remove_chunk(&chunk_id).await?;
+        }
         self.pattern_analyzer.analyze_chunk(&chunk).await?;
 
+        self.sync_inverse_relations(previous.as_ref(), &chunk).await?;
+
         // Update stats
         {
             let mut stats = self.stats.write();
-            stats.total_chunks += 1;
+            if !existed {
+                stats.total_chunks += 1;
+            }
             stats.disk_writes += 1;
             stats.last_update = Some(Utc::now());
         }
@@ -179,6 +347,188 @@ impl MemoryEngine {
         Ok(chunk_id)
     }
 
+    /// Store several chunks with one storage batch and one search commit
+    pub async fn store_chunks_batch(&self, mut chunks: Vec<LearningChunk>) -> Result<Vec<ChunkId>> {
+        if chunks.is_empty() {
+            return Ok(vec![]);
+        }
+
+        for chunk in &mut chunks {
+            self.aliases.normalize_chunk(chunk);
+        }
+
+        tracing::debug!("Storing batch of {} chunks", chunks.len());
+
+        let _guard = self.write_lock.lock().await;
+        self.store_chunks_locked(chunks).await
+    }
+
+    /// Store normalized chunks as one batch; callers must hold the write lock
+    async fn store_chunks_locked(&self, mut chunks: Vec<LearningChunk>) -> Result<Vec<ChunkId>> {
+        let mut existing = HashMap::new();
+        let mut previous = HashMap::new();
+        for chunk in &mut chunks {
+            let stored = self.storage.get_chunk(&chunk.id).await?;
+            existing.insert(chunk.id.clone(), stored.is_some());
+            if let Some(stored) = stored {
+                carry_inverse_relations(&stored, chunk);
+                previous.insert(chunk.id.clone(), stored);
+            }
+            self.feedback.rebase(chunk)?;
+        }
+
+        self.storage.store_chunks_batch(&chunks).await
+            .context("Failed to store chunk batch to disk")?;
+
+        for chunk in &chunks {
+            self.cache.insert(chunk.id.clone(), chunk.clone()).await;
+            self.vector_index.upsert(chunk);
+            self.suggestions.upsert(chunk);
+        }
+
+        if let Some(search_engine) = &self.search_engine {
+            for chunk in &chunks {
+                search_engine.index_chunk(chunk).await
+                    .context("Failed to index chunk for search")?;
+            }
+            search_engine.commit().await?;
+        }
+        self.suggestions.commit()?;
+
+        for chunk in &chunks {
+            if existing.get(&chunk.id).copied().unwrap_or(false) {
+                self.pattern_analyzer.remove_chunk(&chunk.id).await?;
+            }
+            self.pattern_analyzer.analyze_chunk(chunk).await?;
+        }
+
+        for chunk in &chunks {
+            self.sync_inverse_relations(previous.get(&chunk.id), chunk).await?;
+        }
+
+        {
+            let mut stats = self.stats.write();
+            stats.total_chunks += existing.values().filter(|existed| !**existed).count() as u64;
+            stats.disk_writes += 1;
+            stats.last_update = Some(Utc::now());
+        }
+
+        Ok(chunks.into_iter().map(|chunk| chunk.id).collect())
+    }
+
+    /// Store a chunk together with the parts it splits into
+    ///
+    /// Code chunks and Spike templates are split by the [`CodeChunker`] into
+    /// child chunks for their files and functions, classes, components and
+    /// routes, each `PartOf` its parent. Parts stored for a previous version
+    /// that no longer exist are deleted. Returns the IDs of the parent and
+    /// its parts.
+    pub async fn store_chunk_with_parts(&self, mut chunk: LearningChunk) -> Result<Vec<ChunkId>> {
+        let parts = CodeChunker::new(self.config.chunking.clone()).split(&chunk);
+        tracing::debug!("Split chunk {} into {} parts", chunk.id, parts.len());
+
+        mark_split(&mut chunk);
+
+        let mut chunks = Vec::with_capacity(parts.len() + 1);
+        chunks.push(chunk);
+        chunks.extend(parts);
+        for chunk in &mut chunks {
+            self.aliases.normalize_chunk(chunk);
+        }
+
+        let _guard = self.write_lock.lock().await;
+        self.remove_stale_parts(&chunks[0].id, &chunks[1..]).await?;
+        self.store_chunks_locked(chunks).await
+    }
+
+    /// Replace an existing chunk, keeping cache, search index and patterns in sync
+    ///
+    /// A chunk stored with parts (see [`store_chunk_with_parts`](Self::store_chunk_with_parts))
+    /// is split again; parts that no longer exist are deleted.
+    pub async fn update_chunk(&self, mut chunk: LearningChunk) -> Result<()> {
+        tracing::debug!("Updating chunk: {}", chunk.id);
+        self.aliases.normalize_chunk(&mut chunk);
+
+        let _guard = self.write_lock.lock().await;
+
+        let Some(previous) = self.storage.get_chunk(&chunk.id).await? else {
+            return Err(anyhow::anyhow!("Chunk not found: {}", chunk.id));
+        };
+        carry_inverse_relations(&previous, &mut chunk);
+        self.feedback.rebase(&mut chunk)?;
+        let split = is_split(&previous);
+        if split {
+            mark_split(&mut chunk);
+        }
+
+        self.storage.update_chunk(&chunk).await
+            .context("Failed to update chunk on disk")?;
+
+        self.cache.insert(chunk.id.clone(), chunk.clone()).await;
+        self.vector_index.upsert(&chunk);
+        self.suggestions.upsert(&chunk);
+
+        if let Some(search_engine) = &self.search_engine {
+            search_engine.index_chunk(&chunk).await
+                .context("Failed to reindex chunk for search")?;
+            search_engine.commit_if_due().await?;
+        }
+        self.suggestions.commit()?;
+
+        self.pattern_analyzer.remove_chunk(&chunk.id).await?;
+        self.pattern_analyzer.analyze_chunk(&chunk).await?;
+
+        self.sync_inverse_relations(Some(&previous), &chunk).await?;
+
+        {
+            let mut stats = self.stats.write();
+            stats.disk_writes += 1;
+            stats.last_update = Some(Utc::now());
+        }
+
+        if split {
+            let mut parts = CodeChunker::new(self.config.chunking.clone()).split(&chunk);
+            for part in &mut parts {
+                self.aliases.normalize_chunk(part);
+            }
+            self.remove_stale_parts(&chunk.id, &parts).await?;
+            if !parts.is_empty() {
+                self.store_chunks_locked(parts).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Delete a chunk from storage, cache, search index and pattern graph
+    ///
+    /// Parts split from the chunk are deleted with it.
+    pub async fn delete_chunk(&self, chunk_id: &ChunkId) -> Result<bool> {
+        let _guard = self.write_lock.lock().await;
+        let deleted = self.remove_chunks(std::slice::from_ref(chunk_id)).await?;
+        Ok(!deleted.is_empty())
+    }
+
+    /// Delete every chunk matching the filter, returning the number removed
+    ///
+    /// Parts split from a matching chunk are deleted and counted as well.
+    pub async fn delete_where(&self, filter: &ChunkFilter) -> Result<usize> {
+        if filter.is_empty() {
+            return Err(anyhow::anyhow!("Refusing to delete with an empty filter"));
+        }
+
+        let _guard = self.write_lock.lock().await;
+
+        let chunk_ids: Vec<ChunkId> = self.storage.find_chunks(filter).await?
+            .into_iter()
+            .map(|chunk| chunk.id)
+            .collect();
+
+        let deleted = self.remove_chunks(&chunk_ids).await?;
+        tracing::info!("Deleted {} chunks matching filter", deleted.len());
+        Ok(deleted.len())
+    }
+
     /// Retrieve a learning chunk by ID
     pub async fn get_chunk(&self, chunk_id: &ChunkId) -> Result<Option<LearningChunk>> {
         tracing::debug!("Retrieving chunk: {}", chunk_id);
@@ -212,20 +562,279 @@ impl MemoryEngine {
 
     /// Search for chunks using full-text search
     pub async fn search_chunks(&self, query: &str, limit: usize) -> Result<Vec<LearningChunk>> {
-        if let Some(search_engine) = &self.search_engine {
+        let hits = self.search(query, limit).await?;
+        Ok(hits.into_iter().map(|hit| hit.chunk).collect())
+    }
+
+    /// Search and return hits hydrated with the full chunks, scores and snippets
+    ///
+    /// Chunks are loaded through the hot cache with storage fallback; index
+    /// entries whose chunk no longer exists are skipped. [`RERANK_DEPTH`]
+    /// times `limit` hits are ranked by feedback before the limit applies.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let search_engine = self.search_engine.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Search engine not enabled"))?;
+
+        {
             let mut stats = self.stats.write();
             stats.search_queries += 1;
-            drop(stats);
+        }
 
-            search_engine.search(query, limit).await
-        } else {
-            Err(anyhow::anyhow!("Search engine not enabled"))
+        let index_hits = search_engine.search_hits(query, limit.saturating_mul(RERANK_DEPTH)).await?;
+        let mut hits = self.hydrate_hits(index_hits).await?;
+        self.feedback.rerank(&mut hits);
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    /// Typo-tolerant search across content, tags, patterns and frameworks
+    ///
+    /// Exact matches rank above hits that only match within an edit distance.
+    pub async fn fuzzy_search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let search_engine = self.search_engine.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Search engine not enabled"))?;
+
+        {
+            let mut stats = self.stats.write();
+            stats.search_queries += 1;
+        }
+
+        let index_hits = search_engine.fuzzy_search_hits(query, limit.saturating_mul(RERANK_DEPTH)).await?;
+        let mut hits = self.hydrate_hits(index_hits).await?;
+        self.feedback.rerank(&mut hits);
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    /// Complete a prefix over spike names, tags, frameworks and pattern names
+    ///
+    /// Terms are ranked by the usage and quality of the chunks containing them.
+    /// An empty `kinds` slice accepts every kind of term.
+    pub fn suggest(&self, prefix: &str, kinds: &[SuggestionKind], limit: usize) -> Vec<Suggestion> {
+        self.suggestions.complete(prefix, kinds, limit)
+    }
+
+    /// Suggest a corrected query, typically after a search returned no hits
+    pub fn did_you_mean(&self, query: &str) -> Option<String> {
+        self.suggestions.did_you_mean(query)
+    }
+
+    /// Known terms close to a possibly misspelled term
+    pub fn spelling_corrections(&self, term: &str, limit: usize) -> Vec<Suggestion> {
+        self.suggestions.corrections(term, limit)
+    }
+
+    /// Run a structured query and return hydrated hits with facet counts
+    ///
+    /// Like [`search`](Self::search), more hits than the query's limit are
+    /// ranked by feedback before it applies.
+    pub async fn query(&self, query: &SearchQuery) -> Result<SearchResults<SearchHit>> {
+        let search_engine = self.search_engine.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Search engine not enabled"))?;
+
+        {
+            let mut stats = self.stats.write();
+            stats.search_queries += 1;
+        }
+
+        let window = SearchQuery { limit: query.limit.saturating_mul(RERANK_DEPTH), ..query.clone() };
+        let mut results = search_engine.query(&window).await?;
+        let index_hits = std::mem::take(&mut results.hits);
+        let mut hits = self.hydrate_hits(index_hits).await?;
+        self.feedback.rerank(&mut hits);
+        hits.truncate(query.limit);
+
+        Ok(results.with_hits(hits))
+    }
+
+    /// Run a structured query written in the query string syntax
+    ///
+    /// See [`query_syntax`] for the accepted syntax, e.g.
+    /// `framework:nextjs type:component quality>0.8 -tag:deprecated "server action"`.
+    pub async fn query_str(&self, input: &str) -> Result<SearchResults<SearchHit>> {
+        let query = parse_query(input)?;
+        self.query(&query).await
+    }
+
+    /// Run BM25 and embedding nearest-neighbor retrieval in parallel and fuse the rankings
+    ///
+    /// The lexical leg is skipped for empty text or when search is disabled,
+    /// the vector leg when the query carries no embedding.
+    pub async fn hybrid_search(&self, query: &HybridQuery) -> Result<Vec<HybridHit>> {
+        {
+            let mut stats = self.stats.write();
+            stats.search_queries += 1;
+        }
+
+        let lexical = async {
+            match &self.search_engine {
+                Some(search_engine) if !query.text.trim().is_empty() && query.explain => {
+                    search_engine.explain_hits(&query.text, query.lexical_limit).await
+                }
+                Some(search_engine) if !query.text.trim().is_empty() => {
+                    search_engine.search_hits(&query.text, query.lexical_limit).await
+                }
+                _ => Ok(Vec::new()),
+            }
+        };
+
+        let vector = async {
+            match &query.embedding {
+                Some(embedding) => {
+                    let vector_index = self.vector_index.clone();
+                    let embedding = embedding.clone();
+                    let limit = query.vector_limit;
+                    tokio::task::spawn_blocking(move || vector_index.search(&embedding, limit))
+                        .await
+                        .context("Vector search task failed")
+                }
+                None => Ok(Vec::new()),
+            }
+        };
+
+        let (lexical, vector) = tokio::try_join!(lexical, vector)?;
+        let mut hits = Vec::new();
+
+        // Fuse more hits than requested so feedback can lift chunks from beyond the limit
+        let window = HybridQuery { limit: query.limit.saturating_mul(RERANK_DEPTH), ..query.clone() };
+        for fused in fuse(lexical, vector, &window) {
+            match self.get_chunk(&fused.chunk_id).await? {
+                Some(chunk) => hits.push(HybridHit::from_fused_hit(fused, chunk)),
+                None => tracing::warn!("Retrieval references missing chunk: {}", fused.chunk_id),
+            }
+        }
+        self.feedback.rerank(&mut hits);
+        hits.truncate(query.limit);
+
+        Ok(hits)
+    }
+
+    /// Load the chunks behind index hits, skipping entries whose chunk no longer exists
+    async fn hydrate_hits(&self, index_hits: Vec<IndexHit>) -> Result<Vec<SearchHit>> {
+        let mut hits = Vec::with_capacity(index_hits.len());
+
+        for index_hit in index_hits {
+            match self.get_chunk(&index_hit.chunk_id).await? {
+                Some(chunk) => hits.push(SearchHit::from_index_hit(index_hit, chunk)),
+                None => tracing::warn!("Search index references missing chunk: {}", index_hit.chunk_id),
+            }
         }
+
+        Ok(hits)
     }
 
     /// Find similar chunks based on content and patterns
+    ///
+    /// Candidates come from the pattern analyzer's indexes plus the nearest
+    /// embedding neighbours, capped at the configured candidate budget, and
+    /// are then scored against the chunk. Matches are ordered by similarity
+    /// scaled by each chunk's feedback; the reported scores stay unscaled.
     pub async fn find_similar(&self, chunk: &LearningChunk, limit: usize) -> Result<Vec<SimilarityMatch>> {
-        self.pattern_analyzer.find_similar(chunk, limit).await
+        let budget = self.pattern_analyzer.config().candidate_budget;
+
+        let neighbours = match &chunk.embedding {
+            Some(embedding) => {
+                let vector_index = self.vector_index.clone();
+                let embedding = embedding.clone();
+                tokio::task::spawn_blocking(move || vector_index.search(&embedding, budget))
+                    .await
+                    .context("Vector search task failed")?
+            }
+            None => Vec::new(),
+        };
+
+        let candidate_ids = self.pattern_analyzer.similarity_candidates(chunk, &neighbours);
+        let storage = Arc::clone(&self.storage);
+        let candidates = tokio::task::spawn_blocking(move || storage.get_chunks_blocking(&candidate_ids))
+            .await
+            .context("Candidate loader task failed")??;
+
+        // Rank every match so feedback can lift chunks from beyond the limit
+        let mut matches = self.pattern_analyzer.find_similar(chunk, candidates, usize::MAX).await?;
+        let ranking = |similarity: &SimilarityMatch| similarity.score * self.feedback.similarity_boost(&similarity.chunk.id);
+        matches.sort_by(|a, b| ranking(b).total_cmp(&ranking(a)));
+        matches.truncate(limit);
+        Ok(matches)
+    }
+
+    /// Snapshot of chunk relationships for traversal queries
+    ///
+    /// Holds declared relationships and the inferred ones from the last
+    /// relationship rebuild. Take one snapshot to run several queries.
+    pub fn relationship_graph(&self) -> ChunkGraph {
+        self.pattern_analyzer.graph()
+    }
+
+    /// Relationships of a chunk with the given types (all if empty)
+    pub fn neighbors(&self, chunk_id: &ChunkId, types: &[RelationType]) -> Vec<GraphEdge> {
+        self.relationship_graph().neighbors(chunk_id, types)
+    }
+
+    /// Chunks reachable through relationships of the given types, up to `max_depth` hops
+    ///
+    /// Dependency cycles among the reached chunks are returned as warnings.
+    pub fn transitive_relations(
+        &self,
+        chunk_id: &ChunkId,
+        types: &[RelationType],
+        max_depth: Option<usize>,
+    ) -> TransitiveClosure {
+        self.relationship_graph().transitive_closure(chunk_id, types, max_depth)
+    }
+
+    /// Path with the fewest hops between two chunks
+    pub fn shortest_path(&self, from: &ChunkId, to: &ChunkId, types: &[RelationType]) -> Option<GraphPath> {
+        self.relationship_graph().shortest_path(from, to, types)
+    }
+
+    /// Path through the strongest relationships between two chunks
+    pub fn weighted_path(&self, from: &ChunkId, to: &ChunkId, types: &[RelationType]) -> Option<GraphPath> {
+        self.relationship_graph().weighted_path(from, to, types)
+    }
+
+    /// Groups of chunks connected through relationships of the given types
+    pub fn connected_components(&self, types: &[RelationType]) -> Vec<Vec<ChunkId>> {
+        self.relationship_graph().connected_components(types)
+    }
+
+    /// Every `DependsOn` cycle among stored chunks
+    pub fn dependency_cycles(&self) -> Vec<Vec<ChunkId>> {
+        self.relationship_graph().dependency_cycles()
+    }
+
+    /// Knowledge graph of chunks, patterns and frameworks matching a filter
+    pub fn knowledge_graph(&self, filter: &GraphExportFilter) -> GraphExport {
+        self.pattern_analyzer.export_graph(filter)
+    }
+
+    /// Usage and trend of every detected code pattern, most used first
+    pub fn pattern_statistics(&self) -> Vec<PatternStatistics> {
+        self.pattern_analyzer.pattern_statistics(Utc::now())
+    }
+
+    /// Reload pattern detection rules from the configured rule directory
+    ///
+    /// The directory is also checked automatically while chunks are analyzed.
+    /// Returns the number of active rules; invalid packs leave the current
+    /// rules in place.
+    pub fn reload_pattern_rules(&self) -> Result<usize> {
+        Ok(self.pattern_analyzer.reload_rules()?.len())
+    }
+
+    /// Framework combinations including all of `frameworks`, by strength of association
+    ///
+    /// Framework names may be aliases. Only combinations used by at least
+    /// `min_support` of the framework-tagged chunks are returned.
+    pub fn get_framework_combinations<S: AsRef<str>>(&self, frameworks: &[S], min_support: f32) -> Vec<FrameworkCombination> {
+        let frameworks: Vec<String> = frameworks.iter()
+            .map(|framework| self.aliases.normalize(framework.as_ref()))
+            .collect();
+        self.pattern_analyzer.get_framework_combinations(&frameworks, min_support)
+    }
+
+    /// Render the knowledge graph as GraphML, Graphviz DOT or JSON node-link
+    pub fn export_graph(&self, format: GraphExportFormat, filter: &GraphExportFilter) -> Result<String> {
+        self.knowledge_graph(filter).render(format)
     }
 
     /// Get chunks related to a specific framework (e.g., "nextjs", "laravel")
@@ -235,8 +844,84 @@ impl MemoryEngine {
     }
 
     /// Learn from user feedback to improve pattern matching
+    ///
+    /// Updates the chunk's feedback-adjusted quality, writes it to the chunk's
+    /// `quality_score` and reindexes the chunk so filters and ranking see it.
     pub async fn learn_from_feedback(&self, chunk_id: &ChunkId, feedback: UserFeedback) -> Result<()> {
-        self.pattern_analyzer.update_from_feedback(chunk_id, feedback).await
+        let _guard = self.write_lock.lock().await;
+
+        let Some(mut chunk) = self.storage.get_chunk(chunk_id).await? else {
+            return Err(anyhow::anyhow!("Chunk not found: {}", chunk_id));
+        };
+
+        chunk.quality_score = self.feedback.record(chunk_id, chunk.quality_score, &feedback)?;
+        self.pattern_analyzer.update_from_feedback(chunk_id, feedback).await?;
+
+        self.storage.store_chunk(&chunk).await
+            .context("Failed to store feedback-adjusted quality")?;
+        if self.cache.contains(&chunk.id) {
+            self.cache.insert(chunk.id.clone(), chunk.clone()).await;
+        }
+        self.suggestions.upsert(&chunk);
+        self.suggestions.commit()?;
+        if let Some(search_engine) = &self.search_engine {
+            search_engine.index_chunk(&chunk).await
+                .context("Failed to reindex chunk for search")?;
+            search_engine.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Feedback a chunk received and its effect on the chunk's quality
+    pub fn feedback_history(&self, chunk_id: &ChunkId) -> Option<FeedbackHistory> {
+        self.feedback.history(chunk_id)
+    }
+
+    /// Fit the similarity weights to Helpful/NotHelpful feedback on `find_similar` matches
+    ///
+    /// Uses feedback that names the chunk a match was found for in its
+    /// [`SIMILAR_TO_KEY`] metadata. Pairs whose chunks were deleted are
+    /// skipped. The fitted weights are applied when the report says so; the
+    /// previous ones can be restored with
+    /// [`rollback_similarity_weights`](Self::rollback_similarity_weights).
+    pub async fn learn_similarity_weights(&self, options: &WeightFitOptions) -> Result<WeightFitReport> {
+        let pairs = self.feedback.similarity_feedback();
+
+        let mut examples = Vec::with_capacity(pairs.len());
+        for pair in &pairs {
+            let (Some(query), Some(candidate)) = (
+                self.storage.get_chunk(&pair.query).await?,
+                self.storage.get_chunk(&pair.candidate).await?,
+            ) else {
+                continue;
+            };
+            examples.push(SimilarityExample {
+                components: self.pattern_analyzer.similarity_components(&query, &candidate)?,
+                helpful: pair.helpful,
+            });
+        }
+
+        let report = fit_similarity_weights(&self.pattern_analyzer.similarity_weights(), &examples, options)?;
+        if report.applied {
+            self.pattern_analyzer.set_similarity_weights(report.fitted)?;
+        }
+        Ok(report)
+    }
+
+    /// Weights currently combining the component similarities
+    pub fn similarity_weights(&self) -> SimilarityWeights {
+        self.pattern_analyzer.similarity_weights()
+    }
+
+    /// Restore the similarity weights used before the last applied fit
+    pub fn rollback_similarity_weights(&self) -> Result<Option<SimilarityWeights>> {
+        self.pattern_analyzer.rollback_similarity_weights()
+    }
+
+    /// Framework alias table used for ingestion and queries
+    pub fn aliases(&self) -> Arc<AliasTable> {
+        self.aliases.clone()
     }
 
     /// Get runtime statistics
@@ -257,52 +942,506 @@ impl MemoryEngine {
         }
 
         // Update pattern relationships
-        self.pattern_analyzer.rebuild_relationships().await?;
+        self.rebuild_relationships(|progress| {
+            tracing::debug!(
+                "Relationship rebuild: {}/{} chunks, {} edges",
+                progress.processed,
+                progress.total,
+                progress.edges
+            );
+        }).await?;
 
         tracing::info!("Memory engine optimization completed");
         Ok(())
     }
 
-    /// Warm up cache by loading frequently accessed chunks
-    async fn warmup_cache(&self) -> Result<()> {
-        tracing::info!("Warming up cache");
+    /// Recompute similarity edges, clusters and framework combinations from storage
+    ///
+    /// Runs on a blocking thread and calls `progress` after every batch. The
+    /// current graph stays in place until the rebuilt one is complete. Writes
+    /// wait until the rebuilt graph has been swapped in and written back, so
+    /// none of their edges are lost with the graph being replaced.
+    pub async fn rebuild_relationships<P>(&self, progress: P) -> Result<RelationshipRebuildReport>
+    where
+        P: Fn(RelationshipProgress) + Send + 'static,
+    {
+        let _guard = self.write_lock.lock().await;
+        let storage = Arc::clone(&self.storage);
+        let pattern_analyzer = Arc::clone(&self.pattern_analyzer);
 
-        let recent_chunks = self.storage.get_recent_chunks(self.config.max_hot_chunks / 2).await?;
-        
-        for chunk in recent_chunks {
-            self.cache.insert(chunk.id.clone(), chunk).await;
+        let mut report = tokio::task::spawn_blocking(move || {
+            pattern_analyzer.rebuild_relationships(
+                storage.scan_chunks(),
+                |chunk_ids| storage.get_chunks_blocking(chunk_ids),
+                progress,
+            )
+        })
+        .await
+        .context("Relationship rebuild task failed")??;
+
+        report.chunks_updated = self.write_inferred_relations().await?;
+        tracing::info!("Wrote inferred relationships to {} chunks", report.chunks_updated);
+        Ok(report)
+    }
+
+    /// Replace the inferred relationships of stored chunks with the analyzer's graph edges
+    ///
+    /// Declared relationships are kept; an edge that duplicates one is skipped.
+    /// An edge replaces a mirrored (inverse) relationship of the same type.
+    /// Returns the number of chunks whose relationships changed. Callers must
+    /// hold the write lock.
+    async fn write_inferred_relations(&self) -> Result<usize> {
+        let edges = self.pattern_analyzer.adjacency_snapshot();
+
+        // Collect IDs first: syncing inverses rewrites other chunks along the way
+        let mut stale = Vec::new();
+        for chunk in self.storage.scan_chunks() {
+            let chunk = chunk?;
+            if with_inferred_relations(&chunk, edges.get(&chunk.id)).is_some() {
+                stale.push(chunk.id);
+            }
+        }
+
+        let mut updated = 0;
+        for chunk_id in stale {
+            let Some(previous) = self.storage.get_chunk(&chunk_id).await? else {
+                continue;
+            };
+            if let Some(chunk) = with_inferred_relations(&previous, edges.get(&chunk_id)) {
+                self.write_relations(&chunk).await?;
+                self.sync_inverse_relations(Some(&previous), &chunk).await?;
+                updated += 1;
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Mirror a chunk's relationships onto the chunks they point at
+    ///
+    /// Targets receive the inverse relationship (e.g. `DependedOnBy` for
+    /// `DependsOn`) flagged as `inverse`; mirrors of relationships that no
+    /// longer exist are removed. Targets that are not stored are skipped.
+    async fn sync_inverse_relations(&self, previous: Option<&LearningChunk>, chunk: &LearningChunk) -> Result<()> {
+        let mut targets: Vec<&ChunkId> = previous.into_iter()
+            .chain(std::iter::once(chunk))
+            .flat_map(|version| forward_relations(version).map(|(relation, _)| &relation.target))
+            .collect();
+        targets.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        targets.dedup();
+
+        for target_id in targets {
+            let Some(mut target) = self.storage.get_chunk(target_id).await? else {
+                continue;
+            };
+            let mut changed = false;
+
+            // Drop mirrors of relationships the chunk no longer has
+            for (relation, inverse) in previous.into_iter().flat_map(forward_relations) {
+                let still_present = forward_relations(chunk)
+                    .any(|(current, _)| current.target == relation.target && current.relation_type == relation.relation_type);
+                if relation.target != *target_id || still_present {
+                    continue;
+                }
+                let before = target.relationships.len();
+                target.relationships.retain(|mirror| {
+                    !(mirror.inverse && mirror.target == chunk.id && mirror.relation_type == inverse)
+                });
+                changed |= target.relationships.len() != before;
+            }
+
+            for (relation, inverse) in forward_relations(chunk).filter(|(relation, _)| relation.target == *target_id) {
+                let mirror = ChunkRelation {
+                    target: chunk.id.clone(),
+                    relation_type: inverse,
+                    strength: relation.strength,
+                    metadata: HashMap::new(),
+                    provenance: relation.provenance,
+                    confidence: relation.confidence,
+                    inverse: true,
+                };
+
+                match target.relation(&mirror.target, &mirror.relation_type) {
+                    // The target declares the relationship itself
+                    Some(existing) if !existing.inverse => {}
+                    Some(existing) if existing.strength == mirror.strength
+                        && existing.provenance == mirror.provenance
+                        && existing.confidence == mirror.confidence => {}
+                    _ => {
+                        target.set_relation(mirror);
+                        changed = true;
+                    }
+                }
+            }
+
+            if changed {
+                self.write_relations(&target).await?;
+            }
         }
 
-        tracing::info!("Cache warmup completed");
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+    /// Persist a chunk whose relationships changed, refreshing its cached copy
+    ///
+    /// Relationships are not indexed, so search and pattern state stay untouched.
+    async fn write_relations(&self, chunk: &LearningChunk) -> Result<()> {
+        self.storage.store_chunk(chunk).await
+            .context("Failed to store chunk relationships")?;
+        if self.cache.contains(&chunk.id) {
+            self.cache.insert(chunk.id.clone(), chunk.clone()).await;
+        }
+        Ok(())
+    }
 
-    #[tokio::test]
-    async fn test_memory_engine_basic_operations() {
-        let temp_dir = TempDir::new().unwrap();
-        let config = MemoryConfig {
-            storage_path: temp_dir.path().to_path_buf(),
-            cache_size_mb: 64,
-            max_hot_chunks: 100,
-            ..Default::default()
+    /// Start rebuilding the search index from storage in the background
+    ///
+    /// Searches keep using the current index until the rebuilt one is swapped
+    /// in. Returns false if search is disabled or a rebuild is already running.
+    pub fn start_reindex(&self) -> bool {
+        let Some(search_engine) = self.search_engine.clone() else {
+            return false;
         };
 
-        let engine = MemoryEngine::new(config).await.unwrap();
+        let mut reindex_task = self.reindex_task.lock();
+        if reindex_task.as_ref().is_some_and(|task| !task.is_finished()) {
+            return false;
+        }
 
-        // Create test chunk
-        let chunk = LearningChunk {
-            id: ChunkId::new("test-chunk"),
-            chunk_type: ChunkType::SpikeTemplate,
-            content: ChunkContent::Code {
-                language: "typescript".to_string(),
-                code: "export const testFunction = () => 'hello';".to_string(),
-                framework: Some("nextjs".to_string()),
+        let storage = self.storage.clone();
+        *reindex_task = Some(tokio::spawn(async move {
+            let result = search_engine.rebuild(move || storage.scan_chunks()).await;
+            if let Err(e) = &result {
+                tracing::error!("Search index rebuild failed: {:#}", e);
+            }
+            result
+        }));
+        true
+    }
+
+    /// Wait for the background reindex to finish
+    ///
+    /// Returns the number of indexed chunks, or `None` if no reindex was started.
+    pub async fn wait_for_reindex(&self) -> Result<Option<usize>> {
+        let task = self.reindex_task.lock().take();
+        match task {
+            Some(task) => {
+                let indexed = task.await
+                    .context("Search index rebuild task failed")??;
+                Ok(Some(indexed))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Rebuild the search index from storage and wait for it to complete
+    pub async fn reindex(&self) -> Result<usize> {
+        // Let a rebuild that is already running finish first
+        self.wait_for_reindex().await?;
+
+        if !self.start_reindex() {
+            return Err(anyhow::anyhow!("Search is not enabled"));
+        }
+
+        Ok(self.wait_for_reindex().await?.unwrap_or_default())
+    }
+
+    /// Persist the cache hot set and flush pending writes before shutting down
+    pub async fn shutdown(&self) -> Result<()> {
+        tracing::info!("Shutting down memory engine");
+
+        if self.config.persist_hot_set {
+            let hot_set = self.cache.snapshot_hot_set();
+            self.storage.save_hot_set(&hot_set).await
+                .context("Failed to persist cache hot set")?;
+            tracing::info!("Persisted hot set with {} chunks", hot_set.len());
+        }
+
+        // Let an in-flight reindex complete rather than abandoning a half-built generation
+        if let Err(e) = self.wait_for_reindex().await {
+            tracing::warn!("Search index rebuild did not complete: {:#}", e);
+        }
+
+        if let Some(search_engine) = &self.search_engine {
+            search_engine.commit().await?;
+        }
+
+        self.storage.flush().await?;
+
+        tracing::info!("Memory engine shutdown completed");
+        Ok(())
+    }
+
+    /// Shut down and release the storage so the same path can be reopened
+    ///
+    /// Dropping the engine also releases it, but without persisting the hot
+    /// set or waiting for a running reindex, which keeps the database open.
+    pub async fn close(self) -> Result<()> {
+        self.shutdown().await?;
+        drop(self);
+        Ok(())
+    }
+
+    /// Delete the stored parts of a chunk that are not among `parts`; callers must hold the write lock
+    async fn remove_stale_parts(&self, chunk_id: &ChunkId, parts: &[LearningChunk]) -> Result<()> {
+        let current: HashSet<&ChunkId> = parts.iter().map(|part| &part.id).collect();
+        let stale: Vec<ChunkId> = self.stored_parts(chunk_id).await?
+            .into_iter()
+            .filter(|part_id| !current.contains(part_id))
+            .collect();
+        if !stale.is_empty() {
+            self.remove_chunks(&stale).await?;
+        }
+        Ok(())
+    }
+
+    /// Stored parts split from a chunk, found through its `HasPart` edges
+    async fn stored_parts(&self, chunk_id: &ChunkId) -> Result<Vec<ChunkId>> {
+        let mut parts = Vec::new();
+        let mut pending = vec![chunk_id.clone()];
+        while let Some(id) = pending.pop() {
+            let Some(chunk) = self.storage.get_chunk(&id).await? else {
+                continue;
+            };
+            for relation in &chunk.relationships {
+                if relation.relation_type == RelationType::HasPart
+                    && is_part_id(chunk_id, &relation.target)
+                    && !parts.contains(&relation.target)
+                {
+                    parts.push(relation.target.clone());
+                    pending.push(relation.target.clone());
+                }
+            }
+        }
+        Ok(parts)
+    }
+
+    /// Remove chunks from every component; callers must hold the write lock
+    ///
+    /// Parts split from a removed chunk are removed along with it.
+    async fn remove_chunks(&self, chunk_ids: &[ChunkId]) -> Result<Vec<ChunkId>> {
+        let mut seen: HashSet<ChunkId> = chunk_ids.iter().cloned().collect();
+        let mut all_ids = chunk_ids.to_vec();
+        for chunk_id in chunk_ids {
+            for part_id in self.stored_parts(chunk_id).await? {
+                if seen.insert(part_id.clone()) {
+                    all_ids.push(part_id);
+                }
+            }
+        }
+        let chunk_ids = all_ids.as_slice();
+
+        // Remember who points at the deleted chunks so their edges can be pruned
+        let mut related: HashSet<ChunkId> = HashSet::new();
+        for chunk_id in chunk_ids {
+            if let Some(chunk) = self.storage.get_chunk(chunk_id).await? {
+                related.extend(chunk.relationships.into_iter().map(|relation| relation.target));
+            }
+        }
+
+        let deleted = self.storage.delete_chunks_batch(chunk_ids).await
+            .context("Failed to delete chunks from disk")?;
+
+        for chunk_id in chunk_ids {
+            self.cache.remove(chunk_id).await;
+            self.vector_index.remove(chunk_id);
+            self.suggestions.remove(chunk_id);
+        }
+
+        if let Some(search_engine) = &self.search_engine {
+            for chunk_id in &deleted {
+                search_engine.remove_chunk(chunk_id).await?;
+            }
+            search_engine.commit().await?;
+        }
+        self.suggestions.commit()?;
+
+        for chunk_id in &deleted {
+            self.pattern_analyzer.remove_chunk(chunk_id).await?;
+            self.feedback.remove(chunk_id)?;
+        }
+
+        for target in related.iter().filter(|target| !deleted.contains(target)) {
+            let Some(mut chunk) = self.storage.get_chunk(target).await? else {
+                continue;
+            };
+            let pruned: usize = deleted.iter().map(|chunk_id| chunk.remove_relations_to(chunk_id)).sum();
+            if pruned > 0 {
+                self.write_relations(&chunk).await?;
+            }
+        }
+
+        if !deleted.is_empty() {
+            let mut stats = self.stats.write();
+            stats.total_chunks = stats.total_chunks.saturating_sub(deleted.len() as u64);
+            stats.disk_writes += 1;
+            stats.last_update = Some(Utc::now());
+        }
+
+        Ok(deleted)
+    }
+
+    /// Warm up cache by loading frequently accessed chunks
+    ///
+    /// A persisted hot set is consumed; when it is missing or none of its
+    /// chunks could be restored, the most recent chunks are loaded instead.
+    async fn warmup_cache(&self) -> Result<()> {
+        tracing::info!("Warming up cache");
+
+        if self.config.persist_hot_set {
+            if let Some(hot_set) = self.storage.take_hot_set().await? {
+                let restored = self.restore_hot_set(hot_set).await?;
+                tracing::info!("Cache warmup restored {} chunks from hot set snapshot", restored);
+                if restored > 0 {
+                    return Ok(());
+                }
+            }
+        }
+
+        let recent_chunks = self.storage.get_recent_chunks(self.config.max_hot_chunks / 2).await?;
+        
+        for chunk in recent_chunks {
+            self.cache.insert(chunk.id.clone(), chunk).await;
+        }
+
+        tracing::info!("Cache warmup completed");
+        Ok(())
+    }
+
+    /// Load a persisted hot set back into the cache
+    ///
+    /// The hottest entries are selected until the cache byte and count budgets
+    /// are exhausted, then decompressed in parallel blocking workers.
+    async fn restore_hot_set(&self, mut hot_set: Vec<HotSetEntry>) -> Result<usize> {
+        let byte_budget = self.cache.max_size_bytes();
+        let count_budget = self.cache.max_chunks();
+
+        // Snapshot order is most recent first; remember it before ranking by frequency
+        let recency: HashMap<ChunkId, usize> = hot_set.iter()
+            .enumerate()
+            .map(|(rank, entry)| (entry.chunk_id.clone(), rank))
+            .collect();
+        hot_set.sort_by_key(|entry| std::cmp::Reverse(entry.access_count));
+
+        let mut selected = Vec::new();
+        let mut used_bytes = 0usize;
+        for entry in hot_set {
+            if selected.len() >= count_budget {
+                break;
+            }
+            if used_bytes + entry.size_bytes > byte_budget {
+                continue;
+            }
+            used_bytes += entry.size_bytes;
+            selected.push(entry);
+        }
+
+        if selected.is_empty() {
+            return Ok(0);
+        }
+
+        let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let batch_size = selected.len().div_ceil(workers).max(1);
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for batch in selected.chunks(batch_size) {
+            let storage = Arc::clone(&self.storage);
+            let chunk_ids: Vec<ChunkId> = batch.iter().map(|entry| entry.chunk_id.clone()).collect();
+            tasks.spawn_blocking(move || storage.get_chunks_blocking(&chunk_ids));
+        }
+
+        let mut loaded = Vec::with_capacity(selected.len());
+        while let Some(result) = tasks.join_next().await {
+            let chunks = result.context("Hot set loader task failed")??;
+            loaded.extend(chunks);
+        }
+
+        // Insert least recent first so the LRU order matches the snapshot
+        let access_counts: HashMap<ChunkId, u64> = selected.into_iter()
+            .map(|entry| (entry.chunk_id, entry.access_count))
+            .collect();
+        loaded.sort_by_key(|chunk| std::cmp::Reverse(recency.get(&chunk.id).copied().unwrap_or(usize::MAX)));
+
+        let restored = loaded.len();
+        for chunk in loaded {
+            let access_count = access_counts.get(&chunk.id).copied().unwrap_or(1);
+            self.cache.restore(chunk, access_count).await;
+        }
+
+        Ok(restored)
+    }
+}
+
+/// Keep the inverse relationships maintained on a chunk's previous version
+///
+/// They mirror other chunks' relationships, so a new version of the chunk
+/// that does not mention them must not drop them.
+fn carry_inverse_relations(previous: &LearningChunk, chunk: &mut LearningChunk) {
+    for relation in previous.relationships.iter().filter(|relation| relation.inverse) {
+        if chunk.relation(&relation.target, &relation.relation_type).is_none() {
+            chunk.relationships.push(relation.clone());
+        }
+    }
+}
+
+/// Relationships a chunk owns that have an inverse, paired with that inverse
+fn forward_relations(chunk: &LearningChunk) -> impl Iterator<Item = (&ChunkRelation, RelationType)> {
+    chunk.relationships.iter()
+        .filter(move |relation| !relation.inverse && relation.target != chunk.id)
+        .filter_map(|relation| relation.relation_type.inverse().map(|inverse| (relation, inverse)))
+}
+
+/// A chunk with its inferred relationships replaced by graph edges, or `None` if unchanged
+fn with_inferred_relations(chunk: &LearningChunk, edges: Option<&Vec<WeightedEdge>>) -> Option<LearningChunk> {
+    let mut updated = chunk.clone();
+    updated.relationships.retain(|relation| {
+        relation.inverse || relation.provenance != RelationProvenance::Inferred
+    });
+
+    for edge in edges.into_iter().flatten() {
+        // A chunk's own edge supersedes the mirror of the target's edge
+        if updated.relation(&edge.target, &edge.relation_type).is_none_or(|existing| existing.inverse) {
+            updated.add_inferred_relationship(edge.target.clone(), edge.relation_type.clone(), edge.weight, edge.confidence);
+        }
+    }
+
+    let same = updated.relationships.len() == chunk.relationships.len()
+        && updated.relationships.iter().all(|relation| {
+            chunk.relation(&relation.target, &relation.relation_type).is_some_and(|existing| {
+                existing.strength == relation.strength
+                    && existing.confidence == relation.confidence
+                    && existing.provenance == relation.provenance
+                    && existing.inverse == relation.inverse
+            })
+        });
+    (!same).then_some(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_memory_engine_basic_operations() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            cache_size_mb: 64,
+            max_hot_chunks: 100,
+            ..Default::default()
+        };
+
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        // Create test chunk
+        let chunk = LearningChunk {
+            id: ChunkId::new("test-chunk"),
+            chunk_type: ChunkType::SpikeTemplate,
+            content: ChunkContent::Code {
+                language: "typescript".to_string(),
+                code: "export const testFunction = () => 'hello';".to_string(),
+                framework: Some("nextjs".to_string()),
             },
             metadata: ChunkMetadata {
                 source: "test".to_string(),
@@ -371,4 +1510,823 @@ mod tests {
         assert_eq!(nextjs_chunks.len(), 1);
         assert_eq!(nextjs_chunks[0].id.as_str(), "nextjs-chunk");
     }
-}
\ No newline at end of file
+
+    fn create_tagged_chunk(id: &str, framework: &str, tags: &[&str]) -> LearningChunk {
+        LearningChunk {
+            id: ChunkId::new(id),
+            chunk_type: ChunkType::Pattern,
+            content: ChunkContent::Code {
+                language: "typescript".to_string(),
+                code: format!("export const {} = () => null;", id.replace('-', "_")),
+                framework: Some(framework.to_string()),
+            },
+            metadata: ChunkMetadata {
+                frameworks: vec![framework.to_string()],
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_and_delete_keep_components_coherent() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let mut chunk = create_tagged_chunk("coherent-chunk", "nextjs", &["auth"]);
+        engine.store_chunk(chunk.clone()).await.unwrap();
+        assert_eq!(engine.search_chunks("coherent_chunk", 10).await.unwrap().len(), 1);
+
+        // Updates replace the cached copy and the indexed document
+        chunk.metadata.tags = vec!["billing".to_string()];
+        engine.update_chunk(chunk.clone()).await.unwrap();
+        let cached = engine.get_chunk(&chunk.id).await.unwrap().unwrap();
+        assert_eq!(cached.metadata.tags, vec!["billing".to_string()]);
+        assert_eq!(engine.search_engine.as_ref().unwrap().get_stats().total_documents, 1);
+
+        // Deleted chunks are gone from cache, storage and search
+        assert!(engine.delete_chunk(&chunk.id).await.unwrap());
+        assert!(!engine.cache.contains(&chunk.id));
+        assert!(engine.get_chunk(&chunk.id).await.unwrap().is_none());
+        assert!(engine.search_chunks("coherent_chunk", 10).await.unwrap().is_empty());
+        assert!(!engine.delete_chunk(&chunk.id).await.unwrap());
+
+        // Updating a missing chunk is an error rather than an implicit insert
+        assert!(engine.update_chunk(chunk).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_returns_hydrated_hits() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let chunk = create_tagged_chunk("hydrated-hit", "nextjs", &["auth"]);
+        engine.store_chunk(chunk.clone()).await.unwrap();
+
+        let hits = engine.search("hydrated_hit", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk.id, chunk.id);
+        assert_eq!(hits[0].chunk.content, chunk.content);
+        assert!(hits[0].score > 0.0);
+        assert!(hits[0].matched_fields.contains(&"content".to_string()));
+        assert!(hits[0].highlighted_snippet.as_ref().unwrap().contains("<b>"));
+    }
+
+    #[tokio::test]
+    async fn test_structured_query_hydrates_hits() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        engine.store_chunks_batch(vec![
+            create_tagged_chunk("query-a", "nextjs", &["auth"]),
+            create_tagged_chunk("query-b", "laravel", &["auth"]),
+        ]).await.unwrap();
+
+        let results = engine.query(&SearchQuery::new().tag("auth").framework("laravel")).await.unwrap();
+        assert_eq!(results.total_hits, 1);
+        assert_eq!(results.hits[0].chunk.id.as_str(), "query-b");
+        assert_eq!(results.facet_count(facet::FRAMEWORK, "laravel"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_string_applies_negation() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        engine.store_chunks_batch(vec![
+            create_tagged_chunk("invoice-form", "nextjs", &["auth"]),
+            create_tagged_chunk("billing-form", "nextjs", &["auth"]),
+            create_tagged_chunk("legacy-form", "laravel", &["auth", "deprecated"]),
+        ]).await.unwrap();
+
+        let results = engine.query_str("tag:auth -tag:deprecated -billing").await.unwrap();
+        let ids: Vec<&str> = results.hits.iter().map(|h| h.chunk.id.as_str()).collect();
+        assert_eq!(ids, vec!["invoice-form"]);
+
+        // Excluded frameworks are expanded through aliases like included ones
+        let results = engine.query_str("type:pattern -fw:Next.js").await.unwrap();
+        assert_eq!(results.total_hits, 1);
+        assert_eq!(results.hits[0].chunk.id.as_str(), "legacy-form");
+
+        let err = engine.query_str("tag:auth quality>>0.5").await.unwrap_err();
+        assert!(err.to_string().contains("at position 17"));
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_search_fuses_both_legs() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            embedding_dim: 3,
+            ..Default::default()
+        };
+
+        let mut lexical_only = create_tagged_chunk("invoice-table", "nextjs", &[]);
+        lexical_only.embedding = Some(vec![0.0, 0.0, 1.0]);
+        let mut both = create_tagged_chunk("invoice-form", "nextjs", &[]);
+        both.embedding = Some(vec![1.0, 0.1, 0.0]);
+        let mut vector_only = create_tagged_chunk("billing-form", "nextjs", &[]);
+        vector_only.embedding = Some(vec![0.9, 0.3, 0.0]);
+
+        {
+            let engine = MemoryEngine::new(config.clone()).await.unwrap();
+            engine.store_chunks_batch(vec![lexical_only, both, vector_only]).await.unwrap();
+            engine.close().await.unwrap();
+        }
+
+        // Embeddings are reloaded from storage on startup
+        let engine = MemoryEngine::new(config).await.unwrap();
+        assert_eq!(engine.vector_index.len(), 3);
+
+        let query = HybridQuery::new("invoice").embedding(vec![1.0, 0.0, 0.0]).leg_limits(10, 2);
+        let hits = engine.hybrid_search(&query).await.unwrap();
+        let ids: Vec<&str> = hits.iter().map(|h| h.chunk.id.as_str()).collect();
+        assert_eq!(ids[0], "invoice-form");
+        assert_eq!(hits[0].reasons.len(), 2);
+        assert!(ids.contains(&"invoice-table"));
+        assert!(ids.contains(&"billing-form"));
+
+        let vector_hit = hits.iter().find(|h| h.chunk.id.as_str() == "billing-form").unwrap();
+        assert!(matches!(vector_hit.reasons.as_slice(), [MatchReason::Vector { rank: 2, .. }]));
+        assert!(vector_hit.highlighted_snippet.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_schema_change_reindexes_from_storage() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        {
+            let engine = MemoryEngine::new(config.clone()).await.unwrap();
+            engine.store_chunks_batch(vec![
+                create_tagged_chunk("reindex-a", "nextjs", &[]),
+                create_tagged_chunk("reindex-b", "laravel", &[]),
+            ]).await.unwrap();
+            assert_eq!(engine.wait_for_reindex().await.unwrap(), None);
+            engine.close().await.unwrap();
+        }
+
+        // Pretend the index was written by a different schema version
+        let manifest_path = temp_dir.path().join("search_index").join("manifest.json");
+        let mut manifest: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+        manifest["schema_version"] = serde_json::json!(0);
+        std::fs::write(&manifest_path, serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+        let engine = MemoryEngine::new(config).await.unwrap();
+        assert_eq!(engine.wait_for_reindex().await.unwrap(), Some(2));
+
+        let hits = engine.search("reindex_b", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk.id.as_str(), "reindex-b");
+
+        // Manual reindex picks up the same data
+        assert_eq!(engine.reindex().await.unwrap(), 2);
+        let stats = engine.search_engine.as_ref().unwrap().get_stats();
+        assert_eq!(stats.total_documents, 2);
+        assert_eq!(stats.schema_version, SEARCH_SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_framework_aliases_normalize_and_expand() {
+        let temp_dir = TempDir::new().unwrap();
+        let alias_file = temp_dir.path().join("aliases.json");
+        std::fs::write(&alias_file, r#"{"laravel": ["lara"]}"#).unwrap();
+
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().join("memory"),
+            alias_file: Some(alias_file),
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        engine.store_chunks_batch(vec![
+            create_tagged_chunk("alias-next", "Next.js", &[]),
+            create_tagged_chunk("alias-laravel", "illuminate", &[]),
+        ]).await.unwrap();
+
+        // Frameworks are stored under their canonical names
+        let stored = engine.get_chunk(&ChunkId::new("alias-next")).await.unwrap().unwrap();
+        assert_eq!(stored.metadata.frameworks, vec!["nextjs".to_string()]);
+
+        for framework in ["next", "Next 14", "nextjs"] {
+            let chunks = engine.get_framework_chunks(framework).await.unwrap();
+            assert_eq!(chunks.len(), 1, "{}", framework);
+        }
+        assert_eq!(engine.get_framework_chunks("lara").await.unwrap().len(), 1);
+
+        let results = engine.query(&SearchQuery::new().framework("php-laravel")).await.unwrap();
+        assert_eq!(results.hits.len(), 1);
+        assert_eq!(results.hits[0].chunk.id.as_str(), "alias-laravel");
+
+        let hits = engine.search("next", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk.id.as_str(), "alias-next");
+    }
+
+    #[tokio::test]
+    async fn test_suggestions_follow_commits() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        {
+            let engine = MemoryEngine::new(config.clone()).await.unwrap();
+            engine.store_chunks_batch(vec![
+                create_tagged_chunk("suggest-a", "laravel", &["middleware"]),
+                create_tagged_chunk("suggest-b", "nextjs", &["middleware", "metadata"]),
+            ]).await.unwrap();
+
+            let suggestions = engine.suggest("m", &[SuggestionKind::Tag], 10);
+            assert_eq!(suggestions[0].text, "middleware");
+            assert_eq!(suggestions[0].chunk_count, 2);
+
+            engine.delete_chunk(&ChunkId::new("suggest-b")).await.unwrap();
+            assert_eq!(engine.suggest("me", &[], 10).len(), 0);
+            engine.close().await.unwrap();
+        }
+
+        // Terms are rebuilt from storage on startup
+        let engine = MemoryEngine::new(config).await.unwrap();
+        assert_eq!(engine.suggest("lar", &[], 10)[0].kind, SuggestionKind::Framework);
+        assert_eq!(engine.did_you_mean("midleware").as_deref(), Some("middleware"));
+        assert_eq!(engine.spelling_corrections("laravell", 3)[0].text, "laravel");
+    }
+
+    #[tokio::test]
+    async fn test_batch_store_and_delete_where() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let ids = engine.store_chunks_batch(vec![
+            create_tagged_chunk("batch-a", "nextjs", &["deprecated"]),
+            create_tagged_chunk("batch-b", "nextjs", &["auth"]),
+            create_tagged_chunk("batch-c", "laravel", &["deprecated"]),
+        ]).await.unwrap();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(engine.get_stats().total_chunks, 3);
+        assert_eq!(engine.search_engine.as_ref().unwrap().get_stats().total_documents, 3);
+
+        assert!(engine.delete_where(&ChunkFilter::default()).await.is_err());
+
+        let filter = ChunkFilter { tags: vec!["deprecated".to_string()], ..Default::default() };
+        assert_eq!(engine.delete_where(&filter).await.unwrap(), 2);
+
+        assert_eq!(engine.get_stats().total_chunks, 1);
+        assert_eq!(engine.search_engine.as_ref().unwrap().get_stats().total_documents, 1);
+        assert!(engine.get_chunk(&ChunkId::new("batch-a")).await.unwrap().is_none());
+        assert_eq!(engine.get_framework_chunks("laravel").await.unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_hot_set_survives_restart() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+
+        let hot_id = ChunkId::new("hot-chunk");
+        let cold_id = ChunkId::new("cold-chunk");
+
+        {
+            let engine = MemoryEngine::new(config.clone()).await.unwrap();
+            engine.store_chunk(LearningChunk { id: hot_id.clone(), ..Default::default() }).await.unwrap();
+            engine.store_chunk(LearningChunk { id: cold_id.clone(), ..Default::default() }).await.unwrap();
+
+            engine.get_chunk(&hot_id).await.unwrap();
+            engine.get_chunk(&hot_id).await.unwrap();
+            // Simulate the cold chunk having been evicted before shutdown
+            engine.cache.remove(&cold_id).await;
+
+            engine.close().await.unwrap();
+        }
+
+        let engine = MemoryEngine::new(config).await.unwrap();
+        assert_eq!(engine.get_stats().total_chunks, 2);
+        assert!(engine.cache.contains(&hot_id));
+        assert!(!engine.cache.contains(&cold_id));
+
+        let snapshot = engine.cache.snapshot_hot_set();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].access_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_stale_hot_set_falls_back_to_recent_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+
+        let recent_id = ChunkId::new("recent-chunk");
+        {
+            // Closing without persisting keeps the hand-written snapshot below
+            let engine = MemoryEngine::new(MemoryConfig { persist_hot_set: false, ..config.clone() }).await.unwrap();
+            engine.store_chunk(LearningChunk { id: recent_id.clone(), ..Default::default() }).await.unwrap();
+
+            // A snapshot naming only chunks that no longer exist
+            let stale = HotSetEntry { chunk_id: ChunkId::new("deleted-chunk"), access_count: 5, size_bytes: 64 };
+            engine.storage.save_hot_set(&[stale]).await.unwrap();
+            engine.close().await.unwrap();
+        }
+
+        let engine = MemoryEngine::new(config).await.unwrap();
+        assert!(engine.cache.contains(&recent_id));
+        assert!(engine.storage.take_hot_set().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_scores_stored_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+
+        let query = create_tagged_chunk("login-form", "nextjs", &["auth"]);
+        {
+            let engine = MemoryEngine::new(config.clone()).await.unwrap();
+            let mut twin = query.clone();
+            twin.id = ChunkId::new("login-form-copy");
+            engine.store_chunks_batch(vec![
+                twin,
+                create_tagged_chunk("user-model", "laravel", &["orm"]),
+            ]).await.unwrap();
+
+            let matches = engine.find_similar(&query, 10).await.unwrap();
+            assert_eq!(matches.len(), 1);
+            assert_eq!(matches[0].chunk.id.as_str(), "login-form-copy");
+            engine.close().await.unwrap();
+        }
+
+        // Candidate signatures are persisted with the pattern state
+        let engine = MemoryEngine::new(MemoryConfig {
+            patterns: PatternConfig { candidate_budget: 0, ..Default::default() },
+            ..config.clone()
+        }).await.unwrap();
+        assert!(engine.find_similar(&query, 10).await.unwrap().is_empty());
+        drop(engine);
+
+        let engine = MemoryEngine::new(config).await.unwrap();
+        let matches = engine.find_similar(&query, 10).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].chunk.id.as_str(), "login-form-copy");
+    }
+
+    #[tokio::test]
+    async fn test_optimize_rebuilds_relationships() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let original = create_tagged_chunk("checkout-form", "nextjs", &["forms"]);
+        let mut copy = original.clone();
+        copy.id = ChunkId::new("checkout-form-copy");
+        let mut fullstack = create_tagged_chunk("fullstack-api", "nextjs", &[]);
+        fullstack.metadata.frameworks.push("laravel".to_string());
+        engine.store_chunks_batch(vec![original, copy, fullstack]).await.unwrap();
+
+        let updates = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let report = {
+            let updates = Arc::clone(&updates);
+            engine.rebuild_relationships(move |progress| updates.lock().push(progress)).await.unwrap()
+        };
+
+        assert_eq!(report.chunks, 3);
+        assert_eq!(report.edges, 2);
+        assert_eq!(report.clusters, 1);
+        assert_eq!(report.combinations, 1);
+
+        let last = *updates.lock().last().unwrap();
+        assert_eq!(last.processed, 3);
+        assert_eq!(last.edges, 2);
+
+        // Optimizing keeps the rebuilt graph instead of wiping it
+        engine.optimize().await.unwrap();
+        assert!(!engine.pattern_analyzer.get_nextjs_laravel_patterns().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_framework_combinations_are_mined() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config.clone()).await.unwrap();
+
+        let mut chunks = Vec::new();
+        for (id, frameworks) in [
+            ("page", &["nextjs", "laravel", "tailwind"][..]),
+            ("api", &["nextjs", "laravel"][..]),
+            ("styles", &["tailwind"][..]),
+        ] {
+            let mut chunk = create_tagged_chunk(id, frameworks[0], &[]);
+            chunk.metadata.frameworks = frameworks.iter().map(|name| name.to_string()).collect();
+            chunks.push(chunk);
+        }
+        engine.store_chunks_batch(chunks).await.unwrap();
+
+        let combinations = engine.get_framework_combinations(&["next.js"], 0.5);
+        assert_eq!(combinations.len(), 1);
+        assert_eq!(combinations[0].frameworks, vec!["laravel".to_string(), "nextjs".to_string()]);
+        assert_eq!(combinations[0].usage_frequency, 2);
+        assert!((combinations[0].lift - 1.5).abs() < 1e-6);
+        assert_eq!(engine.get_framework_combinations::<&str>(&[], 0.0).len(), 4);
+        engine.close().await.unwrap();
+
+        // Counts are recovered from the persisted framework graph
+        let engine = MemoryEngine::new(config).await.unwrap();
+        assert_eq!(engine.get_framework_combinations(&["tailwind"], 0.0).len(), 3);
+        assert!(engine.delete_chunk(&ChunkId::new("page")).await.unwrap());
+        assert!(engine.get_framework_combinations(&["tailwind"], 0.0).is_empty());
+        assert_eq!(engine.get_framework_combinations(&["laravel"], 0.0).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_relationships_keep_inverse_edges() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let page_id = ChunkId::new("page");
+        let hook_id = ChunkId::new("use-session");
+        engine.store_chunk(create_tagged_chunk("use-session", "nextjs", &[])).await.unwrap();
+
+        let mut page = create_tagged_chunk("page", "nextjs", &[]);
+        page.add_relationship(hook_id.clone(), RelationType::DependsOn, 0.9);
+        engine.store_chunk(page.clone()).await.unwrap();
+
+        let hook = engine.get_chunk(&hook_id).await.unwrap().unwrap();
+        let mirror = hook.relation(&page_id, &RelationType::DependedOnBy).unwrap();
+        assert!(mirror.inverse);
+        assert_eq!(mirror.provenance, RelationProvenance::Declared);
+
+        // Re-storing the target without its mirrors keeps them
+        engine.store_chunk(create_tagged_chunk("use-session", "nextjs", &["auth"])).await.unwrap();
+        let hook = engine.get_chunk(&hook_id).await.unwrap().unwrap();
+        assert!(hook.relation(&page_id, &RelationType::DependedOnBy).is_some());
+
+        // Dropping the relationship removes its mirror
+        page.relationships.clear();
+        engine.update_chunk(page.clone()).await.unwrap();
+        let hook = engine.get_chunk(&hook_id).await.unwrap().unwrap();
+        assert!(hook.relationships.is_empty());
+
+        // Deleting a chunk prunes edges pointing at it
+        page.add_relationship(hook_id.clone(), RelationType::DependsOn, 0.9);
+        engine.update_chunk(page).await.unwrap();
+        assert!(engine.delete_chunk(&hook_id).await.unwrap());
+        let page = engine.get_chunk(&page_id).await.unwrap().unwrap();
+        assert!(page.relationships.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_writes_inferred_relationships() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let original = create_tagged_chunk("card", "react", &["ui"]);
+        let mut copy = original.clone();
+        copy.id = ChunkId::new("card-copy");
+        engine.store_chunks_batch(vec![original.clone(), copy.clone()]).await.unwrap();
+
+        let report = engine.rebuild_relationships(|_| {}).await.unwrap();
+        assert_eq!(report.chunks_updated, 2);
+
+        let stored = engine.get_chunk(&original.id).await.unwrap().unwrap();
+        let relation = stored.relation(&copy.id, &RelationType::Similar).unwrap();
+        assert_eq!(relation.provenance, RelationProvenance::Inferred);
+        assert!(!relation.inverse);
+        assert!(relation.confidence > 0.6);
+
+        // A second rebuild finds nothing new to write
+        let report = engine.rebuild_relationships(|_| {}).await.unwrap();
+        assert_eq!(report.chunks_updated, 0);
+
+        // Inferred edges to deleted chunks are pruned
+        engine.delete_chunk(&copy.id).await.unwrap();
+        let stored = engine.get_chunk(&original.id).await.unwrap().unwrap();
+        assert!(stored.relationships.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_graph_queries_follow_stored_relationships() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let mut page = create_tagged_chunk("orders-page", "nextjs", &[]);
+        page.add_relationship(ChunkId::new("orders-api"), RelationType::DependsOn, 0.9);
+        let mut api = create_tagged_chunk("orders-api", "laravel", &[]);
+        api.add_relationship(ChunkId::new("order-model"), RelationType::DependsOn, 0.8);
+        let mut controller = create_tagged_chunk("orders-controller", "laravel", &[]);
+        controller.add_relationship(ChunkId::new("orders-api"), RelationType::Alternative, 0.7);
+        let model = create_tagged_chunk("order-model", "laravel", &[]);
+        engine.store_chunks_batch(vec![page, api, controller, model]).await.unwrap();
+
+        let page_id = ChunkId::new("orders-page");
+        let model_id = ChunkId::new("order-model");
+
+        let closure = engine.transitive_relations(&page_id, &[RelationType::DependsOn], None);
+        let reached: Vec<&str> = closure.reachable.iter().map(|chunk| chunk.chunk_id.as_str()).collect();
+        assert_eq!(reached, vec!["orders-api", "order-model"]);
+        assert!(closure.warnings.is_empty());
+
+        let alternatives = engine.neighbors(&ChunkId::new("orders-api"), &[RelationType::Alternative]);
+        assert_eq!(alternatives.len(), 1);
+        assert_eq!(alternatives[0].target.as_str(), "orders-controller");
+
+        let path = engine.shortest_path(&page_id, &model_id, &[]).unwrap();
+        assert_eq!(path.chunks.len(), 3);
+        assert!(engine.weighted_path(&model_id, &page_id, &[RelationType::DependedOnBy]).is_some());
+        assert_eq!(engine.connected_components(&[]).len(), 1);
+
+        // Closing the loop is reported as a dependency cycle
+        let mut model = create_tagged_chunk("order-model", "laravel", &[]);
+        model.add_relationship(page_id.clone(), RelationType::DependsOn, 0.5);
+        engine.update_chunk(model).await.unwrap();
+
+        let closure = engine.transitive_relations(&page_id, &[RelationType::DependsOn], None);
+        assert_eq!(closure.warnings.len(), 1);
+        assert_eq!(engine.dependency_cycles().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_feedback_adjusts_quality_and_ranking() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let chunks = vec![
+            create_tagged_chunk("checkout-a", "nextjs", &["checkout"]),
+            create_tagged_chunk("checkout-b", "nextjs", &["checkout"]),
+        ];
+        engine.store_chunks_batch(chunks).await.unwrap();
+
+        let before = engine.search("checkout", 10).await.unwrap();
+        assert_eq!(before.len(), 2);
+        let (top, runner_up) = (before[0].chunk.id.clone(), before[1].chunk.id.clone());
+
+        let vote = |user: &str, feedback_type: FeedbackType| UserFeedback {
+            user_id: user.to_string(),
+            timestamp: Utc::now(),
+            feedback_type,
+            comment: None,
+            metadata: HashMap::new(),
+        };
+        for user in ["alice", "bob", "carol"] {
+            engine.learn_from_feedback(&top, vote(user, FeedbackType::NotHelpful)).await.unwrap();
+            engine.learn_from_feedback(&runner_up, vote(user, FeedbackType::Helpful)).await.unwrap();
+        }
+        // Repeated votes of one user are not counted twice
+        engine.learn_from_feedback(&runner_up, vote("alice", FeedbackType::Helpful)).await.unwrap();
+
+        let demoted = engine.get_chunk(&top).await.unwrap().unwrap();
+        assert!((demoted.quality_score - 2.0 / 7.0).abs() < 1e-3);
+        let history = engine.feedback_history(&runner_up).unwrap();
+        assert_eq!(history.entries.len(), 4);
+        assert_eq!(history.entries.iter().filter(|entry| entry.counted).count(), 3);
+
+        let after = engine.search("checkout", 10).await.unwrap();
+        assert_eq!(after[0].chunk.id, runner_up);
+
+        // Feedback lifts a chunk the index alone ranks beyond the limit
+        assert_eq!(engine.search("checkout", 1).await.unwrap()[0].chunk.id, runner_up);
+        let page = engine.query(&SearchQuery::new().text("checkout").limit(1)).await.unwrap();
+        assert_eq!(page.hits.len(), 1);
+        assert_eq!(page.hits[0].chunk.id, runner_up);
+        let fused = engine.hybrid_search(&HybridQuery::new("checkout").limit(1)).await.unwrap();
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].chunk.id, runner_up);
+        let filtered = engine.query_str("checkout quality>0.5").await.unwrap();
+        assert_eq!(filtered.hits.len(), 1);
+
+        let probe = create_tagged_chunk("checkout-probe", "nextjs", &["checkout"]);
+        let similar = engine.find_similar(&probe, 2).await.unwrap();
+        assert_eq!(similar[0].chunk.id, runner_up);
+
+        assert!(engine.learn_from_feedback(&ChunkId::new("missing"), vote("alice", FeedbackType::Helpful)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_learned_similarity_weights_persist_and_roll_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+
+        {
+            let engine = MemoryEngine::new(config.clone()).await.unwrap();
+            let mut chunks = vec![create_tagged_chunk("query", "nextjs", &[])];
+            for i in 0..5 {
+                chunks.push(create_tagged_chunk(&format!("page-{}", i), "nextjs", &[]));
+                chunks.push(create_tagged_chunk(&format!("model-{}", i), "laravel", &[]));
+            }
+            engine.store_chunks_batch(chunks).await.unwrap();
+
+            // Matches in the same framework were helpful, the others were not
+            for i in 0..5 {
+                for (id, feedback_type) in [(format!("page-{}", i), FeedbackType::Helpful), (format!("model-{}", i), FeedbackType::NotHelpful)] {
+                    let feedback = UserFeedback {
+                        user_id: "alice".to_string(),
+                        timestamp: Utc::now(),
+                        feedback_type,
+                        comment: None,
+                        metadata: HashMap::from([(SIMILAR_TO_KEY.to_string(), serde_json::json!("query"))]),
+                    };
+                    engine.learn_from_feedback(&ChunkId::new(&id), feedback).await.unwrap();
+                }
+            }
+
+            let report = engine.learn_similarity_weights(&WeightFitOptions::default()).await.unwrap();
+            assert!(report.applied);
+            assert_eq!(report.training_examples + report.after.examples, 10);
+            assert!(report.after.auc >= report.before.auc);
+            assert!(report.fitted.framework > SimilarityWeights::default().framework);
+            assert_eq!(engine.similarity_weights(), report.fitted);
+            engine.close().await.unwrap();
+        }
+
+        let engine = MemoryEngine::new(config).await.unwrap();
+        assert_ne!(engine.similarity_weights(), SimilarityWeights::default());
+        assert_eq!(engine.rollback_similarity_weights().unwrap(), Some(SimilarityWeights::default()));
+        assert_eq!(engine.similarity_weights(), SimilarityWeights::default());
+        assert_eq!(engine.rollback_similarity_weights().unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_export_knowledge_graph() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let mut page = create_tagged_chunk("shop/orders-page", "nextjs", &[]);
+        page.metadata.frameworks.push("laravel".to_string());
+        page.add_relationship(ChunkId::new("shop/orders-api"), RelationType::DependsOn, 0.9);
+        let mut api = create_tagged_chunk("shop/orders-api", "laravel", &[]);
+        api.add_relationship(ChunkId::new("blog/post-model"), RelationType::Similar, 0.2);
+        let post = create_tagged_chunk("blog/post-model", "laravel", &[]);
+        engine.store_chunks_batch(vec![page, api, post]).await.unwrap();
+
+        let graph = engine.knowledge_graph(&GraphExportFilter::default());
+        let kinds: HashSet<NodeKind> = graph.nodes.iter().map(|node| node.kind).collect();
+        assert_eq!(kinds.len(), 3);
+        assert!(graph.edges.iter().any(|edge| {
+            edge.source == "framework:laravel" && edge.target == "framework:nextjs" && edge.relation == "UsedWith"
+        }));
+
+        let filter = GraphExportFilter {
+            frameworks: vec!["nextjs".to_string()],
+            namespace: Some("shop/".to_string()),
+            min_weight: 0.5,
+        };
+        let shop = engine.knowledge_graph(&filter);
+        let chunks: Vec<&str> = shop.nodes.iter()
+            .filter(|node| node.kind == NodeKind::Chunk)
+            .map(|node| node.label.as_str())
+            .collect();
+        assert_eq!(chunks, vec!["shop/orders-page"]);
+        assert!(shop.edges.iter().all(|edge| edge.relation != "DependsOn" && edge.relation != "Similar"));
+
+        let dot = engine.export_graph(GraphExportFormat::Dot, &GraphExportFilter::default()).unwrap();
+        assert!(dot.contains("\"chunk:shop/orders-page\" -> \"chunk:shop/orders-api\" [label=\"DependsOn\""));
+        let graphml = engine.export_graph(GraphExportFormat::GraphMl, &filter).unwrap();
+        assert!(graphml.contains("<data key=\"kind\">framework</data>"));
+    }
+
+    #[tokio::test]
+    async fn test_store_chunk_with_parts() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            storage_path: temp_dir.path().to_path_buf(),
+            enable_search: false,
+            ..Default::default()
+        };
+        let engine = MemoryEngine::new(config).await.unwrap();
+
+        let code = "export function login() {\n  return true;\n}\n\nexport function logout() {\n  return false;\n}\n";
+        let mut chunk = create_tagged_chunk("auth", "nextjs", &["auth"]);
+        chunk.content = ChunkContent::Code {
+            language: "typescript".to_string(),
+            code: code.to_string(),
+            framework: Some("nextjs".to_string()),
+        };
+        chunk.metadata.file_path = Some("lib/auth.ts".to_string());
+
+        let ids = engine.store_chunk_with_parts(chunk.clone()).await.unwrap();
+        let ids: Vec<&str> = ids.iter().map(ChunkId::as_str).collect();
+        assert_eq!(ids, ["auth", "auth#login", "auth#logout"]);
+
+        let logout = engine.get_chunk(&ChunkId::new("auth#logout")).await.unwrap().unwrap();
+        assert_eq!(logout.metadata.line_range, Some((5, 7)));
+        assert_eq!(logout.metadata.tags, vec!["auth".to_string()]);
+        assert!(logout.relation(&chunk.id, &RelationType::PartOf).is_some());
+        let parent = engine.get_chunk(&chunk.id).await.unwrap().unwrap();
+        assert!(parent.relation(&logout.id, &RelationType::HasPart).is_some());
+
+        // Re-storing without a unit deletes its part and the edge to it
+        chunk.content = ChunkContent::Code {
+            language: "typescript".to_string(),
+            code: "export function login() {\n  return true;\n}\n\nconst TIMEOUT = 30;\n".to_string(),
+            framework: Some("nextjs".to_string()),
+        };
+        engine.store_chunk_with_parts(chunk.clone()).await.unwrap();
+        assert!(engine.get_chunk(&logout.id).await.unwrap().is_none());
+        let parent = engine.get_chunk(&chunk.id).await.unwrap().unwrap();
+        assert!(parent.relation(&logout.id, &RelationType::HasPart).is_none());
+        assert!(parent.relation(&ChunkId::new("auth#login"), &RelationType::HasPart).is_some());
+
+        // Updating the parent splits it again
+        chunk.content = ChunkContent::Code {
+            language: "typescript".to_string(),
+            code: "export function refresh() {\n  return true;\n}\n\nconst TIMEOUT = 30;\n".to_string(),
+            framework: Some("nextjs".to_string()),
+        };
+        engine.update_chunk(chunk.clone()).await.unwrap();
+        assert!(engine.get_chunk(&ChunkId::new("auth#login")).await.unwrap().is_none());
+        let refresh = engine.get_chunk(&ChunkId::new("auth#refresh")).await.unwrap().unwrap();
+        assert!(refresh.relation(&chunk.id, &RelationType::PartOf).is_some());
+
+        // Deleting the parent deletes its parts
+        assert!(engine.delete_chunk(&chunk.id).await.unwrap());
+        assert!(engine.get_chunk(&refresh.id).await.unwrap().is_none());
+        assert_eq!(engine.get_stats().total_chunks, 0);
+
+        // A chunk that had no parts when stored is still split once it has some
+        let mut notes = create_tagged_chunk("notes", "nextjs", &[]);
+        notes.content = ChunkContent::Documentation {
+            format: "markdown".to_string(),
+            content: "Session handling".to_string(),
+            language: None,
+        };
+        let ids = engine.store_chunk_with_parts(notes.clone()).await.unwrap();
+        assert_eq!(ids, vec![notes.id.clone()]);
+
+        notes.content = ChunkContent::Code {
+            language: "typescript".to_string(),
+            code: code.to_string(),
+            framework: Some("nextjs".to_string()),
+        };
+        engine.update_chunk(notes.clone()).await.unwrap();
+        assert!(engine.get_chunk(&ChunkId::new("notes#logout")).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,285 @@
+//! Persistent pattern analyzer state
+//!
+//! Stores code patterns, framework patterns, the relationship graph and
+//! learning statistics in dedicated sled trees so that `PatternAnalyzer`
+//! survives restarts. Every entry is written as soon as it changes; the
+//! state carries a version so that incompatible layouts are rebuilt from the
+//! stored chunks instead of being misread.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use sled::{Db, Tree};
+
+use crate::candidates::ChunkSignature;
+use crate::chunk::ChunkId;
+use crate::patterns::{
+    CodePattern, FeedbackStats, FrameworkPattern, FrameworkRelations, PatternStats, WeightedEdge,
+};
+use crate::pattern_usage::ChunkPatterns;
+use crate::similarity::SimilarityWeightState;
+
+/// Version of the persisted pattern state layout
+///
+/// Bump whenever a persisted type changes shape. Stores written with another
+/// version are cleared and rebuilt by re-analyzing the stored chunks.
+pub const PATTERN_STATE_VERSION: u32 = 5;
+
+/// Metadata key recording the version of the persisted pattern state
+const VERSION_KEY: &str = "pattern_state_version";
+
+/// Keys in the statistics tree
+const FEEDBACK_STATS_KEY: &str = "feedback";
+const PATTERN_STATS_KEY: &str = "patterns";
+const SIMILARITY_WEIGHTS_KEY: &str = "similarity_weights";
+
+/// Pattern analyzer state as loaded from disk
+#[derive(Debug, Default)]
+pub struct PatternState {
+    pub code_patterns: HashMap<String, CodePattern>,
+    pub chunk_patterns: HashMap<ChunkId, ChunkPatterns>,
+    pub framework_patterns: HashMap<String, FrameworkPattern>,
+    pub adjacency: HashMap<ChunkId, Vec<WeightedEdge>>,
+    pub clusters: HashMap<String, Vec<ChunkId>>,
+    pub framework_graph: HashMap<String, FrameworkRelations>,
+    pub signatures: HashMap<ChunkId, ChunkSignature>,
+    pub feedback_stats: FeedbackStats,
+    pub pattern_stats: PatternStats,
+}
+
+/// Sled trees holding the pattern analyzer state
+#[derive(Debug, Clone)]
+pub struct PatternStore {
+    code_patterns: Tree,
+    chunk_patterns: Tree,
+    framework_patterns: Tree,
+    edges: Tree,
+    clusters: Tree,
+    framework_graph: Tree,
+    signatures: Tree,
+    stats: Tree,
+    // Similarity weights fitted from feedback, kept across resets
+    weights: Tree,
+    // Shared storage metadata tree, also holding the database version
+    metadata: Tree,
+}
+
+impl PatternStore {
+    /// Open the pattern trees of a database
+    pub fn open(db: &Db, metadata: Tree) -> Result<Self> {
+        let open = |name: &str| db.open_tree(name)
+            .with_context(|| format!("Failed to open {} tree", name));
+
+        Ok(Self {
+            code_patterns: open("pattern_code")?,
+            chunk_patterns: open("pattern_chunk_patterns")?,
+            framework_patterns: open("pattern_framework")?,
+            edges: open("pattern_edges")?,
+            clusters: open("pattern_clusters")?,
+            framework_graph: open("pattern_framework_graph")?,
+            signatures: open("pattern_signatures")?,
+            stats: open("pattern_stats")?,
+            weights: open("pattern_similarity_weights")?,
+            metadata,
+        })
+    }
+
+    /// Version of the persisted state, or `None` if it was never written
+    pub fn version(&self) -> Result<Option<u32>> {
+        let Some(bytes) = self.metadata.get(VERSION_KEY).context("Failed to read pattern state version")? else {
+            return Ok(None);
+        };
+
+        let bytes: [u8; 4] = bytes.as_ref().try_into()
+            .map_err(|_| anyhow::anyhow!("Corrupt pattern state version"))?;
+        Ok(Some(u32::from_be_bytes(bytes)))
+    }
+
+    /// Check whether the persisted state can be loaded as is
+    pub fn is_current(&self) -> Result<bool> {
+        Ok(self.version()? == Some(PATTERN_STATE_VERSION))
+    }
+
+    /// Record that the persisted state is complete and matches this version
+    pub fn mark_current(&self) -> Result<()> {
+        self.metadata.insert(VERSION_KEY, &PATTERN_STATE_VERSION.to_be_bytes())
+            .context("Failed to write pattern state version")?;
+        Ok(())
+    }
+
+    /// Similarity weights applied through `PatternAnalyzer::set_similarity_weights`
+    ///
+    /// They come from feedback rather than chunks, so like the feedback
+    /// statistics they survive `reset`.
+    pub fn similarity_weights(&self) -> Result<Option<SimilarityWeightState>> {
+        get(&self.weights, SIMILARITY_WEIGHTS_KEY)
+    }
+
+    /// Store the active similarity weights and their history
+    pub fn put_similarity_weights(&self, state: &SimilarityWeightState) -> Result<()> {
+        put(&self.weights, SIMILARITY_WEIGHTS_KEY, state)
+    }
+
+    /// Remove all state derived from chunks, including the version marker
+    ///
+    /// Feedback statistics and similarity weights cannot be derived from the
+    /// chunks and are kept. Statistics written in an unreadable older layout
+    /// are dropped.
+    pub fn reset(&self) -> Result<()> {
+        let feedback_stats = self.feedback_stats().unwrap_or_else(|e| {
+            tracing::warn!("Discarding unreadable feedback statistics: {:#}", e);
+            None
+        });
+
+        self.metadata.remove(VERSION_KEY).context("Failed to clear pattern state version")?;
+        for tree in self.trees() {
+            tree.clear().context("Failed to clear pattern state")?;
+        }
+
+        if let Some(feedback_stats) = feedback_stats {
+            self.put_feedback_stats(&feedback_stats)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the persisted relationship graph
+    pub fn clear_graph(&self) -> Result<()> {
+        for tree in [&self.edges, &self.clusters, &self.framework_graph] {
+            tree.clear().context("Failed to clear relationship graph")?;
+        }
+        Ok(())
+    }
+
+    /// Load the complete persisted state
+    pub fn load(&self) -> Result<PatternState> {
+        Ok(PatternState {
+            code_patterns: load_tree(&self.code_patterns, |key| key.to_string())?,
+            chunk_patterns: load_tree(&self.chunk_patterns, ChunkId::new)?,
+            framework_patterns: load_tree(&self.framework_patterns, |key| key.to_string())?,
+            adjacency: load_tree(&self.edges, ChunkId::new)?,
+            clusters: load_tree(&self.clusters, |key| key.to_string())?,
+            framework_graph: load_tree(&self.framework_graph, |key| key.to_string())?,
+            signatures: load_tree(&self.signatures, ChunkId::new)?,
+            feedback_stats: self.feedback_stats()?.unwrap_or_default(),
+            pattern_stats: get(&self.stats, PATTERN_STATS_KEY)?.unwrap_or_default(),
+        })
+    }
+
+    /// Store a code pattern
+    pub fn put_code_pattern(&self, pattern: &CodePattern) -> Result<()> {
+        put(&self.code_patterns, &pattern.id, pattern)
+    }
+
+    /// Remove a code pattern
+    pub fn remove_code_pattern(&self, id: &str) -> Result<()> {
+        put_or_remove::<CodePattern>(&self.code_patterns, id, None)
+    }
+
+    /// Store the code patterns counted for a chunk, removing the entry when it is gone
+    pub fn put_chunk_patterns(&self, chunk_id: &ChunkId, patterns: Option<&ChunkPatterns>) -> Result<()> {
+        put_or_remove(&self.chunk_patterns, chunk_id.as_str(), patterns)
+    }
+
+    /// Store a framework pattern
+    pub fn put_framework_pattern(&self, pattern: &FrameworkPattern) -> Result<()> {
+        put(&self.framework_patterns, &pattern.id, pattern)
+    }
+
+    /// Remove a framework pattern
+    pub fn remove_framework_pattern(&self, id: &str) -> Result<()> {
+        put_or_remove::<FrameworkPattern>(&self.framework_patterns, id, None)
+    }
+
+    /// Store the outgoing edges of a chunk, removing the entry when there are none
+    pub fn put_edges(&self, chunk_id: &ChunkId, edges: Option<&Vec<WeightedEdge>>) -> Result<()> {
+        put_or_remove(&self.edges, chunk_id.as_str(), edges.filter(|edges| !edges.is_empty()))
+    }
+
+    /// Store the members of a cluster, removing the entry when it is gone
+    pub fn put_cluster(&self, name: &str, members: Option<&Vec<ChunkId>>) -> Result<()> {
+        put_or_remove(&self.clusters, name, members)
+    }
+
+    /// Store the relations of a framework, removing the entry when it is gone
+    pub fn put_framework_relations(&self, framework: &str, relations: Option<&FrameworkRelations>) -> Result<()> {
+        put_or_remove(&self.framework_graph, framework, relations)
+    }
+
+    /// Store the candidate signature of a chunk, removing the entry when it is gone
+    pub fn put_signature(&self, chunk_id: &ChunkId, signature: Option<&ChunkSignature>) -> Result<()> {
+        put_or_remove(&self.signatures, chunk_id.as_str(), signature)
+    }
+
+    /// Feedback statistics, or `None` if none were stored
+    pub fn feedback_stats(&self) -> Result<Option<FeedbackStats>> {
+        get(&self.stats, FEEDBACK_STATS_KEY)
+    }
+
+    /// Store the feedback statistics
+    pub fn put_feedback_stats(&self, stats: &FeedbackStats) -> Result<()> {
+        put(&self.stats, FEEDBACK_STATS_KEY, stats)
+    }
+
+    /// Store the pattern statistics
+    pub fn put_pattern_stats(&self, stats: &PatternStats) -> Result<()> {
+        put(&self.stats, PATTERN_STATS_KEY, stats)
+    }
+
+    fn trees(&self) -> [&Tree; 8] {
+        [
+            &self.code_patterns,
+            &self.chunk_patterns,
+            &self.framework_patterns,
+            &self.edges,
+            &self.clusters,
+            &self.framework_graph,
+            &self.signatures,
+            &self.stats,
+        ]
+    }
+}
+
+fn put<T: Serialize>(tree: &Tree, key: &str, value: &T) -> Result<()> {
+    let serialized = bincode::serialize(value)
+        .context("Failed to serialize pattern state")?;
+    tree.insert(key, serialized)
+        .context("Failed to write pattern state")?;
+    Ok(())
+}
+
+fn put_or_remove<T: Serialize>(tree: &Tree, key: &str, value: Option<&T>) -> Result<()> {
+    match value {
+        Some(value) => put(tree, key, value),
+        None => {
+            tree.remove(key).context("Failed to remove pattern state")?;
+            Ok(())
+        }
+    }
+}
+
+fn get<T: DeserializeOwned>(tree: &Tree, key: &str) -> Result<Option<T>> {
+    match tree.get(key).context("Failed to read pattern state")? {
+        Some(bytes) => Ok(Some(
+            bincode::deserialize(&bytes).context("Failed to deserialize pattern state")?,
+        )),
+        None => Ok(None),
+    }
+}
+
+fn load_tree<K, T, F>(tree: &Tree, make_key: F) -> Result<HashMap<K, T>>
+where
+    K: std::hash::Hash + Eq,
+    T: DeserializeOwned,
+    F: Fn(&str) -> K,
+{
+    let mut entries = HashMap::new();
+    for entry in tree.iter() {
+        let (key, value) = entry.context("Failed to iterate pattern state")?;
+        let key = std::str::from_utf8(&key).context("Invalid pattern state key")?;
+        let value = bincode::deserialize(&value)
+            .with_context(|| format!("Failed to deserialize pattern state entry {}", key))?;
+        entries.insert(make_key(key), value);
+    }
+    Ok(entries)
+}
@@ -0,0 +1,261 @@
+//! Code pattern usage and trends
+//!
+//! A code pattern is identified by its type and signature, so every chunk
+//! exhibiting it updates one [`CodePattern`] rather than adding a copy. Each
+//! pattern counts the chunks using it, per framework and per chunk creation
+//! day, and keeps the IDs of a few recent example chunks. The patterns found
+//! in each chunk are remembered so removing or re-analyzing a chunk takes
+//! its contribution back out, which keeps every count derivable from the
+//! stored chunks.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::ChunkId;
+use crate::patterns::{CodePattern, PatternType};
+
+/// Relative change between trend windows below which a pattern counts as stable
+pub const TREND_STABLE_CHANGE: f32 = 0.2;
+
+/// Stable ID of the code pattern with the given type and signature
+///
+/// Lowercased, with runs of other characters than letters and digits
+/// collapsed to `-`: `Function` and `export function` give
+/// `function-export-function`.
+pub fn code_pattern_id(pattern_type: &PatternType, signature: &str) -> String {
+    let kind = match pattern_type {
+        PatternType::Custom(name) => format!("custom-{}", name),
+        other => format!("{:?}", other),
+    };
+
+    let mut id = String::new();
+    for c in format!("{} {}", kind, signature).chars() {
+        if c.is_alphanumeric() {
+            id.extend(c.to_lowercase());
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_string()
+}
+
+impl CodePattern {
+    /// A pattern detected in one chunk, not yet counted
+    pub fn detected(name: &str, pattern_type: PatternType, signature: &str, frameworks: Vec<String>, confidence: f32) -> Self {
+        Self {
+            id: code_pattern_id(&pattern_type, signature),
+            name: name.to_string(),
+            pattern_type,
+            signature: signature.to_string(),
+            frameworks,
+            confidence,
+            usage_count: 0,
+            examples: Vec::new(),
+            framework_counts: BTreeMap::new(),
+            daily_counts: BTreeMap::new(),
+        }
+    }
+
+    /// Count a chunk exhibiting the pattern
+    ///
+    /// The chunk becomes the newest example; at most `max_examples` are kept.
+    pub fn add_chunk(&mut self, chunk_id: &ChunkId, frameworks: &[String], day: NaiveDate, max_examples: usize) {
+        self.usage_count += 1;
+        for framework in frameworks {
+            *self.framework_counts.entry(framework.clone()).or_default() += 1;
+        }
+        *self.daily_counts.entry(day).or_default() += 1;
+        self.frameworks = self.framework_counts.keys().cloned().collect();
+
+        self.examples.retain(|id| id != chunk_id);
+        self.examples.insert(0, chunk_id.clone());
+        self.examples.truncate(max_examples);
+    }
+
+    /// Take back what [`add_chunk`](Self::add_chunk) counted for a chunk
+    pub fn remove_chunk(&mut self, chunk_id: &ChunkId, frameworks: &[String], day: NaiveDate) {
+        self.usage_count = self.usage_count.saturating_sub(1);
+        for framework in frameworks {
+            decrement(&mut self.framework_counts, framework);
+        }
+        decrement(&mut self.daily_counts, &day);
+        self.frameworks = self.framework_counts.keys().cloned().collect();
+        self.examples.retain(|id| id != chunk_id);
+    }
+
+    /// Chunks exhibiting the pattern that were created in `[from, to)`
+    pub fn chunks_between(&self, from: NaiveDate, to: NaiveDate) -> u64 {
+        if from >= to {
+            return 0;
+        }
+        self.daily_counts.range(from..to).map(|(_, count)| count).sum()
+    }
+
+    /// Usage and trend of the pattern
+    ///
+    /// `analyzed` is the number of chunks analyzed in total. The trend
+    /// compares the chunks created in the last `window_days` days up to `now`
+    /// with the `window_days` before.
+    pub fn statistics(&self, analyzed: usize, window_days: u32, now: DateTime<Utc>) -> PatternStatistics {
+        let today = now.date_naive();
+        let window = Duration::days(i64::from(window_days.max(1)));
+        let recent_start = today - window + Duration::days(1);
+        let previous_start = recent_start - window;
+        let tomorrow = today + Duration::days(1);
+
+        let recent = self.chunks_between(recent_start, tomorrow);
+        let previous = self.chunks_between(previous_start, recent_start);
+        let change = (previous > 0).then(|| (recent as f32 - previous as f32) / previous as f32);
+        let trend = match change {
+            None if recent > 0 => PatternTrend::New,
+            None => PatternTrend::Dormant,
+            Some(change) if change > TREND_STABLE_CHANGE => PatternTrend::Rising,
+            Some(change) if change < -TREND_STABLE_CHANGE => PatternTrend::Falling,
+            Some(_) => PatternTrend::Stable,
+        };
+
+        PatternStatistics {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            pattern_type: self.pattern_type.clone(),
+            usage_count: self.usage_count,
+            frequency: if analyzed > 0 { self.usage_count as f32 / analyzed as f32 } else { 0.0 },
+            framework_counts: self.framework_counts.clone(),
+            first_seen: self.daily_counts.keys().next().copied(),
+            last_seen: self.daily_counts.keys().next_back().copied(),
+            recent,
+            previous,
+            change,
+            trend,
+        }
+    }
+}
+
+fn decrement<K: Ord>(counts: &mut BTreeMap<K, u64>, key: &K) {
+    if let Some(count) = counts.get_mut(key) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            counts.remove(key);
+        }
+    }
+}
+
+/// Code patterns found in one chunk, as counted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkPatterns {
+    /// Creation day of the chunk
+    pub day: NaiveDate,
+    pub detections: Vec<PatternDetection>,
+}
+
+/// A code pattern counted for a chunk, with the frameworks it was counted under
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternDetection {
+    pub pattern_id: String,
+    pub frameworks: Vec<String>,
+}
+
+/// Direction in which a pattern's usage is moving
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatternTrend {
+    /// Used by noticeably more new chunks than in the previous window
+    Rising,
+    /// Used by about as many new chunks as in the previous window
+    Stable,
+    /// Used by noticeably fewer new chunks than in the previous window
+    Falling,
+    /// Only used by chunks created in the latest window
+    New,
+    /// Not used by any chunk created in either window
+    Dormant,
+}
+
+/// Usage and trend of a code pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternStatistics {
+    pub id: String,
+    pub name: String,
+    pub pattern_type: PatternType,
+    /// Chunks exhibiting the pattern
+    pub usage_count: u64,
+    /// Share of the analyzed chunks exhibiting the pattern
+    pub frequency: f32,
+    /// Chunks exhibiting the pattern per framework
+    pub framework_counts: BTreeMap<String, u64>,
+    /// Creation day of the oldest and newest chunk exhibiting the pattern
+    pub first_seen: Option<NaiveDate>,
+    pub last_seen: Option<NaiveDate>,
+    /// Chunks created in the latest trend window
+    pub recent: u64,
+    /// Chunks created in the window before that
+    pub previous: u64,
+    /// Relative change from `previous` to `recent`, if there were previous chunks
+    pub change: Option<f32>,
+    pub trend: PatternTrend,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(offset: i64) -> NaiveDate {
+        Utc::now().date_naive() - Duration::days(offset)
+    }
+
+    #[test]
+    fn test_code_pattern_ids_are_stable() {
+        assert_eq!(code_pattern_id(&PatternType::Function, "export function"), "function-export-function");
+        assert_eq!(
+            code_pattern_id(&PatternType::ApiEndpoint, "NextApiRequest, NextApiResponse"),
+            "apiendpoint-nextapirequest-nextapiresponse"
+        );
+        assert_eq!(code_pattern_id(&PatternType::Custom("Hooks".to_string()), "use*()"), "custom-hooks-use");
+    }
+
+    #[test]
+    fn test_counts_and_examples() {
+        let mut pattern = CodePattern::detected("Export Function", PatternType::Function, "export function", Vec::new(), 0.9);
+        let frameworks = vec!["nextjs".to_string()];
+        for i in 0..4 {
+            pattern.add_chunk(&ChunkId::new(&format!("chunk-{}", i)), &frameworks, day(0), 3);
+        }
+        pattern.add_chunk(&ChunkId::new("chunk-4"), &["react".to_string()], day(1), 3);
+
+        assert_eq!(pattern.usage_count, 5);
+        assert_eq!(pattern.frameworks, vec!["nextjs".to_string(), "react".to_string()]);
+        let examples: Vec<&str> = pattern.examples.iter().map(ChunkId::as_str).collect();
+        assert_eq!(examples, ["chunk-4", "chunk-3", "chunk-2"]);
+
+        pattern.remove_chunk(&ChunkId::new("chunk-4"), &["react".to_string()], day(1));
+        assert_eq!(pattern.usage_count, 4);
+        assert_eq!(pattern.frameworks, frameworks);
+        assert_eq!(pattern.framework_counts["nextjs"], 4);
+        assert_eq!(pattern.daily_counts.len(), 1);
+        assert_eq!(pattern.examples.len(), 2);
+    }
+
+    #[test]
+    fn test_trend_statistics() {
+        let mut pattern = CodePattern::detected("Arrow Function", PatternType::Function, "=>", Vec::new(), 0.8);
+        let mut add = |name: &str, offset: i64| pattern.add_chunk(&ChunkId::new(name), &[], day(offset), 10);
+        // One chunk in the previous week, three in the current one
+        add("old", 9);
+        add("a", 0);
+        add("b", 2);
+        add("c", 6);
+
+        let stats = pattern.statistics(8, 7, Utc::now());
+        assert_eq!(stats.usage_count, 4);
+        assert!((stats.frequency - 0.5).abs() < 1e-6);
+        assert_eq!((stats.recent, stats.previous), (3, 1));
+        assert_eq!(stats.change, Some(2.0));
+        assert_eq!(stats.trend, PatternTrend::Rising);
+        assert_eq!(stats.first_seen, Some(day(9)));
+        assert_eq!(stats.last_seen, Some(day(0)));
+
+        assert_eq!(pattern.statistics(8, 1, Utc::now()).trend, PatternTrend::New);
+        assert_eq!(pattern.statistics(8, 7, Utc::now() + Duration::days(30)).trend, PatternTrend::Dormant);
+    }
+}
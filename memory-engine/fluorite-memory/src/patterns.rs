@@ -4,18 +4,30 @@
 //! building between learning chunks. Includes Next.js + Laravel pattern specialization
 //! and machine learning-inspired similarity scoring.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use parking_lot::RwLock;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock as AsyncRwLock;
 
+use crate::candidates::{CandidateIndex, ChunkSignature};
+use crate::combinations::FrameworkCooccurrence;
+use crate::export::{relation_name, GraphExport, GraphExportFilter, NodeKind, HAS_PATTERN, USES_FRAMEWORK};
+use crate::graph::ChunkGraph;
 use crate::chunk::{
-    ChunkId, LearningChunk, ChunkContent, ChunkType, RelationType, 
+    ChunkId, LearningChunk, ChunkContent, ChunkType, RelationProvenance, RelationType, 
     UserFeedback, FeedbackType, SimilarityMatch, SimilarityType
 };
+use crate::pattern_store::PatternStore;
+use crate::pattern_usage::{ChunkPatterns, PatternDetection, PatternStatistics};
+use crate::rules::{RuleDirectory, RuleSet};
+use crate::similarity::{SimilarityComponents, SimilarityWeightState, SimilarityWeights};
 
 /// Configuration for pattern analysis
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +42,19 @@ pub struct PatternConfig {
     pub embedding_dim: usize,
     /// Learning rate for feedback incorporation
     pub learning_rate: f32,
+    /// Maximum number of candidate chunks scored by `find_similar`
+    pub candidate_budget: usize,
+    /// Weights of the component similarities; weights fitted from feedback
+    /// take precedence once applied
+    pub similarity_weights: SimilarityWeights,
+    /// Directory of YAML/JSON rule packs added to the built-in detection rules
+    pub rules_dir: Option<PathBuf>,
+    /// How often, in milliseconds, the rule directory is checked for changes
+    pub rules_reload_interval_ms: u64,
+    /// Number of example chunk IDs kept per code pattern
+    pub max_pattern_examples: usize,
+    /// Length in days of the windows compared for pattern trends
+    pub trend_window_days: u32,
 }
 
 impl Default for PatternConfig {
@@ -40,40 +65,107 @@ impl Default for PatternConfig {
             max_relationships: 20,
             embedding_dim: 384,
             learning_rate: 0.1,
+            candidate_budget: 200,
+            similarity_weights: SimilarityWeights::default(),
+            rules_dir: None,
+            rules_reload_interval_ms: 2000,
+            max_pattern_examples: 10,
+            trend_window_days: 7,
         }
     }
 }
 
+/// Graph edges for the relationships a chunk stores itself
+///
+/// Mirrors of other chunks' relationships are left out since the graph holds
+/// the original edge. With `similar` set, freshly computed similarity edges
+/// replace inferred relationships while declared ones are kept.
+fn relation_edges(chunk: &LearningChunk, similar: Option<Vec<WeightedEdge>>) -> Vec<WeightedEdge> {
+    let replace_inferred = similar.is_some();
+    let mut edges = similar.unwrap_or_default();
+
+    for relation in &chunk.relationships {
+        if relation.inverse || relation.target == chunk.id {
+            continue;
+        }
+        if replace_inferred && relation.provenance == RelationProvenance::Inferred {
+            continue;
+        }
+
+        let edge = WeightedEdge {
+            target: relation.target.clone(),
+            relation_type: relation.relation_type.clone(),
+            weight: relation.strength,
+            confidence: relation.confidence,
+        };
+        match edges.iter_mut().find(|existing| {
+            existing.target == edge.target && existing.relation_type == edge.relation_type
+        }) {
+            // Stored relationships win over computed ones
+            Some(existing) => *existing = edge,
+            None => edges.push(edge),
+        }
+    }
+
+    edges
+}
+
+/// Number of chunks scored in parallel per rebuild batch
+const REBUILD_BATCH_SIZE: usize = 256;
+
 /// Pattern recognition and analysis engine
 #[derive(Debug)]
 pub struct PatternAnalyzer {
     config: PatternConfig,
     // Pattern databases
     code_patterns: Arc<RwLock<HashMap<String, CodePattern>>>,
+    // Code patterns counted for each chunk
+    chunk_patterns: Arc<RwLock<HashMap<ChunkId, ChunkPatterns>>>,
     framework_patterns: Arc<RwLock<HashMap<String, FrameworkPattern>>>,
     relationship_graph: Arc<RwLock<RelationshipGraph>>,
+    // Similarity candidate indexes
+    candidates: Arc<RwLock<CandidateIndex>>,
+    // Active similarity weights and the ones they replaced
+    similarity_weights: Arc<RwLock<SimilarityWeightState>>,
+    // Active detection rules and the directory they are reloaded from
+    rules: Arc<RwLock<Arc<RuleSet>>>,
+    rule_directory: Option<RuleDirectory>,
     // Learning statistics
     feedback_stats: Arc<RwLock<FeedbackStats>>,
     pattern_stats: Arc<RwLock<PatternStats>>,
+    // Persistent copy of the state above, written as it changes
+    store: Option<PatternStore>,
+    // Set when the persisted state was missing or outdated and must be rebuilt
+    needs_rebuild: AtomicBool,
 }
 
-/// Code pattern for recognition
+/// Code pattern for recognition, aggregated over the chunks exhibiting it
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodePattern {
+    /// Stable ID derived from the pattern type and signature
     pub id: String,
     pub name: String,
     pub pattern_type: PatternType,
     pub signature: String,
+    /// Frameworks of the chunks exhibiting the pattern, sorted
     pub frameworks: Vec<String>,
     pub confidence: f32,
+    /// Number of chunks exhibiting the pattern
     pub usage_count: u64,
-    pub examples: Vec<String>,
+    /// IDs of the most recently analyzed chunks exhibiting the pattern, newest first
+    pub examples: Vec<ChunkId>,
+    /// Chunks exhibiting the pattern per framework
+    pub framework_counts: BTreeMap<String, u64>,
+    /// Chunks exhibiting the pattern per creation day
+    pub daily_counts: BTreeMap<chrono::NaiveDate, u64>,
 }
 
 /// Framework-specific patterns
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrameworkPattern {
     pub id: String,
+    /// Chunk the pattern was detected in
+    pub chunk_id: ChunkId,
     pub framework: String,
     pub pattern_name: String,
     pub template: String,
@@ -84,7 +176,7 @@ pub struct FrameworkPattern {
 }
 
 /// Types of code patterns
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PatternType {
     /// Function/method patterns
     Function,
@@ -119,10 +211,118 @@ pub struct RelationshipGraph {
     clusters: HashMap<String, Vec<ChunkId>>,
     /// Framework relationships
     framework_graph: HashMap<String, FrameworkRelations>,
+    /// Framework co-occurrence across chunks, derived from `framework_graph`
+    cooccurrence: FrameworkCooccurrence,
+}
+
+impl RelationshipGraph {
+    /// Register a chunk under each of its frameworks and record its framework combination
+    fn add_frameworks(&mut self, chunk: &LearningChunk) {
+        let frameworks = &chunk.metadata.frameworks;
+
+        for framework in frameworks {
+            let relations = self.framework_graph
+                .entry(framework.clone())
+                .or_insert_with(|| FrameworkRelations {
+                    primary_chunks: Vec::new(),
+                    integration_patterns: HashMap::new(),
+                });
+
+            if !relations.primary_chunks.contains(&chunk.id) {
+                relations.primary_chunks.push(chunk.id.clone());
+            }
+
+            for other in frameworks.iter().filter(|other| *other != framework) {
+                let members = relations.integration_patterns
+                    .entry(format!("{}->{}", framework, other))
+                    .or_default();
+                if !members.contains(&chunk.id) {
+                    members.push(chunk.id.clone());
+                }
+            }
+        }
+
+        self.cooccurrence.insert(&chunk.id, frameworks);
+    }
+
+    /// Recount framework co-occurrence from the chunks registered per framework
+    fn restore_cooccurrence(&mut self) {
+        let mut chunk_frameworks: HashMap<&ChunkId, Vec<String>> = HashMap::new();
+        for (framework, relations) in &self.framework_graph {
+            for chunk_id in &relations.primary_chunks {
+                chunk_frameworks.entry(chunk_id).or_default().push(framework.clone());
+            }
+        }
+
+        let mut cooccurrence = FrameworkCooccurrence::new();
+        for (chunk_id, frameworks) in chunk_frameworks {
+            cooccurrence.insert(chunk_id, &frameworks);
+        }
+        self.cooccurrence = cooccurrence;
+    }
+
+    /// Group chunks connected by similarity edges
+    ///
+    /// Each connected component with at least two chunks becomes a cluster
+    /// named after its smallest chunk ID.
+    fn similarity_clusters(&self) -> HashMap<String, Vec<ChunkId>> {
+        let mut parent: HashMap<&ChunkId, &ChunkId> = HashMap::new();
+
+        fn find<'a>(parent: &mut HashMap<&'a ChunkId, &'a ChunkId>, id: &'a ChunkId) -> &'a ChunkId {
+            let mut root = id;
+            while let Some(&next) = parent.get(root) {
+                if next == root {
+                    break;
+                }
+                root = next;
+            }
+            // Path compression
+            let mut current = id;
+            while current != root {
+                let next = parent.insert(current, root).unwrap_or(root);
+                current = next;
+            }
+            root
+        }
+
+        for (source, edges) in &self.adjacency {
+            parent.entry(source).or_insert(source);
+            for edge in edges {
+                parent.entry(&edge.target).or_insert(&edge.target);
+                let a = find(&mut parent, source);
+                let b = find(&mut parent, &edge.target);
+                if a != b {
+                    // Keep the smallest ID as root so cluster names are stable
+                    let (root, child) = if a.as_str() <= b.as_str() { (a, b) } else { (b, a) };
+                    parent.insert(child, root);
+                }
+            }
+        }
+
+        let ids: Vec<&ChunkId> = parent.keys().copied().collect();
+        let mut components: HashMap<&ChunkId, Vec<ChunkId>> = HashMap::new();
+        for id in ids {
+            let root = find(&mut parent, id);
+            components.entry(root).or_default().push(id.clone());
+        }
+
+        components.into_iter()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(root, mut members)| {
+                members.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+                (format!("cluster-{}", root), members)
+            })
+            .collect()
+    }
+
+    /// Number of distinct framework combinations
+    fn combination_count(&self) -> usize {
+        self.cooccurrence.len()
+    }
 }
 
 /// Weighted edge in the relationship graph
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeightedEdge {
     pub target: ChunkId,
     pub relation_type: RelationType,
@@ -131,25 +331,61 @@ pub struct WeightedEdge {
 }
 
 /// Framework relationship mapping
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrameworkRelations {
     pub primary_chunks: Vec<ChunkId>,
     pub integration_patterns: HashMap<String, Vec<ChunkId>>, // e.g., "nextjs->laravel" -> chunks
-    pub common_combinations: Vec<FrameworkCombination>,
 }
 
-/// Common framework combinations (e.g., Next.js + Laravel)
+/// Frameworks used together (e.g., Next.js + Laravel) and how strongly
+///
+/// See [`crate::combinations`] for how the scores are defined.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrameworkCombination {
+    /// Frameworks in the combination, sorted
     pub frameworks: Vec<String>,
     pub pattern_name: String,
+    /// Chunks using every framework in the combination
     pub chunks: Vec<ChunkId>,
+    /// Share of framework-tagged chunks using the whole combination
+    pub support: f32,
+    /// Support relative to the support of the most common member
     pub confidence: f32,
+    /// Support relative to independent use of the frameworks
+    pub lift: f32,
     pub usage_frequency: u64,
 }
 
+/// Progress of a relationship rebuild
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RelationshipProgress {
+    /// Chunks processed so far
+    pub processed: usize,
+    /// Chunks known to the analyzer when the rebuild started
+    pub total: usize,
+    /// Similarity edges found so far
+    pub edges: usize,
+}
+
+/// Outcome of a relationship rebuild
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RelationshipRebuildReport {
+    /// Chunks processed
+    pub chunks: usize,
+    /// Similarity edges in the new graph
+    pub edges: usize,
+    /// Clusters of connected chunks
+    pub clusters: usize,
+    /// Distinct framework combinations
+    pub combinations: usize,
+    /// Stored chunks whose inferred relationships changed (set by `MemoryEngine`)
+    pub chunks_updated: usize,
+    /// Wall time of the rebuild
+    pub duration_ms: u64,
+}
+
 /// Statistics for feedback learning
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FeedbackStats {
     pub total_feedback: u64,
     pub helpful_count: u64,
@@ -160,7 +396,7 @@ pub struct FeedbackStats {
 }
 
 /// Pattern analysis statistics
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PatternStats {
     pub total_patterns: u64,
     pub framework_patterns: HashMap<String, u64>,
@@ -172,27 +408,241 @@ pub struct PatternStats {
 impl PatternAnalyzer {
     /// Create a new pattern analyzer
     pub fn new(embedding_dim: usize) -> Self {
-        let config = PatternConfig {
+        Self::with_config(PatternConfig {
             embedding_dim,
             ..Default::default()
-        };
+        })
+    }
 
+    /// Create a pattern analyzer with custom configuration
+    pub fn with_config(config: PatternConfig) -> Self {
+        let similarity_weights = SimilarityWeightState::new(config.similarity_weights);
+        let rule_directory = config.rules_dir.as_ref().map(|dir| {
+            RuleDirectory::new(dir, Duration::from_millis(config.rules_reload_interval_ms))
+        });
         Self {
             config,
             code_patterns: Arc::new(RwLock::new(HashMap::new())),
+            chunk_patterns: Arc::new(RwLock::new(HashMap::new())),
             framework_patterns: Arc::new(RwLock::new(HashMap::new())),
             relationship_graph: Arc::new(RwLock::new(RelationshipGraph::default())),
+            candidates: Arc::new(RwLock::new(CandidateIndex::new())),
+            similarity_weights: Arc::new(RwLock::new(similarity_weights)),
+            rules: Arc::new(RwLock::new(Arc::new(RuleSet::builtin()))),
+            rule_directory,
             feedback_stats: Arc::new(RwLock::new(FeedbackStats::default())),
             pattern_stats: Arc::new(RwLock::new(PatternStats::default())),
+            store: None,
+            needs_rebuild: AtomicBool::new(false),
+        }
+    }
+
+    /// Create a pattern analyzer backed by persistent state
+    ///
+    /// State written by the current version is loaded as is. Missing or
+    /// outdated state is discarded and the analyzer starts empty, reporting
+    /// [`needs_rebuild`](Self::needs_rebuild) until [`rebuild`](Self::rebuild) runs.
+    pub fn with_store(config: PatternConfig, store: PatternStore) -> Result<Self> {
+        let mut analyzer = Self::with_config(config);
+
+        if let Some(weights) = store.similarity_weights()? {
+            *analyzer.similarity_weights.write() = weights;
+        }
+
+        if store.is_current()? {
+            let state = store.load()?;
+            tracing::info!(
+                "Loaded pattern state with {} code patterns and {} frameworks",
+                state.code_patterns.len(),
+                state.framework_graph.len()
+            );
+
+            *analyzer.code_patterns.write() = state.code_patterns;
+            *analyzer.chunk_patterns.write() = state.chunk_patterns;
+            *analyzer.framework_patterns.write() = state.framework_patterns;
+            *analyzer.relationship_graph.write() = RelationshipGraph {
+                adjacency: state.adjacency,
+                clusters: state.clusters,
+                framework_graph: state.framework_graph,
+                cooccurrence: FrameworkCooccurrence::new(),
+            };
+            analyzer.relationship_graph.write().restore_cooccurrence();
+            {
+                let mut candidates = analyzer.candidates.write();
+                for (chunk_id, signature) in state.signatures {
+                    candidates.insert(chunk_id, signature);
+                }
+            }
+            *analyzer.feedback_stats.write() = state.feedback_stats;
+            *analyzer.pattern_stats.write() = state.pattern_stats;
+        } else {
+            tracing::info!("Pattern state is missing or outdated ({:?}), rebuild required", store.version()?);
+            store.reset()?;
+            *analyzer.feedback_stats.write() = store.feedback_stats()?.unwrap_or_default();
+            analyzer.needs_rebuild.store(true, Ordering::SeqCst);
+        }
+
+        analyzer.store = Some(store);
+        Ok(analyzer)
+    }
+
+    /// Check whether the persisted state must be rebuilt from the stored chunks
+    pub fn needs_rebuild(&self) -> bool {
+        self.needs_rebuild.load(Ordering::SeqCst)
+    }
+
+    /// Discard all learned state and re-analyze the given chunks
+    ///
+    /// Feedback statistics are kept since they cannot be derived from chunks.
+    /// The persisted state is marked current only once every chunk was analyzed.
+    pub async fn rebuild<I>(&self, chunks: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = Result<LearningChunk>>,
+    {
+        self.code_patterns.write().clear();
+        self.chunk_patterns.write().clear();
+        self.framework_patterns.write().clear();
+        *self.relationship_graph.write() = RelationshipGraph::default();
+        self.candidates.write().clear();
+        *self.pattern_stats.write() = PatternStats::default();
+
+        self.persist(|store| store.reset())?;
+
+        let mut analyzed = 0;
+        for chunk in chunks {
+            self.analyze_chunk(&chunk?).await?;
+            analyzed += 1;
+        }
+
+        self.persist(|store| store.mark_current())?;
+        self.needs_rebuild.store(false, Ordering::SeqCst);
+
+        tracing::info!("Rebuilt pattern state from {} chunks", analyzed);
+        Ok(analyzed)
+    }
+
+    /// Snapshot of the relationship graph for traversal queries
+    pub fn graph(&self) -> ChunkGraph {
+        ChunkGraph::from_adjacency(&self.relationship_graph.read().adjacency)
+    }
+
+    /// Copy of the relationship graph's outgoing edges per chunk
+    pub fn adjacency_snapshot(&self) -> HashMap<ChunkId, Vec<WeightedEdge>> {
+        self.relationship_graph.read().adjacency.clone()
+    }
+
+    /// Node-link view of the relationship graph matching a filter
+    ///
+    /// Chunks, the code patterns found in them and their frameworks become
+    /// nodes; chunk relationships, pattern and framework usage, and framework
+    /// combinations (`UsedWith`) become weighted edges.
+    pub fn export_graph(&self, filter: &GraphExportFilter) -> GraphExport {
+        let graph = self.relationship_graph.read();
+        let candidates = self.candidates.read();
+        let mut export = GraphExport::new();
+
+        for (source, edges) in &graph.adjacency {
+            export.add_chunk(source, Vec::new());
+            for edge in edges {
+                export.add_chunk(&edge.target, Vec::new());
+                export.add_relation(source, &edge.target, &edge.relation_type, edge.weight);
+            }
+        }
+
+        for (chunk_id, signature) in candidates.signatures() {
+            export.add_chunk(chunk_id, Vec::new());
+            for pattern in &signature.patterns {
+                export.add_entity(NodeKind::Pattern, pattern);
+                export.add_edge(
+                    NodeKind::Chunk.node_id(chunk_id.as_str()),
+                    NodeKind::Pattern.node_id(pattern),
+                    HAS_PATTERN.to_string(),
+                    1.0,
+                );
+            }
+        }
+
+        for (framework, relations) in &graph.framework_graph {
+            export.add_entity(NodeKind::Framework, framework);
+            for chunk_id in &relations.primary_chunks {
+                export.add_chunk(chunk_id, vec![framework.clone()]);
+                export.add_edge(
+                    NodeKind::Chunk.node_id(chunk_id.as_str()),
+                    NodeKind::Framework.node_id(framework),
+                    USES_FRAMEWORK.to_string(),
+                    1.0,
+                );
+            }
+        }
+
+        for combination in graph.cooccurrence.combinations::<&str>(&[], 0.0) {
+            if let [first, second] = combination.frameworks.as_slice() {
+                for (source, target) in [(first, second), (second, first)] {
+                    export.add_edge(
+                        NodeKind::Framework.node_id(source),
+                        NodeKind::Framework.node_id(target),
+                        relation_name(&RelationType::UsedWith),
+                        combination.confidence,
+                    );
+                }
+            }
+        }
+
+        export.filtered(filter)
+    }
+
+    /// Weights currently combining the component similarities
+    pub fn similarity_weights(&self) -> SimilarityWeights {
+        self.similarity_weights.read().current
+    }
+
+    /// Replace the similarity weights, keeping the old ones for rollback
+    ///
+    /// Similarity edges pick up the new weights on the next relationship rebuild.
+    pub fn set_similarity_weights(&self, weights: SimilarityWeights) -> Result<()> {
+        let mut state = self.similarity_weights.write();
+        state.apply(weights);
+        self.persist(|store| store.put_similarity_weights(&state))
+    }
+
+    /// Restore the similarity weights replaced last, returning them
+    pub fn rollback_similarity_weights(&self) -> Result<Option<SimilarityWeights>> {
+        let mut state = self.similarity_weights.write();
+        let restored = state.rollback();
+        if restored.is_some() {
+            self.persist(|store| store.put_similarity_weights(&state))?;
+        }
+        Ok(restored)
+    }
+
+    /// Configuration of the analyzer
+    pub fn config(&self) -> &PatternConfig {
+        &self.config
+    }
+
+    /// Apply a write to the persistent store, if there is one
+    fn persist<F>(&self, write: F) -> Result<()>
+    where
+        F: FnOnce(&PatternStore) -> Result<()>,
+    {
+        match &self.store {
+            Some(store) => write(store),
+            None => Ok(()),
         }
     }
 
     /// Analyze a chunk for patterns and relationships
     pub async fn analyze_chunk(&self, chunk: &LearningChunk) -> Result<()> {
         tracing::debug!("Analyzing patterns for chunk: {}", chunk.id);
+        self.reload_rules_if_changed();
 
         // Extract code patterns
-        self.extract_code_patterns(chunk).await?;
+        let code_signatures = self.extract_code_patterns(chunk).await?;
+
+        // Index the chunk for similarity candidate generation
+        let signature = ChunkSignature::from_chunk(chunk, &code_signatures);
+        self.persist(|store| store.put_signature(&chunk.id, Some(&signature)))?;
+        self.candidates.write().insert(chunk.id.clone(), signature);
 
         // Analyze framework patterns
         self.analyze_framework_patterns(chunk).await?;
@@ -208,51 +658,150 @@ impl PatternAnalyzer {
             for framework in &chunk.metadata.frameworks {
                 *stats.framework_patterns.entry(framework.clone()).or_insert(0) += 1;
             }
+
+            self.persist(|store| store.put_pattern_stats(&stats))?;
         }
 
         tracing::debug!("Pattern analysis completed for chunk: {}", chunk.id);
         Ok(())
     }
 
-    /// Find similar chunks based on content and patterns
-    pub async fn find_similar(&self, chunk: &LearningChunk, limit: usize) -> Result<Vec<SimilarityMatch>> {
-        tracing::debug!("Finding similar chunks for: {}", chunk.id);
+    /// Forget a chunk that was deleted or is about to be re-analyzed
+    pub async fn remove_chunk(&self, chunk_id: &ChunkId) -> Result<()> {
+        tracing::debug!("Removing chunk from pattern graph: {}", chunk_id);
 
-        let mut similarities = Vec::new();
+        // Only analyzed chunks have a signature and were counted in the statistics
+        let analyzed = self.candidates.write().remove(chunk_id);
+        if analyzed {
+            self.persist(|store| store.put_signature(chunk_id, None))?;
+        }
+        self.forget_code_patterns(chunk_id, &mut self.code_patterns.write(), &mut self.chunk_patterns.write())?;
 
-        // Get potential candidates from relationship graph
-        let candidates = self.get_similarity_candidates(chunk).await?;
+        let mut graph = self.relationship_graph.write();
 
-        for candidate_chunk in candidates {
-            if candidate_chunk.id == chunk.id {
-                continue; // Skip self
+        // Keys whose entries changed, persisted once the graph is consistent again
+        let mut changed_edges = Vec::new();
+        let mut changed_clusters = Vec::new();
+        let mut changed_frameworks = Vec::new();
+        let mut primary_frameworks = Vec::new();
+
+        if graph.adjacency.remove(chunk_id).is_some() {
+            changed_edges.push(chunk_id.clone());
+        }
+        for (source, edges) in graph.adjacency.iter_mut() {
+            let before = edges.len();
+            edges.retain(|edge| &edge.target != chunk_id);
+            if edges.len() != before {
+                changed_edges.push(source.clone());
             }
+        }
 
-            // Calculate different types of similarity
-            let content_similarity = self.calculate_content_similarity(chunk, &candidate_chunk)?;
-            let structural_similarity = self.calculate_structural_similarity(chunk, &candidate_chunk)?;
-            let semantic_similarity = self.calculate_semantic_similarity(chunk, &candidate_chunk)?;
-            let framework_similarity = self.calculate_framework_similarity(chunk, &candidate_chunk)?;
+        for (name, members) in graph.clusters.iter_mut() {
+            if members.contains(chunk_id) {
+                members.retain(|id| id != chunk_id);
+                changed_clusters.push(name.clone());
+            }
+        }
+        graph.clusters.retain(|_, members| !members.is_empty());
 
-            // Combine similarities with weights
-            let overall_similarity = (
-                content_similarity * 0.3 +
-                structural_similarity * 0.3 +
-                semantic_similarity * 0.25 +
-                framework_similarity * 0.15
-            );
+        for (framework, relations) in graph.framework_graph.iter_mut() {
+            let mut changed = relations.primary_chunks.contains(chunk_id);
+            if changed {
+                primary_frameworks.push(framework.clone());
+            }
+            relations.primary_chunks.retain(|id| id != chunk_id);
+            for chunks in relations.integration_patterns.values_mut() {
+                changed |= chunks.contains(chunk_id);
+                chunks.retain(|id| id != chunk_id);
+            }
+            relations.integration_patterns.retain(|_, chunks| !chunks.is_empty());
+            if changed {
+                changed_frameworks.push(framework.clone());
+            }
+        }
+        graph.framework_graph.retain(|_, relations| !relations.primary_chunks.is_empty());
+        graph.cooccurrence.remove(chunk_id);
+
+        let mut removed_patterns = Vec::new();
+        self.framework_patterns.write().retain(|id, pattern| {
+            let keep = &pattern.chunk_id != chunk_id;
+            if !keep {
+                removed_patterns.push(id.clone());
+            }
+            keep
+        });
 
-            if overall_similarity >= self.config.similarity_threshold {
-                let similarity_type = self.determine_primary_similarity_type(
-                    content_similarity,
-                    structural_similarity,
-                    semantic_similarity,
-                    framework_similarity,
-                );
+        let stats = analyzed.then(|| {
+            let mut stats = self.pattern_stats.write();
+            stats.total_patterns = stats.total_patterns.saturating_sub(1);
+            for framework in &primary_frameworks {
+                if let Some(count) = stats.framework_patterns.get_mut(framework) {
+                    *count = count.saturating_sub(1);
+                    if *count == 0 {
+                        stats.framework_patterns.remove(framework);
+                    }
+                }
+            }
+            stats.clone()
+        });
+
+        self.persist(|store| {
+            if let Some(stats) = &stats {
+                store.put_pattern_stats(stats)?;
+            }
+            for id in &removed_patterns {
+                store.remove_framework_pattern(id)?;
+            }
+            for source in &changed_edges {
+                store.put_edges(source, graph.adjacency.get(source))?;
+            }
+            for name in &changed_clusters {
+                store.put_cluster(name, graph.clusters.get(name))?;
+            }
+            for framework in &changed_frameworks {
+                store.put_framework_relations(framework, graph.framework_graph.get(framework))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Get the chunks worth scoring against a chunk, most promising first
+    ///
+    /// Candidates share frameworks, patterns or tags with the chunk, collide
+    /// with it in a MinHash/LSH band, or appear among its embedding
+    /// `neighbours`. At most `candidate_budget` IDs are returned.
+    pub fn similarity_candidates(&self, chunk: &LearningChunk, neighbours: &[(ChunkId, f32)]) -> Vec<ChunkId> {
+        let candidates = self.candidates.read();
+        let signature = match candidates.signature(&chunk.id) {
+            Some(signature) => signature.clone(),
+            None => ChunkSignature::from_chunk(chunk, &[]),
+        };
+
+        candidates.candidates(&chunk.id, &signature, neighbours, self.config.candidate_budget)
+    }
+
+    /// Score candidate chunks against a chunk and keep the most similar ones
+    ///
+    /// Candidates usually come from [`similarity_candidates`](Self::similarity_candidates).
+    pub async fn find_similar(
+        &self,
+        chunk: &LearningChunk,
+        candidates: Vec<LearningChunk>,
+        limit: usize,
+    ) -> Result<Vec<SimilarityMatch>> {
+        tracing::debug!("Finding similar chunks for {} among {} candidates", chunk.id, candidates.len());
+
+        let mut similarities = Vec::new();
+
+        for candidate_chunk in candidates {
+            if candidate_chunk.id == chunk.id {
+                continue; // Skip self
+            }
 
+            if let Some((score, similarity_type)) = self.score_similarity(chunk, &candidate_chunk)? {
                 similarities.push(SimilarityMatch {
                     chunk: candidate_chunk,
-                    score: overall_similarity,
+                    score,
                     similarity_type,
                 });
             }
@@ -266,6 +815,7 @@ impl PatternAnalyzer {
         {
             let mut stats = self.pattern_stats.write();
             stats.similarity_calculations += 1;
+            self.persist(|store| store.put_pattern_stats(&stats))?;
         }
 
         tracing::debug!("Found {} similar chunks for: {}", similarities.len(), chunk.id);
@@ -273,6 +823,9 @@ impl PatternAnalyzer {
     }
 
     /// Update pattern accuracy based on user feedback
+    ///
+    /// Only the aggregate statistics live here; the effect of feedback on the
+    /// chunk itself is tracked by `FeedbackTracker`.
     pub async fn update_from_feedback(&self, chunk_id: &ChunkId, feedback: UserFeedback) -> Result<()> {
         tracing::debug!("Processing feedback for chunk: {}", chunk_id);
 
@@ -291,66 +844,221 @@ impl PatternAnalyzer {
 
             // Recalculate pattern accuracy
             stats.pattern_accuracy = stats.helpful_count as f64 / stats.total_feedback.max(1) as f64;
-        }
 
-        // Adjust pattern weights based on feedback
-        self.adjust_pattern_weights(chunk_id, &feedback).await?;
+            self.persist(|store| store.put_feedback_stats(&stats))?;
+        }
 
         tracing::debug!("Feedback processed for chunk: {}", chunk_id);
         Ok(())
     }
 
-    /// Rebuild relationship graph for optimization
-    pub async fn rebuild_relationships(&self) -> Result<()> {
+    /// Rebuild the relationship graph from every stored chunk
+    ///
+    /// Streams `chunks` in batches, scoring each chunk against its similarity
+    /// candidates (fetched through `load`) in parallel and keeping at most
+    /// `max_relationships` edges above `similarity_threshold`. Clusters and
+    /// framework combinations are recomputed along the way. The new graph is
+    /// built aside and only replaces the current one once complete, so
+    /// readers never see a partial graph and a failed rebuild changes nothing.
+    /// Chunks analyzed while it runs are lost with the replaced graph, so
+    /// callers must keep chunks from being added or removed until it returns.
+    ///
+    /// This does CPU-heavy work synchronously; call it from a blocking thread.
+    pub fn rebuild_relationships<I, L, P>(&self, chunks: I, load: L, progress: P) -> Result<RelationshipRebuildReport>
+    where
+        I: IntoIterator<Item = Result<LearningChunk>>,
+        L: Fn(&[ChunkId]) -> Result<Vec<LearningChunk>> + Sync,
+        P: Fn(RelationshipProgress),
+    {
         tracing::info!("Rebuilding relationship graph");
+        let started = std::time::Instant::now();
+
+        let mut shadow = RelationshipGraph::default();
+        let mut status = RelationshipProgress {
+            processed: 0,
+            total: self.candidates.read().len(),
+            edges: 0,
+        };
+
+        let mut chunks = chunks.into_iter();
+        loop {
+            let batch = chunks.by_ref()
+                .take(REBUILD_BATCH_SIZE)
+                .collect::<Result<Vec<_>>>()?;
+            if batch.is_empty() {
+                break;
+            }
+
+            let edges = batch.par_iter()
+                .map(|chunk| self.similarity_edges(chunk, &load))
+                .collect::<Result<Vec<_>>>()?;
+
+            for (chunk, similar) in batch.iter().zip(edges) {
+                shadow.add_frameworks(chunk);
+                status.edges += similar.len();
+                let edges = relation_edges(chunk, Some(similar));
+                if !edges.is_empty() {
+                    shadow.adjacency.insert(chunk.id.clone(), edges);
+                }
+            }
+
+            status.processed += batch.len();
+            status.total = status.total.max(status.processed);
+            progress(status);
+        }
+
+        shadow.clusters = shadow.similarity_clusters();
+
+        let report = RelationshipRebuildReport {
+            chunks: status.processed,
+            edges: status.edges,
+            clusters: shadow.clusters.len(),
+            combinations: shadow.combination_count(),
+            chunks_updated: 0,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+
+        // Persist first so the stored graph never lags behind the one in memory
+        self.persist(|store| {
+            store.clear_graph()?;
+            for (chunk_id, edges) in &shadow.adjacency {
+                store.put_edges(chunk_id, Some(edges))?;
+            }
+            for (name, members) in &shadow.clusters {
+                store.put_cluster(name, Some(members))?;
+            }
+            for (framework, relations) in &shadow.framework_graph {
+                store.put_framework_relations(framework, Some(relations))?;
+            }
+            Ok(())
+        })?;
+
+        *self.relationship_graph.write() = shadow;
 
-        // Clear existing relationships
         {
-            let mut graph = self.relationship_graph.write();
-            graph.adjacency.clear();
-            graph.clusters.clear();
-            graph.framework_graph.clear();
+            let mut stats = self.pattern_stats.write();
+            stats.relationship_discoveries += report.edges as u64;
+            self.persist(|store| store.put_pattern_stats(&stats))?;
         }
 
-        // This would typically iterate through all chunks and rebuild
-        // For now, we'll just log the operation
-        tracing::info!("Relationship graph rebuild completed");
-        Ok(())
+        tracing::info!(
+            "Relationship graph rebuilt: {} chunks, {} edges, {} clusters in {}ms",
+            report.chunks,
+            report.edges,
+            report.clusters,
+            report.duration_ms
+        );
+        Ok(report)
+    }
+
+    /// Get the framework combinations that include all of `frameworks`
+    ///
+    /// Combinations are mined from every chunk's framework set, counted once
+    /// per chunk and scored by support, confidence and lift. Only those used
+    /// by at least `min_support` of the framework-tagged chunks are returned,
+    /// strongest association first. Pass no frameworks to list them all.
+    pub fn get_framework_combinations<S: AsRef<str>>(&self, frameworks: &[S], min_support: f32) -> Vec<FrameworkCombination> {
+        self.relationship_graph.read().cooccurrence.combinations(frameworks, min_support)
     }
 
     /// Get Next.js + Laravel specific patterns
     pub async fn get_nextjs_laravel_patterns(&self) -> Vec<FrameworkCombination> {
-        let graph = self.relationship_graph.read();
-        
-        graph.framework_graph
-            .values()
-            .flat_map(|relations| &relations.common_combinations)
-            .filter(|combo| {
-                combo.frameworks.contains(&"nextjs".to_string()) &&
-                combo.frameworks.contains(&"laravel".to_string())
-            })
-            .cloned()
-            .collect()
+        self.get_framework_combinations(&["nextjs", "laravel"], 0.0)
     }
 
     /// Extract code patterns from a chunk
-    async fn extract_code_patterns(&self, chunk: &LearningChunk) -> Result<()> {
+    ///
+    /// Returns the signatures of the detected patterns.
+    async fn extract_code_patterns(&self, chunk: &LearningChunk) -> Result<Vec<String>> {
+        let mut signatures = Vec::new();
         if let ChunkContent::Code { code, language, framework } = &chunk.content {
-            let patterns = self.analyze_code_content(code, language, framework).await?;
-            
+            let detected = self.analyze_code_content(code, language, framework, &chunk.metadata.frameworks).await?;
+
             let mut code_patterns = self.code_patterns.write();
-            for pattern in patterns {
-                code_patterns.insert(pattern.id.clone(), pattern);
+            let mut chunk_patterns = self.chunk_patterns.write();
+            // A re-analyzed chunk replaces what it contributed before
+            self.forget_code_patterns(&chunk.id, &mut code_patterns, &mut chunk_patterns)?;
+
+            let mut counted = ChunkPatterns {
+                day: chunk.metadata.created_at.date_naive(),
+                detections: Vec::new(),
+            };
+            for detection in detected {
+                if counted.detections.iter().any(|counted| counted.pattern_id == detection.id) {
+                    continue;
+                }
+                signatures.push(detection.signature.clone());
+
+                let pattern = code_patterns.entry(detection.id.clone()).or_insert_with(|| detection.clone());
+                pattern.name = detection.name.clone();
+                pattern.confidence = detection.confidence;
+                pattern.add_chunk(&chunk.id, &detection.frameworks, counted.day, self.config.max_pattern_examples);
+                self.persist(|store| store.put_code_pattern(pattern))?;
+
+                counted.detections.push(PatternDetection {
+                    pattern_id: detection.id,
+                    frameworks: detection.frameworks,
+                });
+            }
+
+            if !counted.detections.is_empty() {
+                self.persist(|store| store.put_chunk_patterns(&chunk.id, Some(&counted)))?;
+                chunk_patterns.insert(chunk.id.clone(), counted);
+            }
+        }
+        Ok(signatures)
+    }
+
+    /// Take a chunk's contribution out of the code patterns it was counted for
+    ///
+    /// Patterns no chunk exhibits any more are dropped.
+    fn forget_code_patterns(
+        &self,
+        chunk_id: &ChunkId,
+        code_patterns: &mut HashMap<String, CodePattern>,
+        chunk_patterns: &mut HashMap<ChunkId, ChunkPatterns>,
+    ) -> Result<()> {
+        let Some(counted) = chunk_patterns.remove(chunk_id) else {
+            return Ok(());
+        };
+        self.persist(|store| store.put_chunk_patterns(chunk_id, None))?;
+
+        for detection in &counted.detections {
+            let Some(pattern) = code_patterns.get_mut(&detection.pattern_id) else {
+                continue;
+            };
+            pattern.remove_chunk(chunk_id, &detection.frameworks, counted.day);
+            if pattern.usage_count == 0 {
+                code_patterns.remove(&detection.pattern_id);
+                self.persist(|store| store.remove_code_pattern(&detection.pattern_id))?;
+            } else {
+                self.persist(|store| store.put_code_pattern(pattern))?;
             }
         }
         Ok(())
     }
 
+    /// A code pattern by its ID
+    pub fn code_pattern(&self, id: &str) -> Option<CodePattern> {
+        self.code_patterns.read().get(id).cloned()
+    }
+
+    /// Usage and trend of every code pattern as of `now`, most used first
+    pub fn pattern_statistics(&self, now: chrono::DateTime<chrono::Utc>) -> Vec<PatternStatistics> {
+        let analyzed = self.candidates.read().len();
+        let mut statistics: Vec<PatternStatistics> = self.code_patterns.read()
+            .values()
+            .map(|pattern| pattern.statistics(analyzed, self.config.trend_window_days, now))
+            .collect();
+        statistics.sort_by(|a, b| b.usage_count.cmp(&a.usage_count).then_with(|| a.id.cmp(&b.id)));
+        statistics
+    }
+
     /// Analyze framework-specific patterns
     async fn analyze_framework_patterns(&self, chunk: &LearningChunk) -> Result<()> {
         for framework in &chunk.metadata.frameworks {
-            let pattern = self.extract_framework_pattern(chunk, framework).await?;
-            if let Some(pattern) = pattern {
+            for pattern in self.extract_framework_patterns(chunk, framework).await? {
+                self.persist(|store| store.put_framework_pattern(&pattern))?;
                 let mut framework_patterns = self.framework_patterns.write();
                 framework_patterns.insert(pattern.id.clone(), pattern);
             }
@@ -361,188 +1069,148 @@ impl PatternAnalyzer {
     /// Update the relationship graph with new chunk
     async fn update_relationship_graph(&self, chunk: &LearningChunk) -> Result<()> {
         let mut graph = self.relationship_graph.write();
-        
-        // Add chunk to framework graph
-        for framework in &chunk.metadata.frameworks {
-            let framework_relations = graph.framework_graph
-                .entry(framework.clone())
-                .or_insert_with(|| FrameworkRelations {
-                    primary_chunks: Vec::new(),
-                    integration_patterns: HashMap::new(),
-                    common_combinations: Vec::new(),
-                });
-            
-            framework_relations.primary_chunks.push(chunk.id.clone());
+        graph.add_frameworks(chunk);
+
+        let edges = relation_edges(chunk, None);
+        if !edges.is_empty() {
+            self.persist(|store| store.put_edges(&chunk.id, Some(&edges)))?;
+            graph.adjacency.insert(chunk.id.clone(), edges);
         }
 
-        // Detect framework combinations
-        if chunk.metadata.frameworks.len() > 1 {
-            let combination = FrameworkCombination {
-                frameworks: chunk.metadata.frameworks.clone(),
-                pattern_name: format!("{} integration", chunk.metadata.frameworks.join("-")),
-                chunks: vec![chunk.id.clone()],
-                confidence: 0.8,
-                usage_frequency: 1,
+        // Inverse mirrors record the edges other chunks point at this one,
+        // which `remove_chunk` dropped when the chunk was re-analyzed
+        for relation in chunk.relationships.iter().filter(|relation| relation.inverse) {
+            let Some(relation_type) = relation.relation_type.inverse() else {
+                continue;
             };
+            let incoming = graph.adjacency.entry(relation.target.clone()).or_default();
+            if incoming.iter().any(|edge| edge.target == chunk.id && edge.relation_type == relation_type) {
+                continue;
+            }
+            incoming.push(WeightedEdge {
+                target: chunk.id.clone(),
+                relation_type,
+                weight: relation.strength,
+                confidence: relation.confidence,
+            });
+            let incoming = incoming.clone();
+            self.persist(|store| store.put_edges(&relation.target, Some(&incoming)))?;
+        }
 
-            // Add to appropriate framework relations
+        self.persist(|store| {
             for framework in &chunk.metadata.frameworks {
-                if let Some(relations) = graph.framework_graph.get_mut(framework) {
-                    relations.common_combinations.push(combination.clone());
-                }
+                store.put_framework_relations(framework, graph.framework_graph.get(framework))?;
             }
-        }
+            Ok(())
+        })
+    }
 
-        Ok(())
+    /// Analyze code content for patterns using the active rules
+    async fn analyze_code_content(&self, code: &str, language: &str, framework: &Option<String>, frameworks: &[String]) -> Result<Vec<CodePattern>> {
+        Ok(self.rules().code_patterns(code, language, framework.as_ref(), frameworks))
     }
 
-    /// Analyze code content for patterns (simplified implementation)
-    async fn analyze_code_content(&self, code: &str, language: &str, framework: &Option<String>) -> Result<Vec<CodePattern>> {
-        let mut patterns = Vec::new();
-
-        // Simple pattern detection based on keywords and structure
-        if code.contains("export") && code.contains("function") {
-            patterns.push(CodePattern {
-                id: format!("export-function-{}", uuid::Uuid::new_v4()),
-                name: "Export Function".to_string(),
-                pattern_type: PatternType::Function,
-                signature: "export function".to_string(),
-                frameworks: framework.as_ref().map(|f| vec![f.clone()]).unwrap_or_default(),
-                confidence: 0.9,
-                usage_count: 1,
-                examples: vec![code.to_string()],
-            });
-        }
+    /// Extract framework-specific patterns using the active rules
+    async fn extract_framework_patterns(&self, chunk: &LearningChunk, framework: &str) -> Result<Vec<FrameworkPattern>> {
+        Ok(self.rules().framework_patterns(chunk, framework))
+    }
 
-        if code.contains("const") && code.contains("=") && code.contains("=>") {
-            patterns.push(CodePattern {
-                id: format!("arrow-function-{}", uuid::Uuid::new_v4()),
-                name: "Arrow Function".to_string(),
-                pattern_type: PatternType::Function,
-                signature: "const name = () =>".to_string(),
-                frameworks: framework.as_ref().map(|f| vec![f.clone()]).unwrap_or_default(),
-                confidence: 0.8,
-                usage_count: 1,
-                examples: vec![code.to_string()],
-            });
-        }
+    /// Rules currently used for pattern detection
+    pub fn rules(&self) -> Arc<RuleSet> {
+        self.rules.read().clone()
+    }
 
-        if code.contains("export default") && (code.contains("function") || code.contains("class")) {
-            patterns.push(CodePattern {
-                id: format!("default-export-{}", uuid::Uuid::new_v4()),
-                name: "Default Export".to_string(),
-                pattern_type: PatternType::Module,
-                signature: "export default".to_string(),
-                frameworks: framework.as_ref().map(|f| vec![f.clone()]).unwrap_or_default(),
-                confidence: 0.95,
-                usage_count: 1,
-                examples: vec![code.to_string()],
-            });
+    /// Reload the configured rule directory
+    ///
+    /// The new rules apply to chunks analyzed from now on. If any pack fails
+    /// to load or validate, the active rules are kept and the error returned.
+    /// Without a rule directory this keeps the built-in rules.
+    pub fn reload_rules(&self) -> Result<Arc<RuleSet>> {
+        let Some(directory) = &self.rule_directory else {
+            return Ok(self.rules());
+        };
+
+        let rules = Arc::new(directory.load()?);
+        tracing::info!(
+            "Loaded {} pattern rules from {} packs in {:?}",
+            rules.len(),
+            rules.packs().len(),
+            directory.path()
+        );
+        *self.rules.write() = rules.clone();
+        Ok(rules)
+    }
+
+    /// Reload the rule directory if its packs changed since the last load
+    fn reload_rules_if_changed(&self) {
+        let Some(directory) = &self.rule_directory else {
+            return;
+        };
+        if directory.changed() {
+            if let Err(e) = self.reload_rules() {
+                tracing::warn!("Keeping current pattern rules, reload from {:?} failed: {:#}", directory.path(), e);
+            }
         }
+    }
 
-        // Next.js specific patterns
-        if let Some(fw) = framework {
-            if fw == "nextjs" {
-                if code.contains("getServerSideProps") {
-                    patterns.push(CodePattern {
-                        id: format!("nextjs-ssr-{}", uuid::Uuid::new_v4()),
-                        name: "Next.js Server-Side Rendering".to_string(),
-                        pattern_type: PatternType::Function,
-                        signature: "getServerSideProps".to_string(),
-                        frameworks: vec!["nextjs".to_string()],
-                        confidence: 1.0,
-                        usage_count: 1,
-                        examples: vec![code.to_string()],
-                    });
-                }
+    /// Score a candidate against a chunk
+    ///
+    /// Returns the weighted overall similarity and its dominant kind, or
+    /// `None` if the candidate falls below the similarity threshold.
+    fn score_similarity(&self, chunk: &LearningChunk, candidate: &LearningChunk) -> Result<Option<(f32, SimilarityType)>> {
+        let components = self.similarity_components(chunk, candidate)?;
 
-                if code.contains("getStaticProps") {
-                    patterns.push(CodePattern {
-                        id: format!("nextjs-ssg-{}", uuid::Uuid::new_v4()),
-                        name: "Next.js Static Site Generation".to_string(),
-                        pattern_type: PatternType::Function,
-                        signature: "getStaticProps".to_string(),
-                        frameworks: vec!["nextjs".to_string()],
-                        confidence: 1.0,
-                        usage_count: 1,
-                        examples: vec![code.to_string()],
-                    });
-                }
+        // Combine similarities with the active weights
+        let overall_similarity = self.similarity_weights().combine(&components);
 
-                if code.contains("NextApiRequest") && code.contains("NextApiResponse") {
-                    patterns.push(CodePattern {
-                        id: format!("nextjs-api-{}", uuid::Uuid::new_v4()),
-                        name: "Next.js API Route".to_string(),
-                        pattern_type: PatternType::ApiEndpoint,
-                        signature: "NextApiRequest, NextApiResponse".to_string(),
-                        frameworks: vec!["nextjs".to_string()],
-                        confidence: 1.0,
-                        usage_count: 1,
-                        examples: vec![code.to_string()],
-                    });
-                }
-            }
+        if overall_similarity < self.config.similarity_threshold {
+            return Ok(None);
         }
 
-        Ok(patterns)
-    }
-
-    /// Extract framework-specific pattern
-    async fn extract_framework_pattern(&self, chunk: &LearningChunk, framework: &str) -> Result<Option<FrameworkPattern>> {
-        if let ChunkContent::Code { code, .. } = &chunk.content {
-            // Framework-specific pattern extraction
-            match framework {
-                "nextjs" => {
-                    if code.contains("pages/") || code.contains("app/") {
-                        return Ok(Some(FrameworkPattern {
-                            id: format!("nextjs-page-{}", chunk.id),
-                            framework: "nextjs".to_string(),
-                            pattern_name: "Page Component".to_string(),
-                            template: code.clone(),
-                            dependencies: vec!["react".to_string(), "next".to_string()],
-                            best_practices: vec![
-                                "Use getServerSideProps for dynamic data".to_string(),
-                                "Implement proper SEO metadata".to_string(),
-                            ],
-                            common_issues: vec![
-                                "Hydration mismatch errors".to_string(),
-                                "Missing key props in lists".to_string(),
-                            ],
-                            related_patterns: vec!["react-component".to_string()],
-                        }));
-                    }
-                }
-                "laravel" => {
-                    if code.contains("class") && code.contains("Controller") {
-                        return Ok(Some(FrameworkPattern {
-                            id: format!("laravel-controller-{}", chunk.id),
-                            framework: "laravel".to_string(),
-                            pattern_name: "Controller".to_string(),
-                            template: code.clone(),
-                            dependencies: vec!["laravel".to_string()],
-                            best_practices: vec![
-                                "Use resource controllers".to_string(),
-                                "Validate request data".to_string(),
-                            ],
-                            common_issues: vec![
-                                "Missing CSRF protection".to_string(),
-                                "Unvalidated input".to_string(),
-                            ],
-                            related_patterns: vec!["laravel-model".to_string()],
-                        }));
-                    }
-                }
-                _ => {}
+        let similarity_type = self.determine_primary_similarity_type(
+            components.content,
+            components.structural,
+            components.semantic,
+            components.framework,
+        );
+        Ok(Some((overall_similarity, similarity_type)))
+    }
+
+    /// Content, structural, semantic and framework similarity of two chunks
+    pub fn similarity_components(&self, chunk: &LearningChunk, candidate: &LearningChunk) -> Result<SimilarityComponents> {
+        Ok(SimilarityComponents {
+            content: self.calculate_content_similarity(chunk, candidate)?,
+            structural: self.calculate_structural_similarity(chunk, candidate)?,
+            semantic: self.calculate_semantic_similarity(chunk, candidate)?,
+            framework: self.calculate_framework_similarity(chunk, candidate)?,
+        })
+    }
+
+    /// Compute the strongest similarity edges of a chunk
+    fn similarity_edges<L>(&self, chunk: &LearningChunk, load: &L) -> Result<Vec<WeightedEdge>>
+    where
+        L: Fn(&[ChunkId]) -> Result<Vec<LearningChunk>>,
+    {
+        let candidates = load(&self.similarity_candidates(chunk, &[]))?;
+
+        let mut edges = Vec::new();
+        for candidate in &candidates {
+            if candidate.id == chunk.id {
+                continue;
+            }
+            if let Some((score, _)) = self.score_similarity(chunk, candidate)? {
+                edges.push(WeightedEdge {
+                    target: candidate.id.clone(),
+                    relation_type: RelationType::Similar,
+                    weight: score,
+                    confidence: score.min(1.0),
+                });
             }
         }
-        Ok(None)
-    }
 
-    /// Get candidate chunks for similarity comparison
-    async fn get_similarity_candidates(&self, chunk: &LearningChunk) -> Result<Vec<LearningChunk>> {
-        // This is a simplified implementation
-        // In practice, you'd query the storage for chunks with similar frameworks/patterns
-        Ok(vec![])
+        edges.sort_by(|a, b| b.weight.total_cmp(&a.weight).then_with(|| a.target.as_str().cmp(b.target.as_str())));
+        edges.truncate(self.config.max_relationships);
+        Ok(edges)
     }
 
     /// Calculate content-based similarity
@@ -634,26 +1302,6 @@ impl PatternAnalyzer {
             SimilarityType::Content
         }
     }
-
-    /// Adjust pattern weights based on feedback
-    async fn adjust_pattern_weights(&self, chunk_id: &ChunkId, feedback: &UserFeedback) -> Result<()> {
-        let learning_rate = self.config.learning_rate;
-        
-        // Adjust weights based on feedback type
-        let weight_adjustment = match feedback.feedback_type {
-            FeedbackType::Helpful => learning_rate,
-            FeedbackType::NotHelpful => -learning_rate,
-            FeedbackType::NeedsImprovement => -learning_rate * 0.5,
-            FeedbackType::HasErrors => -learning_rate * 2.0,
-            _ => 0.0,
-        };
-
-        // Apply adjustments to related patterns
-        // This is a simplified implementation
-        tracing::debug!("Adjusted pattern weights for chunk {} by {}", chunk_id, weight_adjustment);
-        
-        Ok(())
-    }
 }
 
 #[cfg(test)]
@@ -752,6 +1400,20 @@ mod tests {
         assert_eq!(stats.helpful_count, 1);
     }
 
+    #[tokio::test]
+    async fn test_remove_chunk_from_graph() {
+        let analyzer = PatternAnalyzer::new(384);
+        let mut chunk = create_test_chunk("fullstack", "export default function Page() {}", "nextjs");
+        chunk.metadata.frameworks.push("laravel".to_string());
+
+        analyzer.analyze_chunk(&chunk).await.unwrap();
+        assert!(!analyzer.get_nextjs_laravel_patterns().await.is_empty());
+
+        analyzer.remove_chunk(&chunk.id).await.unwrap();
+        assert!(analyzer.get_nextjs_laravel_patterns().await.is_empty());
+        assert!(analyzer.relationship_graph.read().framework_graph.is_empty());
+    }
+
     #[tokio::test]
     async fn test_nextjs_pattern_detection() {
         let analyzer = PatternAnalyzer::new(384);
@@ -767,4 +1429,211 @@ mod tests {
         let ssr_pattern = patterns.values().find(|p| p.name == "Next.js Server-Side Rendering");
         assert!(ssr_pattern.is_some());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_code_patterns_are_aggregated() {
+        let analyzer = PatternAnalyzer::with_config(PatternConfig {
+            max_pattern_examples: 2,
+            ..Default::default()
+        });
+        for id in ["a", "b", "c"] {
+            let chunk = create_test_chunk(id, "export function handler() {}", "nextjs");
+            analyzer.analyze_chunk(&chunk).await.unwrap();
+        }
+        // Re-analyzing a chunk does not count it twice
+        let mut updated = create_test_chunk("c", "export function handler() {}", "react");
+        analyzer.analyze_chunk(&updated).await.unwrap();
+
+        assert_eq!(analyzer.code_patterns.read().len(), 1);
+        let pattern = analyzer.code_pattern("function-export-function").unwrap();
+        assert_eq!(pattern.usage_count, 3);
+        assert_eq!(pattern.frameworks, vec!["nextjs".to_string(), "react".to_string()]);
+        assert_eq!(pattern.framework_counts["nextjs"], 2);
+        let examples: Vec<&str> = pattern.examples.iter().map(ChunkId::as_str).collect();
+        assert_eq!(examples, ["c", "b"]);
+
+        let statistics = analyzer.pattern_statistics(chrono::Utc::now());
+        assert_eq!(statistics.len(), 1);
+        assert!((statistics[0].frequency - 1.0).abs() < 1e-6);
+        assert_eq!(statistics[0].recent, 3);
+
+        // Chunks whose code no longer matches take their count back out
+        updated.content = ChunkContent::Code {
+            language: "typescript".to_string(),
+            code: "let x = 1;".to_string(),
+            framework: Some("react".to_string()),
+        };
+        analyzer.analyze_chunk(&updated).await.unwrap();
+        analyzer.remove_chunk(&ChunkId::new("a")).await.unwrap();
+        let pattern = analyzer.code_pattern("function-export-function").unwrap();
+        assert_eq!(pattern.usage_count, 1);
+        assert_eq!(pattern.frameworks, vec!["nextjs".to_string()]);
+
+        analyzer.remove_chunk(&ChunkId::new("b")).await.unwrap();
+        assert!(analyzer.code_patterns.read().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rule_packs_hot_reload() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let analyzer = PatternAnalyzer::with_config(PatternConfig {
+            rules_dir: Some(temp_dir.path().to_path_buf()),
+            rules_reload_interval_ms: 0,
+            ..Default::default()
+        });
+        let chunk = create_test_chunk("controller", "class PostsController < ApplicationController\nend", "rails");
+        let detected = |analyzer: &PatternAnalyzer| {
+            analyzer.code_patterns.read().values().any(|pattern| pattern.name == "Rails Controller")
+        };
+
+        analyzer.analyze_chunk(&chunk).await.unwrap();
+        assert!(!detected(&analyzer));
+
+        let pack = "name: rails\nrules:\n  - id: rails-controller\n    name: Rails Controller\n    pattern_type: api_endpoint\n    frameworks: [rails]\n    when:\n      all: [{ regex: \"class \\\\w+Controller < \" }]\n";
+        std::fs::write(temp_dir.path().join("rails.yaml"), pack).unwrap();
+        analyzer.analyze_chunk(&chunk).await.unwrap();
+        assert!(detected(&analyzer));
+
+        // A broken pack keeps the rules that were active
+        std::fs::write(temp_dir.path().join("broken.yaml"), "name: broken\nrules: [{ id: x }]\n").unwrap();
+        assert!(analyzer.reload_rules().is_err());
+        assert_eq!(analyzer.rules().packs(), ["builtin".to_string(), "rails".to_string()]);
+        analyzer.code_patterns.write().clear();
+        analyzer.analyze_chunk(&chunk).await.unwrap();
+        assert!(detected(&analyzer));
+    }
+
+    fn open_store(path: &std::path::Path) -> (sled::Db, PatternStore) {
+        let db = crate::storage::open_db(path).unwrap();
+        let metadata = db.open_tree("metadata").unwrap();
+        let store = PatternStore::open(&db, metadata).unwrap();
+        (db, store)
+    }
+
+    #[tokio::test]
+    async fn test_state_survives_reopen() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut chunk = create_test_chunk("fullstack", "// app/page.tsx\nexport default function Page() {}", "nextjs");
+        chunk.metadata.frameworks.push("laravel".to_string());
+
+        {
+            let (_db, store) = open_store(temp_dir.path());
+            let analyzer = PatternAnalyzer::with_store(PatternConfig::default(), store).unwrap();
+            assert!(analyzer.needs_rebuild());
+            analyzer.rebuild(std::iter::empty()).await.unwrap();
+
+            analyzer.analyze_chunk(&chunk).await.unwrap();
+            analyzer.update_from_feedback(&chunk.id, UserFeedback {
+                user_id: "user1".to_string(),
+                timestamp: chrono::Utc::now(),
+                feedback_type: FeedbackType::Helpful,
+                comment: None,
+                metadata: HashMap::new(),
+            }).await.unwrap();
+        }
+
+        {
+            let (_db, store) = open_store(temp_dir.path());
+            let analyzer = PatternAnalyzer::with_store(PatternConfig::default(), store).unwrap();
+            assert!(!analyzer.needs_rebuild());
+            assert!(!analyzer.code_patterns.read().is_empty());
+            assert_eq!(analyzer.pattern_stats.read().total_patterns, 1);
+            assert_eq!(analyzer.feedback_stats.read().helpful_count, 1);
+            assert!(!analyzer.get_nextjs_laravel_patterns().await.is_empty());
+            assert!(!analyzer.framework_patterns.read().is_empty());
+
+            // Removals are persisted too
+            analyzer.remove_chunk(&chunk.id).await.unwrap();
+        }
+
+        let (_db, store) = open_store(temp_dir.path());
+        let analyzer = PatternAnalyzer::with_store(PatternConfig::default(), store).unwrap();
+        assert!(analyzer.relationship_graph.read().framework_graph.is_empty());
+        assert!(analyzer.framework_patterns.read().is_empty());
+        assert_eq!(analyzer.pattern_stats.read().total_patterns, 0);
+        assert!(analyzer.pattern_stats.read().framework_patterns.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_outdated_state_is_rebuilt() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let chunk = create_test_chunk("ssr", "export async function getServerSideProps() {}", "nextjs");
+
+        let (db, store) = open_store(temp_dir.path());
+        store.put_code_pattern(&CodePattern {
+            id: "stale".to_string(),
+            name: "Stale".to_string(),
+            pattern_type: PatternType::Function,
+            signature: String::new(),
+            frameworks: Vec::new(),
+            confidence: 1.0,
+            usage_count: 1,
+            examples: Vec::new(),
+            framework_counts: BTreeMap::new(),
+            daily_counts: BTreeMap::new(),
+        }).unwrap();
+        store.put_feedback_stats(&FeedbackStats { total_feedback: 3, helpful_count: 2, ..Default::default() }).unwrap();
+        db.open_tree("metadata").unwrap()
+            .insert("pattern_state_version", &0u32.to_be_bytes()).unwrap();
+
+        let analyzer = PatternAnalyzer::with_store(PatternConfig::default(), store.clone()).unwrap();
+        assert!(analyzer.needs_rebuild());
+        assert!(analyzer.code_patterns.read().is_empty());
+        assert_eq!(analyzer.feedback_stats.read().helpful_count, 2);
+
+        assert_eq!(analyzer.rebuild(vec![Ok(chunk)]).await.unwrap(), 1);
+        assert!(!analyzer.needs_rebuild());
+        assert!(store.is_current().unwrap());
+
+        let state = store.load().unwrap();
+        assert_eq!(state.feedback_stats.total_feedback, 3);
+        assert!(!state.code_patterns.contains_key("stale"));
+        assert!(state.code_patterns.values().any(|p| p.name == "Next.js Server-Side Rendering"));
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_relationships_builds_bounded_graph() {
+        let analyzer = PatternAnalyzer::with_config(PatternConfig {
+            max_relationships: 1,
+            ..Default::default()
+        });
+        let chunks: Vec<LearningChunk> = ["form-a", "form-b", "form-c"].iter()
+            .map(|id| create_test_chunk(id, "export const Form = () => <form />", "react"))
+            .chain(std::iter::once(create_test_chunk("query", "SELECT * FROM users", "postgres")))
+            .collect();
+        for chunk in &chunks {
+            analyzer.analyze_chunk(chunk).await.unwrap();
+        }
+
+        let by_id: HashMap<ChunkId, LearningChunk> = chunks.iter()
+            .map(|chunk| (chunk.id.clone(), chunk.clone()))
+            .collect();
+        let load = |ids: &[ChunkId]| Ok(ids.iter().filter_map(|id| by_id.get(id).cloned()).collect());
+
+        let mut updates = Vec::new();
+        let progress = std::sync::Mutex::new(&mut updates);
+        let report = analyzer.rebuild_relationships(
+            chunks.iter().cloned().map(Ok),
+            load,
+            |status| progress.lock().unwrap().push(status),
+        ).unwrap();
+
+        assert_eq!(report.chunks, 4);
+        assert_eq!(report.edges, 3);
+        assert_eq!(report.clusters, 1);
+        assert_eq!(updates.last().unwrap().processed, 4);
+
+        let graph = analyzer.relationship_graph.read();
+        assert!(graph.adjacency.values().all(|edges| edges.len() == 1));
+        assert!(!graph.adjacency.contains_key(&ChunkId::new("query")));
+        assert_eq!(graph.clusters["cluster-form-a"].len(), 3);
+        assert_eq!(graph.framework_graph["react"].primary_chunks.len(), 3);
+        drop(graph);
+
+        // A failing rebuild leaves the current graph untouched
+        let failing = chunks.iter().cloned().map(Ok)
+            .chain(std::iter::once(Err(anyhow::anyhow!("storage failure"))));
+        assert!(analyzer.rebuild_relationships(failing, |_: &[ChunkId]| Ok(Vec::new()), |_| {}).is_err());
+        assert_eq!(analyzer.relationship_graph.read().clusters.len(), 1);
+    }
+}
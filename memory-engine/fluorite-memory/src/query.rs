@@ -0,0 +1,403 @@
+//! Structured search queries
+//!
+//! Typed query builder combining free text with exact filters and the facet
+//! counts returned alongside search results.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{ChunkContent, ChunkType, LearningChunk};
+use crate::filter::ChunkFilter;
+
+/// Default number of hits returned by a structured query
+const DEFAULT_QUERY_LIMIT: usize = 20;
+
+/// Facet dimension names reported in [`FacetCounts`]
+pub mod facet {
+    pub const FRAMEWORK: &str = "framework";
+    pub const LANGUAGE: &str = "language";
+    pub const CHUNK_TYPE: &str = "chunk_type";
+    pub const TAG: &str = "tag";
+    pub const SOURCE: &str = "source";
+    pub const SPIKE_COMPLEXITY: &str = "spike_complexity";
+    pub const SPIKE_DEPENDENCY: &str = "spike_dependency";
+
+    /// All dimensions collected for every query
+    pub const ALL: &[&str] = &[
+        FRAMEWORK,
+        LANGUAGE,
+        CHUNK_TYPE,
+        TAG,
+        SOURCE,
+        SPIKE_COMPLEXITY,
+        SPIKE_DEPENDENCY,
+    ];
+}
+
+/// Complexity bucket of a Spike template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpikeComplexity {
+    Low,
+    Medium,
+    High,
+}
+
+impl SpikeComplexity {
+    /// Bucket a complexity score in the 0.0 - 1.0 range
+    pub fn from_score(score: f64) -> Self {
+        if score < 0.33 {
+            Self::Low
+        } else if score < 0.66 {
+            Self::Medium
+        } else {
+            Self::High
+        }
+    }
+
+    /// Bucket the complexity recorded on a Spike template chunk
+    pub fn of_chunk(chunk: &LearningChunk) -> Option<Self> {
+        if chunk.chunk_type != ChunkType::SpikeTemplate {
+            return None;
+        }
+
+        chunk.metadata.properties.get("complexity")
+            .and_then(|v| v.as_f64())
+            .map(Self::from_score)
+    }
+
+    /// Facet value used in the index
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+impl std::str::FromStr for SpikeComplexity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            other => Err(anyhow::anyhow!("Unknown spike complexity: {}", other)),
+        }
+    }
+}
+
+/// Package names a Spike template chunk depends on
+pub fn spike_dependencies(chunk: &LearningChunk) -> Vec<String> {
+    if chunk.chunk_type != ChunkType::SpikeTemplate {
+        return Vec::new();
+    }
+
+    match &chunk.content {
+        ChunkContent::Data { data, .. } => data.get("dependencies")
+            .and_then(|deps| deps.as_array())
+            .map(|deps| {
+                deps.iter()
+                    .filter_map(|dep| dep.get("name").and_then(|n| n.as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Name of a Spike template chunk
+pub fn spike_name(chunk: &LearningChunk) -> Option<String> {
+    if chunk.chunk_type != ChunkType::SpikeTemplate {
+        return None;
+    }
+
+    match &chunk.content {
+        ChunkContent::Data { data, .. } => data.get("name")
+            .and_then(|name| name.as_str())
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// Typed search query with text and exact filters
+///
+/// All filters are conjunctive. Within the filter, frameworks, languages and
+/// chunk types match any listed value while tags must all be present.
+/// Chunks matching any excluded value or excluded text are dropped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchQuery {
+    /// Free-text query in the tantivy query syntax (matches everything when absent)
+    pub text: Option<String>,
+    /// Metadata filter shared with storage scans
+    pub filter: ChunkFilter,
+    /// Accepted content languages (any of)
+    pub languages: Vec<String>,
+    /// Accepted Spike template complexity buckets (any of)
+    pub spike_complexity: Vec<SpikeComplexity>,
+    /// Required Spike template dependencies (all of)
+    pub spike_dependencies: Vec<String>,
+    /// Free-text queries that must not match, in the tantivy query syntax
+    #[serde(default)]
+    pub excluded_text: Vec<String>,
+    /// Rejected content languages
+    #[serde(default)]
+    pub excluded_languages: Vec<String>,
+    /// Rejected Spike template complexity buckets
+    #[serde(default)]
+    pub excluded_spike_complexity: Vec<SpikeComplexity>,
+    /// Rejected Spike template dependencies
+    #[serde(default)]
+    pub excluded_spike_dependencies: Vec<String>,
+    /// Maximum number of hits
+    pub limit: usize,
+    /// Attach a ranking explanation to every hit
+    #[serde(default)]
+    pub explain: bool,
+}
+
+impl Default for SearchQuery {
+    fn default() -> Self {
+        Self {
+            text: None,
+            filter: ChunkFilter::default(),
+            languages: Vec::new(),
+            spike_complexity: Vec::new(),
+            spike_dependencies: Vec::new(),
+            excluded_text: Vec::new(),
+            excluded_languages: Vec::new(),
+            excluded_spike_complexity: Vec::new(),
+            excluded_spike_dependencies: Vec::new(),
+            limit: DEFAULT_QUERY_LIMIT,
+            explain: false,
+        }
+    }
+}
+
+impl SearchQuery {
+    /// Create a query that matches every chunk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the free-text part of the query
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Accept chunks associated with a framework
+    pub fn framework(mut self, framework: impl Into<String>) -> Self {
+        self.filter.frameworks.push(framework.into());
+        self
+    }
+
+    /// Accept chunks written in a language
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.languages.push(language.into());
+        self
+    }
+
+    /// Accept chunks of a type
+    pub fn chunk_type(mut self, chunk_type: ChunkType) -> Self {
+        self.filter.chunk_types.push(chunk_type);
+        self
+    }
+
+    /// Require a tag
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.filter.tags.push(tag.into());
+        self
+    }
+
+    /// Require a chunk source
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.filter.source = Some(source.into());
+        self
+    }
+
+    /// Restrict the quality score to an inclusive range
+    pub fn quality_range(mut self, min: Option<f32>, max: Option<f32>) -> Self {
+        self.filter.min_quality = min;
+        self.filter.max_quality = max;
+        self
+    }
+
+    /// Restrict the creation time to `[after, before)`
+    pub fn created_between(mut self, after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> Self {
+        self.filter.created_after = after;
+        self.filter.created_before = before;
+        self
+    }
+
+    /// Accept Spike templates of a complexity bucket
+    pub fn spike_complexity(mut self, complexity: SpikeComplexity) -> Self {
+        self.spike_complexity.push(complexity);
+        self
+    }
+
+    /// Require a Spike template dependency
+    pub fn spike_dependency(mut self, dependency: impl Into<String>) -> Self {
+        self.spike_dependencies.push(dependency.into());
+        self
+    }
+
+    /// Reject chunks matching a free-text query
+    pub fn exclude_text(mut self, text: impl Into<String>) -> Self {
+        self.excluded_text.push(text.into());
+        self
+    }
+
+    /// Reject chunks associated with a framework
+    pub fn exclude_framework(mut self, framework: impl Into<String>) -> Self {
+        self.filter.excluded_frameworks.push(framework.into());
+        self
+    }
+
+    /// Reject chunks written in a language
+    pub fn exclude_language(mut self, language: impl Into<String>) -> Self {
+        self.excluded_languages.push(language.into());
+        self
+    }
+
+    /// Reject chunks of a type
+    pub fn exclude_chunk_type(mut self, chunk_type: ChunkType) -> Self {
+        self.filter.excluded_chunk_types.push(chunk_type);
+        self
+    }
+
+    /// Reject chunks carrying a tag
+    pub fn exclude_tag(mut self, tag: impl Into<String>) -> Self {
+        self.filter.excluded_tags.push(tag.into());
+        self
+    }
+
+    /// Reject chunks from a source
+    pub fn exclude_source(mut self, source: impl Into<String>) -> Self {
+        self.filter.excluded_sources.push(source.into());
+        self
+    }
+
+    /// Reject Spike templates of a complexity bucket
+    pub fn exclude_spike_complexity(mut self, complexity: SpikeComplexity) -> Self {
+        self.excluded_spike_complexity.push(complexity);
+        self
+    }
+
+    /// Reject Spike templates depending on a package
+    pub fn exclude_spike_dependency(mut self, dependency: impl Into<String>) -> Self {
+        self.excluded_spike_dependencies.push(dependency.into());
+        self
+    }
+
+    /// Set the maximum number of hits
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Request a ranking explanation for every hit
+    pub fn explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Check whether the query has any criteria besides free text
+    pub fn has_filters(&self) -> bool {
+        !self.filter.is_empty()
+            || !self.languages.is_empty()
+            || !self.spike_complexity.is_empty()
+            || !self.spike_dependencies.is_empty()
+            || !self.excluded_text.is_empty()
+            || !self.excluded_languages.is_empty()
+            || !self.excluded_spike_complexity.is_empty()
+            || !self.excluded_spike_dependencies.is_empty()
+    }
+}
+
+/// Facet counts per dimension, keyed by facet value
+pub type FacetCounts = BTreeMap<String, BTreeMap<String, u64>>;
+
+/// Hits of a structured query together with facet counts over all matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResults<H> {
+    /// Top hits, best first
+    pub hits: Vec<H>,
+    /// Total number of matching chunks (not limited)
+    pub total_hits: usize,
+    /// Facet counts over all matching chunks
+    pub facets: FacetCounts,
+}
+
+impl<H> SearchResults<H> {
+    /// Replace the hits while keeping totals and facets
+    pub fn with_hits<T>(self, hits: Vec<T>) -> SearchResults<T> {
+        SearchResults {
+            hits,
+            total_hits: self.total_hits,
+            facets: self.facets,
+        }
+    }
+
+    /// Count for a single facet value
+    pub fn facet_count(&self, dimension: &str, value: &str) -> u64 {
+        self.facets.get(dimension)
+            .and_then(|values| values.get(value))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::ChunkMetadata;
+
+    #[test]
+    fn test_query_builder() {
+        let query = SearchQuery::new()
+            .text("server action")
+            .framework("nextjs")
+            .language("typescript")
+            .chunk_type(ChunkType::Component)
+            .tag("auth")
+            .quality_range(Some(0.8), None)
+            .limit(5);
+
+        assert_eq!(query.text.as_deref(), Some("server action"));
+        assert_eq!(query.filter.frameworks, vec!["nextjs".to_string()]);
+        assert_eq!(query.filter.min_quality, Some(0.8));
+        assert_eq!(query.limit, 5);
+        assert!(query.has_filters());
+        assert!(!SearchQuery::new().text("anything").has_filters());
+    }
+
+    #[test]
+    fn test_spike_facet_extraction() {
+        let mut properties = std::collections::HashMap::new();
+        properties.insert("complexity".to_string(), serde_json::json!(0.7));
+
+        let chunk = LearningChunk {
+            chunk_type: ChunkType::SpikeTemplate,
+            content: ChunkContent::Data {
+                format: "json".to_string(),
+                data: serde_json::json!({
+                    "name": "nextjs-auth",
+                    "dependencies": [{ "name": "next-auth" }, { "name": "zod" }],
+                }),
+            },
+            metadata: ChunkMetadata { properties, ..Default::default() },
+            ..Default::default()
+        };
+
+        assert_eq!(SpikeComplexity::of_chunk(&chunk), Some(SpikeComplexity::High));
+        assert_eq!(spike_dependencies(&chunk), vec!["next-auth".to_string(), "zod".to_string()]);
+        assert_eq!(SpikeComplexity::of_chunk(&LearningChunk::default()), None);
+        assert_eq!("Medium".parse::<SpikeComplexity>().unwrap(), SpikeComplexity::Medium);
+    }
+}
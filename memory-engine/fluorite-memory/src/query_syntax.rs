@@ -0,0 +1,525 @@
+//! Query string syntax for structured search
+//!
+//! Parses power-user queries such as
+//! `framework:nextjs type:component quality>0.8 tag:auth -tag:deprecated "server action"`
+//! into a [`SearchQuery`]. Terms are separated by whitespace and combined
+//! conjunctively:
+//!
+//! - `field:value` filters on `framework` (`fw`), `type`, `tag`, `lang`
+//!   (`language`), `source`, `complexity`, `dep` (`dependency`) and `limit`
+//! - `quality` and `created` accept `>`, `>=`, `<`, `<=`, `:value` and
+//!   `:min..max` ranges (either side may be left open); dates are RFC 3339,
+//!   `YYYY-MM-DD` or relative to now (`12h`, `7d`, `2w`); `quality` compares
+//!   the stored score, whose feedback decay is fixed at the last feedback
+//! - `"quoted phrases"` and bare words form the free-text part
+//! - a leading `-` negates a word, phrase or value filter
+//!
+//! Values containing spaces can be quoted, as in `tag:"server action"`.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::chunk::ChunkType;
+use crate::query::{SearchQuery, SpikeComplexity};
+
+/// Field names accepted by the query syntax, in the order they are listed in errors
+const FIELDS: &[&str] = &[
+    "framework", "type", "tag", "lang", "source", "quality", "created", "complexity", "dep", "limit",
+];
+
+/// Chunk type names accepted by `type:`
+const CHUNK_TYPE_NAMES: &str = "spike_template, pattern, api_integration, component, application, \
+    configuration, best_practice, error_solution, performance, security, testing, documentation";
+
+/// Error raised for malformed query strings
+///
+/// `position` is the 0-based character offset of the offending term or value.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{message} at position {position}")]
+pub struct QueryParseError {
+    /// Character offset into the query string
+    pub position: usize,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+impl QueryParseError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self { position, message: message.into() }
+    }
+}
+
+/// Parse a query string into a structured search query
+pub fn parse_query(input: &str) -> Result<SearchQuery, QueryParseError> {
+    let mut builder = QueryBuilder::default();
+    for term in lex(input)? {
+        builder.apply(term)?;
+    }
+    Ok(builder.finish())
+}
+
+impl FromStr for SearchQuery {
+    type Err = QueryParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_query(s)
+    }
+}
+
+/// Comparison between a field and its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Op {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Eq => ":",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+        }
+    }
+}
+
+/// Field name and operator of a `field:value` term
+#[derive(Debug)]
+struct FieldRef {
+    name: String,
+    op: Op,
+    /// Offset of the operator
+    op_start: usize,
+}
+
+/// Single whitespace-separated term of the query string
+#[derive(Debug)]
+struct RawTerm {
+    /// Offset of the term, including a leading `-`
+    start: usize,
+    negated: bool,
+    field: Option<FieldRef>,
+    value: String,
+    /// Offset of the value, after the field and any opening quote
+    value_start: usize,
+}
+
+/// Split a query string into terms
+fn lex(input: &str) -> Result<Vec<RawTerm>, QueryParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut terms = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let negated = chars[i] == '-';
+        if negated {
+            i += 1;
+            if i == chars.len() || chars[i].is_whitespace() {
+                return Err(QueryParseError::new(start, "Expected a term after `-`"));
+            }
+        }
+
+        let name_end = (i..chars.len())
+            .find(|&j| !(chars[j].is_ascii_alphabetic() || chars[j] == '_'))
+            .unwrap_or(chars.len());
+        let op = (name_end > i).then(|| operator_at(&chars, name_end)).flatten();
+
+        let field = op.map(|(op, len)| {
+            let field = FieldRef { name: chars[i..name_end].iter().collect(), op, op_start: name_end };
+            i = name_end + len;
+            field
+        });
+
+        let value_start;
+        let value: String = if i < chars.len() && chars[i] == '"' {
+            let (phrase, end) = read_phrase(&chars, i)?;
+            value_start = i + 1;
+            i = end;
+            if i < chars.len() && !chars[i].is_whitespace() {
+                return Err(QueryParseError::new(i, "Expected whitespace after closing quote"));
+            }
+            phrase
+        } else {
+            value_start = i;
+            while i < chars.len() && !chars[i].is_whitespace() {
+                i += 1;
+            }
+            chars[value_start..i].iter().collect()
+        };
+
+        if let Some(field) = &field {
+            if value.trim().is_empty() {
+                return Err(QueryParseError::new(value_start, format!("Missing value for `{}`", field.name)));
+            }
+        }
+
+        terms.push(RawTerm { start, negated, field, value, value_start });
+    }
+
+    Ok(terms)
+}
+
+/// Operator following a field name, with its length in characters
+///
+/// A `::` path separator is not treated as an operator, so `std::fs` stays a word.
+fn operator_at(chars: &[char], i: usize) -> Option<(Op, usize)> {
+    let next = chars.get(i + 1).copied();
+    match (chars.get(i).copied()?, next) {
+        (':', Some(':')) => None,
+        (':', _) | ('=', _) => Some((Op::Eq, 1)),
+        ('>', Some('=')) => Some((Op::Ge, 2)),
+        ('<', Some('=')) => Some((Op::Le, 2)),
+        ('>', _) => Some((Op::Gt, 1)),
+        ('<', _) => Some((Op::Lt, 1)),
+        _ => None,
+    }
+}
+
+/// Read a quoted phrase starting at the opening quote, returning it and the offset after the closing quote
+fn read_phrase(chars: &[char], open: usize) -> Result<(String, usize), QueryParseError> {
+    let mut phrase = String::new();
+    let mut i = open + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if matches!(chars.get(i + 1), Some('"') | Some('\\')) => {
+                phrase.push(chars[i + 1]);
+                i += 2;
+            }
+            '"' => return Ok((phrase, i + 1)),
+            c => {
+                phrase.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err(QueryParseError::new(open, "Unterminated quoted phrase"))
+}
+
+/// Accumulates parsed terms into a search query
+#[derive(Default)]
+struct QueryBuilder {
+    query: SearchQuery,
+    text: Vec<String>,
+    source_seen: bool,
+    limit_seen: bool,
+}
+
+impl QueryBuilder {
+    fn apply(&mut self, term: RawTerm) -> Result<(), QueryParseError> {
+        let Some(field) = &term.field else {
+            let text = quote_text(&term.value);
+            if text.is_empty() {
+                return Ok(());
+            }
+            if term.negated {
+                self.query.excluded_text.push(text);
+            } else {
+                self.text.push(text);
+            }
+            return Ok(());
+        };
+
+        let name = field.name.to_lowercase();
+        let value = term.value.trim();
+        let at = term.value_start;
+
+        match name.as_str() {
+            "quality" | "created" | "limit" if term.negated => {
+                return Err(QueryParseError::new(term.start, format!("`{}` cannot be negated", name)));
+            }
+            "framework" | "fw" | "type" | "tag" | "lang" | "language" | "source" | "complexity"
+            | "dep" | "dependency" | "limit" if field.op != Op::Eq => {
+                return Err(QueryParseError::new(
+                    field.op_start,
+                    format!("`{}` does not support `{}`; use `{}:value`", name, field.op.as_str(), name),
+                ));
+            }
+            _ => {}
+        }
+
+        let filter = &mut self.query.filter;
+        match (name.as_str(), term.negated) {
+            ("framework" | "fw", false) => filter.frameworks.push(value.to_string()),
+            ("framework" | "fw", true) => filter.excluded_frameworks.push(value.to_string()),
+            ("type", negated) => {
+                let chunk_type = parse_chunk_type(value).ok_or_else(|| QueryParseError::new(
+                    at,
+                    format!("Unknown chunk type `{}`; expected one of: {}", value, CHUNK_TYPE_NAMES),
+                ))?;
+                if negated {
+                    filter.excluded_chunk_types.push(chunk_type);
+                } else {
+                    filter.chunk_types.push(chunk_type);
+                }
+            }
+            ("tag", false) => filter.tags.push(value.to_string()),
+            ("tag", true) => filter.excluded_tags.push(value.to_string()),
+            ("lang" | "language", false) => self.query.languages.push(value.to_string()),
+            ("lang" | "language", true) => self.query.excluded_languages.push(value.to_string()),
+            ("source", false) => {
+                if std::mem::replace(&mut self.source_seen, true) {
+                    return Err(QueryParseError::new(term.start, "`source` can only be given once"));
+                }
+                filter.source = Some(value.to_string());
+            }
+            ("source", true) => filter.excluded_sources.push(value.to_string()),
+            ("complexity", negated) => {
+                let complexity = value.parse::<SpikeComplexity>().map_err(|_| QueryParseError::new(
+                    at,
+                    format!("Unknown complexity `{}`; expected one of: low, medium, high", value),
+                ))?;
+                if negated {
+                    self.query.excluded_spike_complexity.push(complexity);
+                } else {
+                    self.query.spike_complexity.push(complexity);
+                }
+            }
+            ("dep" | "dependency", false) => self.query.spike_dependencies.push(value.to_string()),
+            ("dep" | "dependency", true) => self.query.excluded_spike_dependencies.push(value.to_string()),
+            ("limit", _) => {
+                if std::mem::replace(&mut self.limit_seen, true) {
+                    return Err(QueryParseError::new(term.start, "`limit` can only be given once"));
+                }
+                self.query.limit = match value.parse::<usize>() {
+                    Ok(limit) if limit > 0 => limit,
+                    _ => return Err(QueryParseError::new(at, format!("Invalid limit `{}`; expected a positive integer", value))),
+                };
+            }
+            ("quality", _) => {
+                let (min, max) = parse_bounds(field.op, value, at, parse_quality)?;
+                // Bounds are stored inclusively, so strict comparisons step to the adjacent float
+                let min = min.map(|(q, inclusive)| if inclusive { q } else { q.next_up() });
+                let max = max.map(|(q, inclusive)| if inclusive { q } else { q.next_down() });
+                filter.min_quality = tighter(filter.min_quality, min, f32::max);
+                filter.max_quality = tighter(filter.max_quality, max, f32::min);
+            }
+            ("created", _) => {
+                let (after, before) = parse_bounds(field.op, value, at, parse_time)?;
+                // `created_after` is inclusive and `created_before` exclusive
+                let after = after.map(|((t, step), inclusive)| if inclusive { t } else { t + step });
+                let before = before.map(|((t, step), inclusive)| if inclusive { t + step } else { t });
+                filter.created_after = tighter(filter.created_after, after, std::cmp::max);
+                filter.created_before = tighter(filter.created_before, before, std::cmp::min);
+            }
+            _ => {
+                return Err(QueryParseError::new(
+                    term.start + usize::from(term.negated),
+                    format!(
+                        "Unknown field `{}`; expected one of: {} (quote the term to search for it as text)",
+                        field.name,
+                        FIELDS.join(", "),
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> SearchQuery {
+        if !self.text.is_empty() {
+            self.query.text = Some(self.text.join(" "));
+        }
+        self.query
+    }
+}
+
+/// Lower and upper bounds of a comparison, each with whether it is inclusive
+type Bounds<T> = (Option<(T, bool)>, Option<(T, bool)>);
+
+/// Turn a comparison or `min..max` range into bounds
+fn parse_bounds<T, F>(op: Op, value: &str, at: usize, parse: F) -> Result<Bounds<T>, QueryParseError>
+where
+    T: Clone,
+    F: Fn(&str, usize) -> Result<T, QueryParseError>,
+{
+    let bounds = match op {
+        Op::Gt => (Some((parse(value, at)?, false)), None),
+        Op::Ge => (Some((parse(value, at)?, true)), None),
+        Op::Lt => (None, Some((parse(value, at)?, false))),
+        Op::Le => (None, Some((parse(value, at)?, true))),
+        Op::Eq => match value.split_once("..") {
+            Some((min, max)) => {
+                let max_at = at + min.chars().count() + 2;
+                let min = (!min.is_empty()).then(|| parse(min, at)).transpose()?;
+                let max = (!max.is_empty()).then(|| parse(max, max_at)).transpose()?;
+                if min.is_none() && max.is_none() {
+                    return Err(QueryParseError::new(at, "Range needs at least one bound"));
+                }
+                (min.map(|v| (v, true)), max.map(|v| (v, true)))
+            }
+            None => {
+                let v = parse(value, at)?;
+                (Some((v.clone(), true)), Some((v, true)))
+            }
+        },
+    };
+    Ok(bounds)
+}
+
+/// Combine an existing bound with a new one, keeping the tighter
+fn tighter<T>(current: Option<T>, new: Option<T>, pick: fn(T, T) -> T) -> Option<T> {
+    match (current, new) {
+        (Some(current), Some(new)) => Some(pick(current, new)),
+        (current, new) => current.or(new),
+    }
+}
+
+fn parse_quality(value: &str, at: usize) -> Result<f32, QueryParseError> {
+    match value.parse::<f32>() {
+        Ok(quality) if (0.0..=1.0).contains(&quality) => Ok(quality),
+        Ok(_) => Err(QueryParseError::new(at, format!("Quality `{}` is outside 0.0 - 1.0", value))),
+        Err(_) => Err(QueryParseError::new(at, format!("Invalid quality `{}`; expected a number", value))),
+    }
+}
+
+/// Parse a point in time together with its granularity
+///
+/// Dates cover a whole day, so `created<=2024-06-01` includes that day.
+fn parse_time(value: &str, at: usize) -> Result<(DateTime<Utc>, Duration), QueryParseError> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok((time.with_timezone(&Utc), Duration::seconds(1)));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        return Ok((midnight.and_utc(), Duration::days(1)));
+    }
+
+    let relative = value.char_indices().last().and_then(|(unit_at, unit)| {
+        let amount = value[..unit_at].parse::<i64>().ok()?;
+        let age = match unit {
+            'h' => Duration::try_hours(amount)?,
+            'd' => Duration::try_days(amount)?,
+            'w' => Duration::try_weeks(amount)?,
+            _ => return None,
+        };
+        Utc::now().checked_sub_signed(age)
+    });
+
+    relative
+        .map(|time| (time, Duration::seconds(1)))
+        .ok_or_else(|| QueryParseError::new(
+            at,
+            format!("Invalid time `{}`; expected RFC 3339, YYYY-MM-DD or an age like 7d", value),
+        ))
+}
+
+/// Resolve a chunk type name, ignoring case, `_` and `-`
+fn parse_chunk_type(value: &str) -> Option<ChunkType> {
+    let key: String = value.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    let chunk_type = match key.as_str() {
+        "spiketemplate" | "spike" => ChunkType::SpikeTemplate,
+        "pattern" => ChunkType::Pattern,
+        "apiintegration" | "api" => ChunkType::ApiIntegration,
+        "component" => ChunkType::Component,
+        "application" | "app" => ChunkType::Application,
+        "configuration" | "config" => ChunkType::Configuration,
+        "bestpractice" => ChunkType::BestPractice,
+        "errorsolution" | "error" => ChunkType::ErrorSolution,
+        "performance" | "perf" => ChunkType::Performance,
+        "security" => ChunkType::Security,
+        "testing" | "test" => ChunkType::Testing,
+        "documentation" | "docs" => ChunkType::Documentation,
+        _ => return None,
+    };
+    Some(chunk_type)
+}
+
+/// Quote a word or phrase for the tantivy query syntax, dropping characters it cannot escape
+fn quote_text(text: &str) -> String {
+    let cleaned: String = text.chars().filter(|c| *c != '"' && *c != '\\').collect();
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() {
+        String::new()
+    } else {
+        format!("\"{}\"", cleaned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_query() {
+        let query = parse_query(
+            r#"framework:nextjs type:component quality>0.8 tag:auth -tag:deprecated "server action""#,
+        ).unwrap();
+
+        assert_eq!(query.filter.frameworks, vec!["nextjs".to_string()]);
+        assert_eq!(query.filter.chunk_types, vec![ChunkType::Component]);
+        assert_eq!(query.filter.tags, vec!["auth".to_string()]);
+        assert_eq!(query.filter.excluded_tags, vec!["deprecated".to_string()]);
+        assert!(query.filter.min_quality.unwrap() > 0.8);
+        assert_eq!(query.filter.max_quality, None);
+        assert_eq!(query.text.as_deref(), Some("\"server action\""));
+    }
+
+    #[test]
+    fn test_parse_negation_facets_and_ranges() {
+        let query: SearchQuery = r#"auth -"legacy api" -fw:laravel lang:typescript complexity:low -dep:jquery quality:0.5..0.9 created:2024-01-01..2024-06-30 limit:5"#
+            .parse()
+            .unwrap();
+
+        assert_eq!(query.text.as_deref(), Some("\"auth\""));
+        assert_eq!(query.excluded_text, vec!["\"legacy api\"".to_string()]);
+        assert_eq!(query.filter.excluded_frameworks, vec!["laravel".to_string()]);
+        assert_eq!(query.languages, vec!["typescript".to_string()]);
+        assert_eq!(query.spike_complexity, vec![SpikeComplexity::Low]);
+        assert_eq!(query.excluded_spike_dependencies, vec!["jquery".to_string()]);
+        assert_eq!(query.filter.min_quality, Some(0.5));
+        assert_eq!(query.filter.max_quality, Some(0.9));
+        assert_eq!(query.filter.created_after.unwrap().to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(query.filter.created_before.unwrap().to_rfc3339(), "2024-07-01T00:00:00+00:00");
+        assert_eq!(query.limit, 5);
+
+        // Path separators are not mistaken for fields
+        let query = parse_query("std::fs type:best-practice").unwrap();
+        assert_eq!(query.text.as_deref(), Some("\"std::fs\""));
+        assert_eq!(query.filter.chunk_types, vec![ChunkType::BestPractice]);
+    }
+
+    #[test]
+    fn test_parse_errors_report_positions() {
+        let err = parse_query(r#"tag:auth "server action"#).unwrap_err();
+        assert_eq!(err.position, 9);
+        assert!(err.message.contains("Unterminated"));
+
+        let err = parse_query("framework:nextjs framwork:vue").unwrap_err();
+        assert_eq!(err.position, 17);
+        assert!(err.to_string().contains("Unknown field `framwork`"));
+
+        let err = parse_query("quality>high").unwrap_err();
+        assert_eq!(err.position, 8);
+
+        let err = parse_query("type:widget").unwrap_err();
+        assert_eq!(err.position, 5);
+
+        let err = parse_query("tag>auth").unwrap_err();
+        assert_eq!(err.position, 3);
+
+        let err = parse_query("auth -quality>0.5").unwrap_err();
+        assert_eq!(err.position, 5);
+
+        let err = parse_query("tag: auth").unwrap_err();
+        assert_eq!(err.position, 4);
+        assert_eq!(err.to_string(), "Missing value for `tag` at position 4");
+    }
+}
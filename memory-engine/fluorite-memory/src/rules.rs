@@ -0,0 +1,684 @@
+//! Declarative pattern detection rules
+//!
+//! Code and framework patterns are detected by rules loaded from YAML or JSON
+//! rule packs instead of being compiled in. A pack names itself and lists its
+//! rules:
+//!
+//! ```yaml
+//! name: nuxt
+//! rules:
+//!   - id: nuxt-async-data
+//!     name: Nuxt Async Data
+//!     pattern_type: function        # or api_endpoint, component, ...; anything else is custom
+//!     frameworks: [nuxt]            # optional scope, canonical framework names
+//!     languages: [typescript, vue]  # optional scope
+//!     when:
+//!       all:  [{ token: useAsyncData }]
+//!       any:  [{ regex: "await\\s+\\$fetch" }, { contains: useFetch }]
+//!       none: [{ contains: "// legacy" }]
+//!     confidence: 0.9
+//!     best_practices: [Key every request]
+//!     common_issues: [Duplicate fetches on navigation]
+//! ```
+//!
+//! Conditions are `contains` (substring), `token` (whole identifier) or
+//! `regex`. A rule matches when every `all` condition, at least one `any`
+//! condition (if there are any) and no `none` condition holds. Rules of
+//! `kind: code` (the default) produce [`CodePattern`]s; `kind: framework`
+//! rules produce a [`FrameworkPattern`] carrying their dependencies, best
+//! practices and common issues.
+//!
+//! The built-in pack is always loaded first. Packs from a rule directory may
+//! replace built-in rules by reusing their id; two packs in the directory
+//! defining the same id is an error.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{bail, Context, Result};
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::alias::{alias_key, AliasTable};
+use crate::chunk::{ChunkContent, LearningChunk};
+use crate::patterns::{CodePattern, FrameworkPattern, PatternType};
+
+/// The built-in rule pack
+pub const BUILTIN_RULES: &str = include_str!("builtin_rules.yaml");
+
+/// Extensions of rule pack files in a rule directory
+pub const RULE_PACK_EXTENSIONS: &[&str] = &["yaml", "yml", "json"];
+
+/// What a rule produces when it matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    /// A [`CodePattern`]
+    #[default]
+    Code,
+    /// A [`FrameworkPattern`] for each in-scope framework of the chunk
+    Framework,
+}
+
+/// One condition on a chunk's code; exactly one field must be set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleCondition {
+    /// Substring the code must contain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contains: Option<String>,
+    /// Identifier the code must contain as a whole token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Regular expression the code must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+}
+
+/// Conditions a rule's code must satisfy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConditions {
+    #[serde(default)]
+    pub all: Vec<RuleCondition>,
+    #[serde(default)]
+    pub any: Vec<RuleCondition>,
+    #[serde(default)]
+    pub none: Vec<RuleCondition>,
+}
+
+/// A pattern detection rule as written in a rule pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub kind: RuleKind,
+    /// Pattern type name such as `function` or `api_endpoint`
+    #[serde(default = "default_pattern_type")]
+    pub pattern_type: String,
+    /// Signature recorded on code patterns; defaults to the rule name
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Languages the rule applies to; empty means any
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Frameworks the rule applies to, by any known alias; empty means any
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+    pub when: RuleConditions,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub best_practices: Vec<String>,
+    #[serde(default)]
+    pub common_issues: Vec<String>,
+    #[serde(default)]
+    pub related_patterns: Vec<String>,
+}
+
+fn default_pattern_type() -> String {
+    "custom".to_string()
+}
+
+fn default_confidence() -> f32 {
+    0.8
+}
+
+/// A named set of rules, as stored in one YAML or JSON file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RulePack {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub rules: Vec<PatternRule>,
+}
+
+impl RulePack {
+    /// Parse a pack from YAML, which also accepts JSON
+    pub fn from_yaml(data: &str) -> Result<Self> {
+        serde_yaml::from_str(data).context("Failed to parse rule pack")
+    }
+
+    /// Read a pack from a `.yaml`, `.yml` or `.json` file
+    pub fn load_file(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rule pack {:?}", path))?;
+
+        let is_json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if is_json {
+            serde_json::from_str(&data)
+                .with_context(|| format!("Failed to parse rule pack {:?}", path))
+        } else {
+            serde_yaml::from_str(&data)
+                .with_context(|| format!("Failed to parse rule pack {:?}", path))
+        }
+    }
+
+    /// Check every rule, returning them compiled
+    pub fn compile(&self) -> Result<Vec<CompiledRule>> {
+        if self.name.trim().is_empty() {
+            bail!("Rule pack has no name");
+        }
+
+        let mut seen = HashMap::new();
+        let mut compiled = Vec::with_capacity(self.rules.len());
+        for (index, rule) in self.rules.iter().enumerate() {
+            if let Some(previous) = seen.insert(rule.id.as_str(), index) {
+                bail!("Rule pack {:?} defines rule {:?} twice (rules {} and {})", self.name, rule.id, previous + 1, index + 1);
+            }
+            let rule = CompiledRule::new(&self.name, rule.clone())
+                .with_context(|| format!("Invalid rule {:?} (rule {}) in pack {:?}", rule.id, index + 1, self.name))?;
+            compiled.push(rule);
+        }
+        Ok(compiled)
+    }
+}
+
+/// Resolve a pattern type name, treating unknown names as custom types
+pub fn parse_pattern_type(name: &str) -> PatternType {
+    let key: String = name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+    match key.as_str() {
+        "function" => PatternType::Function,
+        "component" => PatternType::Component,
+        "module" => PatternType::Module,
+        "configuration" => PatternType::Configuration,
+        "apiendpoint" => PatternType::ApiEndpoint,
+        "databasemodel" => PatternType::DatabaseModel,
+        "authentication" => PatternType::Authentication,
+        "errorhandling" => PatternType::ErrorHandling,
+        "performance" => PatternType::Performance,
+        "testing" => PatternType::Testing,
+        _ => PatternType::Custom(name.trim().to_string()),
+    }
+}
+
+/// A validated rule with its conditions compiled
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    /// Name of the pack the rule came from
+    pub pack: String,
+    pub rule: PatternRule,
+    pattern_type: PatternType,
+    framework_keys: Vec<String>,
+    all: Vec<Regex>,
+    any: Vec<Regex>,
+    none: Vec<Regex>,
+}
+
+impl CompiledRule {
+    /// Validate a rule and compile its conditions
+    pub fn new(pack: &str, rule: PatternRule) -> Result<Self> {
+        if rule.id.trim().is_empty() {
+            bail!("Rule has no id");
+        }
+        if rule.name.trim().is_empty() {
+            bail!("Rule has no name");
+        }
+        if rule.pattern_type.trim().is_empty() {
+            bail!("Rule has an empty pattern_type");
+        }
+        if !(0.0..=1.0).contains(&rule.confidence) {
+            bail!("Confidence {} is outside 0.0..=1.0", rule.confidence);
+        }
+        if rule.when.all.is_empty() && rule.when.any.is_empty() {
+            bail!("Rule has no `all` or `any` conditions and would match everything");
+        }
+
+        let compile = |conditions: &[RuleCondition]| -> Result<Vec<Regex>> {
+            conditions.iter().map(compile_condition).collect()
+        };
+
+        Ok(Self {
+            pack: pack.to_string(),
+            pattern_type: parse_pattern_type(&rule.pattern_type),
+            framework_keys: rule.frameworks.iter().map(|framework| framework_key(framework)).collect(),
+            all: compile(&rule.when.all)?,
+            any: compile(&rule.when.any)?,
+            none: compile(&rule.when.none)?,
+            rule,
+        })
+    }
+
+    /// Rule ID
+    pub fn id(&self) -> &str {
+        &self.rule.id
+    }
+
+    /// Type of the patterns the rule produces
+    pub fn pattern_type(&self) -> &PatternType {
+        &self.pattern_type
+    }
+
+    /// Check whether the rule's language scope admits `language`
+    pub fn applies_to_language(&self, language: &str) -> bool {
+        self.rule.languages.is_empty()
+            || self.rule.languages.iter().any(|scope| scope.eq_ignore_ascii_case(language))
+    }
+
+    /// Check whether the rule's framework scope admits `framework`
+    pub fn applies_to_framework(&self, framework: &str) -> bool {
+        self.rule.frameworks.is_empty() || self.in_framework_scope(framework)
+    }
+
+    fn in_framework_scope(&self, framework: &str) -> bool {
+        let key = framework_key(framework);
+        self.framework_keys.contains(&key)
+    }
+
+    /// Check whether the code satisfies the rule's conditions
+    pub fn matches(&self, code: &str) -> bool {
+        self.all.iter().all(|regex| regex.is_match(code))
+            && (self.any.is_empty() || self.any.iter().any(|regex| regex.is_match(code)))
+            && !self.none.iter().any(|regex| regex.is_match(code))
+    }
+}
+
+/// Key of a framework's canonical name under the built-in aliases
+fn framework_key(name: &str) -> String {
+    static ALIASES: OnceLock<AliasTable> = OnceLock::new();
+    let aliases = ALIASES.get_or_init(AliasTable::builtin);
+    alias_key(aliases.canonicalize(name).unwrap_or(name))
+}
+
+fn compile_condition(condition: &RuleCondition) -> Result<Regex> {
+    let pattern = match (&condition.contains, &condition.token, &condition.regex) {
+        (Some(text), None, None) => regex::escape(text),
+        (None, Some(token), None) => {
+            if token.trim().is_empty() {
+                bail!("Empty token condition");
+            }
+            format!(r"(?:^|[^\w$]){}(?:$|[^\w$])", regex::escape(token.trim()))
+        }
+        (None, None, Some(pattern)) => pattern.clone(),
+        _ => bail!("A condition must set exactly one of `contains`, `token` or `regex`"),
+    };
+    Regex::new(&pattern).with_context(|| format!("Invalid condition pattern {:?}", pattern))
+}
+
+/// The active set of pattern rules
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+    packs: Vec<String>,
+}
+
+impl RuleSet {
+    /// Rules from the built-in pack only
+    pub fn builtin() -> Self {
+        let pack = RulePack::from_yaml(BUILTIN_RULES).expect("built-in rule pack parses");
+        Self::from_packs(vec![pack]).expect("built-in rule pack is valid")
+    }
+
+    /// Combine packs in order
+    ///
+    /// A rule replaces an earlier rule with the same ID from the first pack,
+    /// so the first pack acts as the defaults. Any other repeated ID is an
+    /// error.
+    pub fn from_packs(packs: Vec<RulePack>) -> Result<Self> {
+        let mut set = Self::default();
+        let mut owners: HashMap<String, (usize, String)> = HashMap::new();
+
+        for (pack_index, pack) in packs.iter().enumerate() {
+            for rule in pack.compile()? {
+                match owners.get(rule.id()) {
+                    Some((0, _)) if pack_index > 0 => {
+                        let position = set.rules.iter().position(|existing| existing.id() == rule.id())
+                            .expect("owned rule is in the set");
+                        owners.insert(rule.id().to_string(), (pack_index, pack.name.clone()));
+                        set.rules[position] = rule;
+                    }
+                    Some((_, owner)) => {
+                        bail!("Rule {:?} is defined by both pack {:?} and pack {:?}", rule.id(), owner, pack.name);
+                    }
+                    None => {
+                        owners.insert(rule.id().to_string(), (pack_index, pack.name.clone()));
+                        set.rules.push(rule);
+                    }
+                }
+            }
+            set.packs.push(pack.name.clone());
+        }
+        Ok(set)
+    }
+
+    /// Built-in rules plus every pack in a directory, in file name order
+    pub fn load_dir(dir: &Path) -> Result<Self> {
+        let mut packs = vec![RulePack::from_yaml(BUILTIN_RULES)?];
+        for path in rule_pack_paths(dir)? {
+            packs.push(RulePack::load_file(&path)?);
+        }
+        Self::from_packs(packs).with_context(|| format!("Invalid rule packs in {:?}", dir))
+    }
+
+    /// Number of rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Check whether the set has no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Names of the loaded packs, in load order
+    pub fn packs(&self) -> &[String] {
+        &self.packs
+    }
+
+    /// Every rule, in evaluation order
+    pub fn rules(&self) -> &[CompiledRule] {
+        &self.rules
+    }
+
+    /// Code patterns detected in a chunk's code, not yet counted
+    ///
+    /// `framework` is the framework of the code itself and `frameworks` all
+    /// frameworks of the chunk. Scoped rules record the chunk frameworks they
+    /// matched; unscoped rules record the code's framework.
+    pub fn code_patterns(&self, code: &str, language: &str, framework: Option<&String>, frameworks: &[String]) -> Vec<CodePattern> {
+        let mut chunk_frameworks: Vec<&String> = framework.into_iter().collect();
+        chunk_frameworks.extend(frameworks.iter().filter(|name| Some(*name) != framework));
+
+        self.rules.iter()
+            .filter(|rule| rule.rule.kind == RuleKind::Code && rule.applies_to_language(language))
+            .filter_map(|rule| {
+                let pattern_frameworks: Vec<String> = if rule.rule.frameworks.is_empty() {
+                    framework.cloned().into_iter().collect()
+                } else {
+                    let scoped: Vec<String> = chunk_frameworks.iter()
+                        .filter(|name| rule.in_framework_scope(name))
+                        .map(|name| name.to_string())
+                        .collect();
+                    if scoped.is_empty() {
+                        return None;
+                    }
+                    scoped
+                };
+                if !rule.matches(code) {
+                    return None;
+                }
+
+                let signature = rule.rule.signature.as_deref().unwrap_or(&rule.rule.name);
+                Some(CodePattern::detected(
+                    &rule.rule.name,
+                    rule.pattern_type.clone(),
+                    signature,
+                    pattern_frameworks,
+                    rule.rule.confidence,
+                ))
+            })
+            .collect()
+    }
+
+    /// Framework patterns detected in a chunk for one of its frameworks
+    pub fn framework_patterns(&self, chunk: &LearningChunk, framework: &str) -> Vec<FrameworkPattern> {
+        let ChunkContent::Code { code, language, .. } = &chunk.content else {
+            return Vec::new();
+        };
+
+        self.rules.iter()
+            .filter(|rule| rule.rule.kind == RuleKind::Framework)
+            .filter(|rule| rule.applies_to_framework(framework) && rule.applies_to_language(language))
+            .filter(|rule| rule.matches(code))
+            .map(|rule| FrameworkPattern {
+                id: format!("{}-{}", rule.id(), chunk.id),
+                chunk_id: chunk.id.clone(),
+                framework: framework.to_string(),
+                pattern_name: rule.rule.name.clone(),
+                template: code.clone(),
+                dependencies: rule.rule.dependencies.clone(),
+                best_practices: rule.rule.best_practices.clone(),
+                common_issues: rule.rule.common_issues.clone(),
+                related_patterns: rule.rule.related_patterns.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Rule pack files in a directory, sorted by name
+fn rule_pack_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read rule directory {:?}", dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| RULE_PACK_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known)))
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+// Path, modification time and length of each pack file
+type Fingerprint = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+fn fingerprint(dir: &Path) -> Result<Fingerprint> {
+    rule_pack_paths(dir)?
+        .into_iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(&path)
+                .with_context(|| format!("Failed to stat rule pack {:?}", path))?;
+            Ok((path, metadata.modified().ok(), metadata.len()))
+        })
+        .collect()
+}
+
+#[derive(Debug, Default)]
+struct WatchState {
+    // Fingerprint of the last load attempt, successful or not
+    fingerprint: Option<Fingerprint>,
+    checked: Option<Instant>,
+}
+
+/// A rule directory watched for changes
+///
+/// Changes are detected by polling file names, sizes and modification times,
+/// at most once per `interval`.
+#[derive(Debug)]
+pub struct RuleDirectory {
+    path: PathBuf,
+    interval: Duration,
+    state: Mutex<WatchState>,
+}
+
+impl RuleDirectory {
+    /// Watch a directory, checking it at most once per `interval`
+    pub fn new(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        Self {
+            path: path.into(),
+            interval,
+            state: Mutex::new(WatchState::default()),
+        }
+    }
+
+    /// The watched directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the rules now
+    ///
+    /// The directory's state is remembered even if loading fails, so a broken
+    /// pack is reported once rather than on every check.
+    pub fn load(&self) -> Result<RuleSet> {
+        let mut state = self.state.lock();
+        state.fingerprint = fingerprint(&self.path).ok();
+        state.checked = Some(Instant::now());
+        drop(state);
+
+        RuleSet::load_dir(&self.path)
+    }
+
+    /// Check whether the directory changed since the last load
+    ///
+    /// Returns `false` without touching the file system if the last check was
+    /// less than `interval` ago.
+    pub fn changed(&self) -> bool {
+        let mut state = self.state.lock();
+        if state.checked.is_some_and(|checked| checked.elapsed() < self.interval) {
+            return false;
+        }
+        state.checked = Some(Instant::now());
+        fingerprint(&self.path).ok() != state.fingerprint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::{ChunkId, ChunkMetadata, ChunkType};
+
+    const NUXT_PACK: &str = r#"
+name: nuxt
+rules:
+  - id: nuxt-async-data
+    name: Nuxt Async Data
+    pattern_type: function
+    frameworks: [nuxt.js]
+    when:
+      all: [{ token: useAsyncData }]
+      none: [{ regex: "//\\s*legacy" }]
+    confidence: 0.9
+  - id: nuxt-server-route
+    kind: framework
+    name: Server Route
+    frameworks: [nuxt]
+    languages: [typescript]
+    when:
+      any: [{ contains: defineEventHandler }]
+    best_practices: [Validate the request body]
+"#;
+
+    fn chunk(code: &str, framework: &str) -> LearningChunk {
+        LearningChunk {
+            id: ChunkId::new("chunk"),
+            chunk_type: ChunkType::Pattern,
+            content: ChunkContent::Code {
+                language: "typescript".to_string(),
+                code: code.to_string(),
+                framework: Some(framework.to_string()),
+            },
+            metadata: ChunkMetadata {
+                frameworks: vec![framework.to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_builtin_rules_detect_patterns() {
+        let rules = RuleSet::builtin();
+        assert_eq!(rules.packs(), ["builtin".to_string()]);
+
+        let framework = "nextjs".to_string();
+        let patterns = rules.code_patterns(
+            "export async function getServerSideProps() { return { props: {} }; }",
+            "typescript",
+            Some(&framework),
+            std::slice::from_ref(&framework),
+        );
+        let names: Vec<&str> = patterns.iter().map(|pattern| pattern.name.as_str()).collect();
+        assert_eq!(names, ["Export Function", "Next.js Server-Side Rendering"]);
+
+        // Scoped rules do not fire for other frameworks
+        let vue = "vue".to_string();
+        let patterns = rules.code_patterns("getServerSideProps()", "typescript", Some(&vue), std::slice::from_ref(&vue));
+        assert!(patterns.is_empty());
+
+        let controller = chunk("class UserController extends Controller {}", "laravel");
+        let patterns = rules.framework_patterns(&controller, "laravel");
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].id, "laravel-controller-chunk");
+        assert!(!patterns[0].best_practices.is_empty());
+    }
+
+    #[test]
+    fn test_pack_rules_and_conditions() {
+        let pack = RulePack::from_yaml(NUXT_PACK).unwrap();
+        let rules = RuleSet::from_packs(vec![RulePack::from_yaml(BUILTIN_RULES).unwrap(), pack]).unwrap();
+        let nuxt = "nuxt".to_string();
+
+        let code = "const { data } = await useAsyncData('posts', () => $fetch('/api/posts'))";
+        let patterns = rules.code_patterns(code, "typescript", Some(&nuxt), std::slice::from_ref(&nuxt));
+        let pattern = patterns.iter().find(|pattern| pattern.name == "Nuxt Async Data").unwrap();
+        assert_eq!(pattern.frameworks, vec!["nuxt".to_string()]);
+        assert_eq!(pattern.signature, "Nuxt Async Data");
+        assert_eq!(pattern.pattern_type, PatternType::Function);
+
+        // Tokens must match whole identifiers and `none` conditions veto
+        for code in ["useAsyncDataLater()", "useAsyncData() // legacy"] {
+            let patterns = rules.code_patterns(code, "typescript", Some(&nuxt), std::slice::from_ref(&nuxt));
+            assert!(patterns.iter().all(|pattern| pattern.name != "Nuxt Async Data"), "{}", code);
+        }
+
+        let route = chunk("export default defineEventHandler(() => 'ok')", "nuxt");
+        let patterns = rules.framework_patterns(&route, "nuxt");
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].best_practices, vec!["Validate the request body".to_string()]);
+    }
+
+    #[test]
+    fn test_invalid_packs_are_rejected() {
+        let invalid = [
+            "name: bad\nrules:\n  - id: a\n    name: A\n    when: { all: [{ regex: \"(\" }] }\n",
+            "name: bad\nrules:\n  - id: a\n    name: A\n    when: {}\n",
+            "name: bad\nrules:\n  - id: a\n    name: A\n    confidence: 2.0\n    when: { all: [{ contains: x }] }\n",
+            "name: bad\nrules:\n  - id: a\n    name: A\n    when: { all: [{ contains: x, token: y }] }\n",
+            "name: bad\nrules:\n  - id: a\n    name: A\n    scope: [x]\n    when: { all: [{ contains: x }] }\n",
+        ];
+        for pack in invalid {
+            let result = RulePack::from_yaml(pack).and_then(|pack| pack.compile());
+            assert!(result.is_err(), "{}", pack);
+        }
+
+        // Built-in rules may be overridden, but packs may not clash with each other
+        let override_pack = "name: custom\nrules:\n  - id: export-function\n    name: Exported\n    when: { all: [{ token: export }] }\n";
+        let rules = RuleSet::from_packs(vec![
+            RulePack::from_yaml(BUILTIN_RULES).unwrap(),
+            RulePack::from_yaml(override_pack).unwrap(),
+        ]).unwrap();
+        assert_eq!(rules.len(), RuleSet::builtin().len());
+        assert!(rules.rules().iter().any(|rule| rule.rule.name == "Exported"));
+
+        let clash = RuleSet::from_packs(vec![
+            RulePack::from_yaml(BUILTIN_RULES).unwrap(),
+            RulePack::from_yaml(override_pack).unwrap(),
+            RulePack::from_yaml(&override_pack.replace("custom", "other")).unwrap(),
+        ]);
+        assert!(clash.is_err());
+    }
+
+    #[test]
+    fn test_rule_directory_detects_changes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let directory = RuleDirectory::new(temp_dir.path(), Duration::ZERO);
+
+        let rules = directory.load().unwrap();
+        assert_eq!(rules.len(), RuleSet::builtin().len());
+        assert!(!directory.changed());
+
+        std::fs::write(temp_dir.path().join("nuxt.yaml"), NUXT_PACK).unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "ignored").unwrap();
+        assert!(directory.changed());
+        let rules = directory.load().unwrap();
+        assert_eq!(rules.packs(), ["builtin".to_string(), "nuxt".to_string()]);
+        assert!(!directory.changed());
+
+        let throttled = RuleDirectory::new(temp_dir.path(), Duration::from_secs(3600));
+        throttled.load().unwrap();
+        std::fs::remove_file(temp_dir.path().join("nuxt.yaml")).unwrap();
+        assert!(!throttled.changed());
+        assert!(directory.changed());
+    }
+}
@@ -3,21 +3,36 @@
 //! Provides fast, full-text search capabilities for learning chunks with
 //! support for complex queries, faceted search, and relevance scoring.
 
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::{Context, Result};
+use fst::automaton::Levenshtein;
+use fst::Automaton;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use tantivy::{
-    collector::TopDocs,
-    query::{AllQuery, BooleanQuery, FuzzyTermQuery, QueryParser, TermQuery},
-    schema::{Field, Schema, Value, FAST, INDEXED, STORED, TEXT},
-    Index, IndexReader, IndexWriter, Searcher, TantivyError, Term,
-    tokenizer::TextAnalyzer,
+    collector::{Count, FacetCollector, TopDocs},
+    query::{AllQuery, BooleanQuery, ConstScoreQuery, EnableScoring, FuzzyTermQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
+    schema::{
+        Facet, FacetOptions, Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value,
+        FAST, INDEXED, STORED, STRING,
+    },
+    snippet::SnippetGenerator,
+    DocAddress, DocSet, Index, IndexReader, IndexWriter, Searcher, TantivyDocument, TantivyError, Term,
+    tokenizer::{TextAnalyzer, TokenizerManager},
 };
-use tokio::sync::RwLock as AsyncRwLock;
+use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
 
+use crate::alias::AliasTable;
 use crate::chunk::{ChunkId, LearningChunk, ChunkType, ChunkContent};
+use crate::explain::{FieldContribution, FilterEffect, HitExplanation, RankingStage, TermContribution};
+use crate::tokenizer::{cjk_analyzer, code_analyzer, CodeTokenizerOptions, TextScript, CJK_TOKENIZER, CODE_TOKENIZER};
+use crate::query::{facet, spike_dependencies, FacetCounts, SearchQuery, SearchResults, SpikeComplexity};
 
 /// Search engine configuration
 #[derive(Debug, Clone)]
@@ -28,8 +43,12 @@ pub struct SearchConfig {
     pub max_results: usize,
     /// Enable fuzzy search
     pub enable_fuzzy: bool,
-    /// Commit frequency for indexing
+    /// Minimum interval between commits of single-chunk writes; searches commit pending writes first
     pub commit_interval_secs: u64,
+    /// Options for the code tokenizer used on content, patterns and file paths
+    pub tokenizer: CodeTokenizerOptions,
+    /// Framework aliases used to expand framework filters and query words
+    pub aliases: Arc<AliasTable>,
 }
 
 impl Default for SearchConfig {
@@ -39,16 +58,91 @@ impl Default for SearchConfig {
             max_results: 1000,
             enable_fuzzy: true,
             commit_interval_secs: 5,
+            tokenizer: CodeTokenizerOptions::default(),
+            aliases: Arc::new(AliasTable::builtin()),
         }
     }
 }
 
+/// Version of the index schema and analyzers
+///
+/// Bump whenever fields, field options or tokenizers change so that existing
+/// indexes are rebuilt from storage on startup.
+pub const SEARCH_SCHEMA_VERSION: u32 = 1;
+
+/// File in the index directory recording the active generation and schema version
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Writer heap size per index generation
+const WRITER_HEAP_BYTES: usize = 50_000_000;
+
+/// Share of the weakest exact hit's score given to the best fuzzy-only hit
+const FUZZY_SCORE_RATIO: f32 = 0.5;
+
+/// Maximum length of generated highlight snippets
+const SNIPPET_MAX_CHARS: usize = 200;
+
+/// Chunks buffered between the storage scan and the indexer during a rebuild
+const REBUILD_CHANNEL_CAPACITY: usize = 256;
+
+/// Raw search hit as returned by the index, before hydration from storage
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexHit {
+    /// ID of the matching chunk
+    pub chunk_id: ChunkId,
+    /// BM25 relevance score
+    pub score: f32,
+    /// Names of the index fields that contain a query term
+    pub matched_fields: Vec<String>,
+    /// Highlighted content fragment (HTML with `<b>` markers)
+    pub snippet: Option<String>,
+    /// Ranking breakdown, present when explanations were requested
+    pub explanation: Option<HitExplanation>,
+}
+
+/// Search result carrying the full chunk along with its ranking details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// The matching chunk, loaded from cache or storage
+    pub chunk: LearningChunk,
+    /// BM25 relevance score
+    pub score: f32,
+    /// Names of the index fields that contain a query term
+    pub matched_fields: Vec<String>,
+    /// Highlighted content fragment (HTML with `<b>` markers)
+    pub highlighted_snippet: Option<String>,
+    /// Ranking breakdown, present when explanations were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<HitExplanation>,
+}
+
+impl SearchHit {
+    /// Combine an index hit with its hydrated chunk
+    pub fn from_index_hit(hit: IndexHit, chunk: LearningChunk) -> Self {
+        Self {
+            chunk,
+            score: hit.score,
+            matched_fields: hit.matched_fields,
+            highlighted_snippet: hit.snippet,
+            explanation: hit.explanation,
+        }
+    }
+}
+
+/// Exact clause of a structured query, described for explanations
+struct FilterClause {
+    occur: Occur,
+    description: String,
+    query: Box<dyn Query>,
+}
+
 /// Fields in the search index
 #[derive(Debug, Clone)]
 struct IndexSchema {
     chunk_id: Field,
     chunk_type: Field,
     content: Field,
+    content_cjk: Field,
     language: Field,
     framework: Field,
     tags: Field,
@@ -56,78 +150,148 @@ struct IndexSchema {
     file_path: Field,
     source: Field,
     quality_score: Field,
+    created_at: Field,
+    facets: Field,
 }
 
-/// Search engine powered by tantivy
+/// Normalize a keyword value so that exact filters are case-insensitive
+fn normalize_keyword(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+/// Keyword stored for a chunk type
+fn chunk_type_keyword(chunk_type: &ChunkType) -> String {
+    format!("{:?}", chunk_type)
+}
+
+/// Active index generation recorded next to the index data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IndexManifest {
+    schema_version: u32,
+    generation: u64,
+    tokenizer: CodeTokenizerOptions,
+}
+
+/// Write performed while a rebuild is running, replayed into the new generation
 #[derive(Debug)]
+enum JournalEntry {
+    Upsert(Box<LearningChunk>),
+    Remove(ChunkId),
+}
+
+/// One on-disk index generation with its writer and reader
+struct IndexState {
+    generation: u64,
+    writer: AsyncRwLock<IndexWriter>,
+    reader: IndexReader,
+}
+
+/// Search engine powered by tantivy
+///
+/// Each index lives in a numbered generation directory. Rebuilds write a new
+/// generation from storage and swap it in atomically once complete.
 pub struct SearchEngine {
     config: SearchConfig,
-    index: Index,
     schema: Schema,
     fields: IndexSchema,
-    writer: Arc<AsyncRwLock<IndexWriter>>,
-    reader: IndexReader,
+    state: RwLock<Arc<IndexState>>,
+    // Writes made during a rebuild, `None` when no rebuild is running
+    journal: AsyncMutex<Option<Vec<JournalEntry>>>,
+    rebuilding: AtomicBool,
+    needs_rebuild: AtomicBool,
+    // Whether the writer holds changes that searches cannot see yet
+    pending: AtomicBool,
+    last_commit: parking_lot::Mutex<Instant>,
     query_parser: QueryParser,
+    cjk_query_parser: QueryParser,
+}
+
+impl std::fmt::Debug for SearchEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SearchEngine")
+            .field("config", &self.config)
+            .field("generation", &self.state.read().generation)
+            .field("needs_rebuild", &self.needs_rebuild())
+            .finish()
+    }
 }
 
 impl SearchEngine {
     /// Create a new search engine
     pub async fn new(index_path: &Path) -> Result<Self> {
+        Self::with_config(SearchConfig {
+            index_path: index_path.to_path_buf(),
+            ..Default::default()
+        }).await
+    }
+
+    /// Create a new search engine with custom configuration
+    ///
+    /// An index written with a different schema version, schema or tokenizer
+    /// configuration is not opened. A fresh generation is created instead and
+    /// [`needs_rebuild`](Self::needs_rebuild) reports that it must be
+    /// repopulated from storage.
+    pub async fn with_config(config: SearchConfig) -> Result<Self> {
+        let index_path = config.index_path.as_path();
         tracing::info!("Initializing search engine at: {:?}", index_path);
 
         // Create directory if it doesn't exist
         tokio::fs::create_dir_all(index_path).await
             .context("Failed to create search index directory")?;
 
-        // Build schema
-        let mut schema_builder = Schema::builder();
-        
-        let chunk_id = schema_builder.add_text_field("chunk_id", TEXT | STORED);
-        let chunk_type = schema_builder.add_text_field("chunk_type", TEXT | INDEXED);
-        let content = schema_builder.add_text_field("content", TEXT | INDEXED);
-        let language = schema_builder.add_text_field("language", TEXT | INDEXED | FAST);
-        let framework = schema_builder.add_text_field("framework", TEXT | INDEXED | FAST);
-        let tags = schema_builder.add_text_field("tags", TEXT | INDEXED);
-        let patterns = schema_builder.add_text_field("patterns", TEXT | INDEXED);
-        let file_path = schema_builder.add_text_field("file_path", TEXT | INDEXED | STORED);
-        let source = schema_builder.add_text_field("source", TEXT | INDEXED | FAST);
-        let quality_score = schema_builder.add_f64_field("quality_score", INDEXED | FAST);
-
-        let schema = schema_builder.build();
-        let fields = IndexSchema {
-            chunk_id,
-            chunk_type,
-            content,
-            language,
-            framework,
-            tags,
-            patterns,
-            file_path,
-            source,
-            quality_score,
+        let (schema, fields) = build_schema();
+
+        // Decide whether the existing index can be reused
+        let manifest = read_manifest(index_path).await?;
+        let expected = |generation| IndexManifest {
+            schema_version: SEARCH_SCHEMA_VERSION,
+            generation,
+            tokenizer: config.tokenizer.clone(),
+        };
+        let (mut generation, mut needs_rebuild) = match &manifest {
+            Some(manifest) if *manifest == expected(manifest.generation) => (manifest.generation, false),
+            Some(manifest) => {
+                tracing::warn!(
+                    "Search index schema version {} does not match {}, rebuilding",
+                    manifest.schema_version,
+                    SEARCH_SCHEMA_VERSION
+                );
+                (manifest.generation + 1, true)
+            }
+            // Indexes written before generations were introduced live directly in the index directory
+            None => (0, index_path.join("meta.json").exists()),
         };
 
-        // Open or create index
-        let index = if index_path.exists() && index_path.read_dir()?.next().is_some() {
-            Index::open_in_dir(index_path)
-                .context("Failed to open existing search index")?
-        } else {
-            Index::create_in_dir(index_path, schema.clone())
-                .context("Failed to create search index")?
+        let state = match open_generation(&config, &schema, generation, needs_rebuild) {
+            Ok(state) => state,
+            Err(e) if !needs_rebuild => {
+                tracing::warn!("Failed to open search index generation {}, rebuilding: {:#}", generation, e);
+                generation += 1;
+                needs_rebuild = true;
+                open_generation(&config, &schema, generation, true)?
+            }
+            Err(e) => return Err(e),
         };
 
-        // Create writer and reader
-        let writer = index.writer(50_000_000) // 50MB heap
-            .context("Failed to create index writer")?;
-        
-        let reader = index.reader_builder()
-            .reload_policy(tantivy::ReloadPolicy::OnCommit)
-            .try_into()
-            .context("Failed to create index reader")?;
+        if !needs_rebuild {
+            if manifest.is_none() {
+                write_manifest(index_path, &expected(generation)).await?;
+            }
+            remove_stale_generations(index_path, generation);
+        }
+
+        // Queries are split the same way but without the original compound,
+        // which would otherwise be required as part of the resulting phrase
+        let query_tokenizers = TokenizerManager::default();
+        query_tokenizers.register(
+            CODE_TOKENIZER,
+            code_analyzer(&CodeTokenizerOptions { keep_original: false, ..config.tokenizer.clone() }),
+        );
+        query_tokenizers.register(CJK_TOKENIZER, cjk_analyzer());
 
         // Create query parser for multiple fields
-        let query_parser = QueryParser::for_index(
-            &index,
+        let query_parser = QueryParser::new(
+            schema.clone(),
             vec![
                 fields.content,
                 fields.tags,
@@ -136,74 +300,275 @@ impl SearchEngine {
                 fields.framework,
                 fields.language,
             ],
+            query_tokenizers.clone(),
         );
 
-        let config = SearchConfig {
-            index_path: index_path.to_path_buf(),
-            ..Default::default()
-        };
+        // Queries containing CJK text run against the bigram field instead of the code fields
+        let cjk_query_parser = QueryParser::new(
+            schema.clone(),
+            vec![fields.content_cjk, fields.tags, fields.framework, fields.language],
+            query_tokenizers,
+        );
 
-        tracing::info!("Search engine initialized successfully");
+        tracing::info!("Search engine initialized successfully (generation {})", generation);
 
         Ok(Self {
             config,
-            index,
             schema,
             fields,
-            writer: Arc::new(AsyncRwLock::new(writer)),
-            reader,
+            state: RwLock::new(Arc::new(state)),
+            journal: AsyncMutex::new(None),
+            rebuilding: AtomicBool::new(false),
+            needs_rebuild: AtomicBool::new(needs_rebuild),
+            pending: AtomicBool::new(false),
+            last_commit: parking_lot::Mutex::new(Instant::now()),
             query_parser,
+            cjk_query_parser,
         })
     }
 
+    /// Check whether the index was found incompatible on startup and has not been rebuilt yet
+    pub fn needs_rebuild(&self) -> bool {
+        self.needs_rebuild.load(Ordering::SeqCst)
+    }
+
+    /// Check whether a rebuild is currently running
+    pub fn is_rebuilding(&self) -> bool {
+        self.rebuilding.load(Ordering::SeqCst)
+    }
+
+    /// Rebuild the index from the authoritative chunk data
+    ///
+    /// Documents are written to a new generation while searches keep using the
+    /// current one. Writes made in the meantime are journaled and replayed
+    /// before the new generation is swapped in and recorded in the manifest.
+    ///
+    /// `scan` is called on a blocking thread once journaling has started, so
+    /// every write either lands in the journal or is already visible to it.
+    pub async fn rebuild<F, I>(&self, scan: F) -> Result<usize>
+    where
+        F: FnOnce() -> I + Send + 'static,
+        I: IntoIterator<Item = Result<LearningChunk>>,
+    {
+        if self.rebuilding.swap(true, Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("Search index rebuild already in progress"));
+        }
+
+        let result = self.rebuild_generation(scan).await;
+        if result.is_err() {
+            *self.journal.lock().await = None;
+        }
+
+        self.rebuilding.store(false, Ordering::SeqCst);
+        result
+    }
+
+    async fn rebuild_generation<F, I>(&self, scan: F) -> Result<usize>
+    where
+        F: FnOnce() -> I + Send + 'static,
+        I: IntoIterator<Item = Result<LearningChunk>>,
+    {
+        let generation = self.state.read().generation + 1;
+        tracing::info!("Rebuilding search index into generation {}", generation);
+
+        let new_state = open_generation(&self.config, &self.schema, generation, true)?;
+        *self.journal.lock().await = Some(Vec::new());
+
+        // Scan only after journaling started so no write falls between the two
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(REBUILD_CHANNEL_CAPACITY);
+        let scanner = tokio::task::spawn_blocking(move || {
+            for chunk in scan() {
+                if sender.blocking_send(chunk).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut indexed = 0;
+        {
+            let writer = new_state.writer.write().await;
+            while let Some(chunk) = receiver.recv().await {
+                writer.add_document(self.build_document(&chunk?))
+                    .context("Failed to add document to rebuilt index")?;
+                indexed += 1;
+
+                if indexed % 1000 == 0 {
+                    tracing::debug!("Rebuilt {} documents", indexed);
+                }
+            }
+        }
+        scanner.await.context("Search index rebuild scan failed")?;
+
+        // Holding the journal blocks writers until the new generation is live
+        let mut journal = self.journal.lock().await;
+        {
+            let mut writer = new_state.writer.write().await;
+            for entry in journal.take().unwrap_or_default() {
+                match entry {
+                    JournalEntry::Upsert(chunk) => {
+                        writer.delete_term(self.id_term(&chunk.id));
+                        writer.add_document(self.build_document(&chunk))
+                            .context("Failed to replay document into rebuilt index")?;
+                    }
+                    JournalEntry::Remove(chunk_id) => {
+                        writer.delete_term(self.id_term(&chunk_id));
+                    }
+                }
+            }
+            writer.commit()
+                .context("Failed to commit rebuilt search index")?;
+        }
+        new_state.reader.reload()
+            .context("Failed to reload rebuilt search index reader")?;
+
+        write_manifest(&self.config.index_path, &IndexManifest {
+            schema_version: SEARCH_SCHEMA_VERSION,
+            generation,
+            tokenizer: self.config.tokenizer.clone(),
+        }).await?;
+
+        let previous = std::mem::replace(&mut *self.state.write(), Arc::new(new_state));
+        drop(journal);
+        drop(previous);
+
+        self.needs_rebuild.store(false, Ordering::SeqCst);
+        remove_stale_generations(&self.config.index_path, generation);
+
+        tracing::info!("Search index rebuilt with {} documents (generation {})", indexed, generation);
+        Ok(indexed)
+    }
+
+    /// Currently active index generation
+    fn state(&self) -> Arc<IndexState> {
+        self.state.read().clone()
+    }
+
+    /// Searcher over the active generation
+    fn searcher(&self) -> Searcher {
+        self.state().reader.searcher()
+    }
+
+    /// Term matching a chunk's document
+    fn id_term(&self, chunk_id: &ChunkId) -> Term {
+        Term::from_field_text(self.fields.chunk_id, chunk_id.as_str())
+    }
+
     /// Index a learning chunk for search
     pub async fn index_chunk(&self, chunk: &LearningChunk) -> Result<()> {
         tracing::debug!("Indexing chunk: {}", chunk.id);
 
-        let mut doc = tantivy::Document::new();
+        let doc = self.build_document(chunk);
+
+        // Replace any previous version of the chunk
+        {
+            let mut journal = self.journal.lock().await;
+            let state = self.state();
+            let writer = state.writer.write().await;
+            writer.delete_term(self.id_term(&chunk.id));
+            writer.add_document(doc)
+                .context("Failed to add document to index")?;
+            self.pending.store(true, Ordering::SeqCst);
+
+            if let Some(journal) = journal.as_mut() {
+                journal.push(JournalEntry::Upsert(Box::new(chunk.clone())));
+            }
+        }
+
+        tracing::debug!("Chunk indexed successfully: {}", chunk.id);
+        Ok(())
+    }
+
+    /// Build the index document for a chunk
+    fn build_document(&self, chunk: &LearningChunk) -> TantivyDocument {
+        let mut doc = TantivyDocument::new();
+        let mut facet_values: BTreeSet<(&str, String)> = BTreeSet::new();
 
         // Add basic fields
+        let chunk_type = chunk_type_keyword(&chunk.chunk_type);
         doc.add_text(self.fields.chunk_id, chunk.id.as_str());
-        doc.add_text(self.fields.chunk_type, &format!("{:?}", chunk.chunk_type));
+        doc.add_text(self.fields.chunk_type, &chunk_type);
         doc.add_f64(self.fields.quality_score, chunk.quality_score as f64);
-        doc.add_text(self.fields.source, &chunk.metadata.source);
+        doc.add_date(
+            self.fields.created_at,
+            tantivy::DateTime::from_timestamp_secs(chunk.metadata.created_at.timestamp()),
+        );
+        facet_values.insert((facet::CHUNK_TYPE, chunk_type));
+        facet_values.insert((facet::SOURCE, normalize_keyword(&chunk.metadata.source)));
 
         // Add content based on type
-        match &chunk.content {
+        let mut languages = Vec::new();
+        let mut frameworks = Vec::new();
+        let mut declared_script = None;
+        let content_text = match &chunk.content {
             ChunkContent::Code { language, code, framework } => {
-                doc.add_text(self.fields.content, code);
-                doc.add_text(self.fields.language, language);
-                if let Some(fw) = framework {
-                    doc.add_text(self.fields.framework, fw);
-                }
+                languages.push(language);
+                frameworks.extend(framework);
+                Some(code.clone())
             }
             ChunkContent::Config { content, format, .. } => {
-                doc.add_text(self.fields.content, content);
-                doc.add_text(self.fields.language, format);
+                languages.push(format);
+                Some(content.clone())
             }
             ChunkContent::Documentation { content, format, language } => {
-                doc.add_text(self.fields.content, content);
-                doc.add_text(self.fields.language, format);
-                if let Some(lang) = language {
-                    doc.add_text(self.fields.language, lang);
-                }
+                languages.push(format);
+                languages.extend(language);
+                declared_script = language.as_deref().and_then(TextScript::from_language);
+                Some(content.clone())
             }
             ChunkContent::Data { data, format } => {
-                doc.add_text(self.fields.content, &data.to_string());
-                doc.add_text(self.fields.language, format);
+                languages.push(format);
+                Some(data.to_string())
             }
             ChunkContent::Binary { mime_type, .. } => {
-                doc.add_text(self.fields.language, mime_type);
+                languages.push(mime_type);
+                None
+            }
+        };
+
+        if let Some(text) = &content_text {
+            doc.add_text(self.fields.content, text);
+
+            // CJK text is additionally indexed as bigrams
+            let script = declared_script.unwrap_or_else(|| TextScript::detect(text));
+            if script == TextScript::Cjk {
+                doc.add_text(self.fields.content_cjk, text);
             }
         }
+        frameworks.extend(&chunk.metadata.frameworks);
+
+        // Add keyword metadata
+        for language in languages {
+            facet_values.insert((facet::LANGUAGE, normalize_keyword(language)));
+        }
+
+        for framework in frameworks {
+            facet_values.insert((facet::FRAMEWORK, normalize_keyword(framework)));
+        }
 
-        // Add metadata
         for tag in &chunk.metadata.tags {
-            doc.add_text(self.fields.tags, tag);
+            facet_values.insert((facet::TAG, normalize_keyword(tag)));
+        }
+
+        if let Some(complexity) = SpikeComplexity::of_chunk(chunk) {
+            facet_values.insert((facet::SPIKE_COMPLEXITY, complexity.as_str().to_string()));
+        }
+
+        for dependency in spike_dependencies(chunk) {
+            facet_values.insert((facet::SPIKE_DEPENDENCY, normalize_keyword(&dependency)));
         }
 
-        for framework in &chunk.metadata.frameworks {
-            doc.add_text(self.fields.framework, framework);
+        for (dimension, value) in &facet_values {
+            let field = match *dimension {
+                facet::LANGUAGE => Some(self.fields.language),
+                facet::FRAMEWORK => Some(self.fields.framework),
+                facet::TAG => Some(self.fields.tags),
+                facet::SOURCE => Some(self.fields.source),
+                _ => None,
+            };
+            if let Some(field) = field {
+                doc.add_text(field, value);
+            }
+            doc.add_facet(self.fields.facets, Facet::from_path([*dimension, value.as_str()]));
         }
 
         for pattern in &chunk.metadata.patterns {
@@ -214,26 +579,23 @@ impl SearchEngine {
             doc.add_text(self.fields.file_path, file_path);
         }
 
-        // Add to index
-        {
-            let mut writer = self.writer.write().await;
-            writer.add_document(doc)
-                .context("Failed to add document to index")?;
-        }
-
-        tracing::debug!("Chunk indexed successfully: {}", chunk.id);
-        Ok(())
+        doc
     }
 
     /// Remove a chunk from the search index
     pub async fn remove_chunk(&self, chunk_id: &ChunkId) -> Result<()> {
         tracing::debug!("Removing chunk from index: {}", chunk_id);
 
-        let term = Term::from_field_text(self.fields.chunk_id, chunk_id.as_str());
-        
         {
-            let mut writer = self.writer.write().await;
-            writer.delete_term(term);
+            let mut journal = self.journal.lock().await;
+            let state = self.state();
+            let writer = state.writer.write().await;
+            writer.delete_term(self.id_term(chunk_id));
+            self.pending.store(true, Ordering::SeqCst);
+
+            if let Some(journal) = journal.as_mut() {
+                journal.push(JournalEntry::Remove(chunk_id.clone()));
+            }
         }
 
         tracing::debug!("Chunk removed from index: {}", chunk_id);
@@ -241,142 +603,349 @@ impl SearchEngine {
     }
 
     /// Search for chunks using query string
-    pub async fn search(&self, query_str: &str, limit: usize) -> Result<Vec<LearningChunk>> {
-        tracing::debug!("Searching for: {}", query_str);
+    pub async fn search(&self, query_str: &str, limit: usize) -> Result<Vec<IndexHit>> {
+        self.search_hits(query_str, limit).await
+    }
 
-        let searcher = self.reader.searcher();
-        
-        // Parse the query
-        let query = self.query_parser.parse_query(query_str)
-            .context("Failed to parse search query")?;
+    /// Search for chunks and return scored hits with matched fields and snippets
+    pub async fn search_hits(&self, query_str: &str, limit: usize) -> Result<Vec<IndexHit>> {
+        tracing::debug!("Searching hits for: {}", query_str);
+        self.commit_pending().await?;
 
-        // Execute search
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))
-            .context("Failed to execute search")?;
+        let query = self.parse_text(query_str)?;
 
-        let mut results = Vec::new();
+        let results = self.execute(query.as_ref(), Some(query.as_ref()), &[], limit, false, false)?;
+        Ok(results.hits)
+    }
 
-        for (_score, doc_address) in top_docs {
-            let retrieved_doc = searcher.doc(doc_address)
-                .context("Failed to retrieve document")?;
+    /// Search like [`search_hits`](Self::search_hits), attaching a ranking explanation to every hit
+    pub async fn explain_hits(&self, query_str: &str, limit: usize) -> Result<Vec<IndexHit>> {
+        tracing::debug!("Explaining hits for: {}", query_str);
+        self.commit_pending().await?;
 
-            if let Some(chunk_id_value) = retrieved_doc.get_first(self.fields.chunk_id) {
-                if let Some(chunk_id_str) = chunk_id_value.as_text() {
-                    // For this implementation, we'll return placeholder chunks
-                    // In a real implementation, you'd load the full chunk from storage
-                    let chunk = self.create_search_result_chunk(chunk_id_str, &retrieved_doc)?;
-                    results.push(chunk);
-                }
-            }
-        }
+        let query = self.parse_text(query_str)?;
 
-        tracing::debug!("Search completed. Found {} results", results.len());
-        Ok(results)
+        let results = self.execute(query.as_ref(), Some(query.as_ref()), &[], limit, false, true)?;
+        Ok(results.hits)
     }
 
-    /// Search chunks by framework
-    pub async fn search_by_framework(&self, framework: &str, limit: usize) -> Result<Vec<LearningChunk>> {
-        let term = Term::from_field_text(self.fields.framework, framework);
-        let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
-        
-        let searcher = self.reader.searcher();
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))
-            .context("Failed to execute framework search")?;
+    /// Search chunks by framework, matching every known spelling of it
+    pub async fn search_by_framework(&self, framework: &str, limit: usize) -> Result<Vec<IndexHit>> {
+        self.commit_pending().await?;
 
-        let mut results = Vec::new();
-        for (_score, doc_address) in top_docs {
-            let retrieved_doc = searcher.doc(doc_address)
-                .context("Failed to retrieve document")?;
+        let query = BooleanQuery::new(
+            self.framework_terms(&[framework.to_string()]).into_iter()
+                .map(|term| (Occur::Should, Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>))
+                .collect(),
+        );
+        Ok(self.execute(&query, None, &[], limit, false, false)?.hits)
+    }
 
-            if let Some(chunk_id_value) = retrieved_doc.get_first(self.fields.chunk_id) {
-                if let Some(chunk_id_str) = chunk_id_value.as_text() {
-                    let chunk = self.create_search_result_chunk(chunk_id_str, &retrieved_doc)?;
-                    results.push(chunk);
-                }
-            }
-        }
+    /// Search chunks by pattern
+    pub async fn search_by_pattern(&self, pattern: &str, limit: usize) -> Result<Vec<IndexHit>> {
+        self.commit_pending().await?;
 
-        Ok(results)
+        let query = TermQuery::new(Term::from_field_text(self.fields.patterns, pattern), IndexRecordOption::Basic);
+        Ok(self.execute(&query, None, &[], limit, false, false)?.hits)
     }
 
-    /// Search chunks by pattern
-    pub async fn search_by_pattern(&self, pattern: &str, limit: usize) -> Result<Vec<LearningChunk>> {
-        let term = Term::from_field_text(self.fields.patterns, pattern);
-        let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
-        
-        let searcher = self.reader.searcher();
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))
-            .context("Failed to execute pattern search")?;
+    /// Run a structured query and return hits with facet counts over all matches
+    pub async fn query(&self, query: &SearchQuery) -> Result<SearchResults<IndexHit>> {
+        tracing::debug!("Running structured query: {:?}", query);
+        self.commit_pending().await?;
 
-        let mut results = Vec::new();
-        for (_score, doc_address) in top_docs {
-            let retrieved_doc = searcher.doc(doc_address)
-                .context("Failed to retrieve document")?;
+        let text_query = match query.text.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => Some(self.parse_text(text)?),
+            _ => None,
+        };
 
-            if let Some(chunk_id_value) = retrieved_doc.get_first(self.fields.chunk_id) {
-                if let Some(chunk_id_str) = chunk_id_value.as_text() {
-                    let chunk = self.create_search_result_chunk(chunk_id_str, &retrieved_doc)?;
-                    results.push(chunk);
-                }
-            }
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        if let Some(text_query) = &text_query {
+            clauses.push((Occur::Must, text_query.box_clone()));
+        }
+
+        let mut filters = self.filter_clauses(query);
+        for text in query.excluded_text.iter().map(|text| text.trim()).filter(|text| !text.is_empty()) {
+            filters.push(FilterClause {
+                occur: Occur::MustNot,
+                description: format!("text: {}", text),
+                query: self.parse_text(text)?,
+            });
+        }
+
+        for filter in &filters {
+            // Filters restrict the result set without affecting relevance
+            clauses.push((filter.occur, Box::new(ConstScoreQuery::new(filter.query.box_clone(), 0.0))));
+        }
+
+        // Exclusions alone match nothing, so they subtract from the full set
+        if !clauses.is_empty() && clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+            clauses.push((Occur::Must, Box::new(AllQuery)));
         }
 
-        Ok(results)
+        let combined: Box<dyn Query> = if clauses.is_empty() {
+            Box::new(AllQuery)
+        } else {
+            Box::new(BooleanQuery::new(clauses))
+        };
+
+        self.execute(combined.as_ref(), text_query.as_deref(), &filters, query.limit, true, query.explain)
     }
 
-    /// Fuzzy search for chunks
-    pub async fn fuzzy_search(&self, term: &str, limit: usize) -> Result<Vec<LearningChunk>> {
-        if !self.config.enable_fuzzy {
-            return self.search(term, limit).await;
+    /// Typo-tolerant search, see [`fuzzy_search_hits`](Self::fuzzy_search_hits)
+    pub async fn fuzzy_search(&self, query_str: &str, limit: usize) -> Result<Vec<IndexHit>> {
+        self.fuzzy_search_hits(query_str, limit).await
+    }
+
+    /// Typo-tolerant search across content, tags, patterns and frameworks
+    ///
+    /// Hits for the exact query come first, ranked by BM25. Remaining slots are
+    /// filled with documents that only match query terms within an edit
+    /// distance scaled by term length (see [`fuzzy_distance`]); these rank by
+    /// how many terms and fields they match and how closely, and their scores
+    /// are scaled below the weakest exact hit.
+    pub async fn fuzzy_search_hits(&self, query_str: &str, limit: usize) -> Result<Vec<IndexHit>> {
+        tracing::debug!("Fuzzy searching for: {}", query_str);
+        self.commit_pending().await?;
+
+        let exact_query = self.parse_text(query_str)?;
+        let mut hits = self.execute(exact_query.as_ref(), Some(exact_query.as_ref()), &[], limit, false, false)?.hits;
+
+        if !self.config.enable_fuzzy || hits.len() >= limit {
+            return Ok(hits);
+        }
+
+        let field_queries = self.fuzzy_field_queries(query_str);
+        if field_queries.is_empty() {
+            return Ok(hits);
         }
 
-        let content_term = Term::from_field_text(self.fields.content, term);
-        let fuzzy_query = FuzzyTermQuery::new(content_term, 2, true); // Distance 2, transpositions enabled
+        let fuzzy_query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(BooleanQuery::new(
+                field_queries.iter().map(|(_, query)| (Occur::Should, query.box_clone())).collect(),
+            ))),
+            // Exact matches were already collected above
+            (Occur::MustNot, exact_query),
+        ]);
 
-        let searcher = self.reader.searcher();
-        let top_docs = searcher.search(&fuzzy_query, &TopDocs::with_limit(limit))
+        let searcher = self.searcher();
+        let top_docs = searcher.search(&fuzzy_query, &TopDocs::with_limit(limit - hits.len()))
             .context("Failed to execute fuzzy search")?;
 
-        let mut results = Vec::new();
-        for (_score, doc_address) in top_docs {
-            let retrieved_doc = searcher.doc(doc_address)
+        let max_fuzzy_score = top_docs.first().map(|(score, _)| *score).unwrap_or(0.0);
+        let score_ceiling = hits.last().map(|hit| hit.score * FUZZY_SCORE_RATIO);
+
+        let query_tokens = self.query_tokens(query_str);
+        for (score, doc_address) in top_docs {
+            let doc: TantivyDocument = searcher.doc(doc_address)
                 .context("Failed to retrieve document")?;
 
-            if let Some(chunk_id_value) = retrieved_doc.get_first(self.fields.chunk_id) {
-                if let Some(chunk_id_str) = chunk_id_value.as_text() {
-                    let chunk = self.create_search_result_chunk(chunk_id_str, &retrieved_doc)?;
-                    results.push(chunk);
+            let score = match score_ceiling {
+                Some(ceiling) if max_fuzzy_score > 0.0 => score / max_fuzzy_score * ceiling,
+                _ => score,
+            };
+
+            let content = doc.get_first(self.fields.content).and_then(|v| v.as_str()).unwrap_or("");
+            let snippet_generator = self.fuzzy_snippet_generator(&searcher, content, &query_tokens)?;
+            let matched_fields = self.fuzzy_matched_fields(&searcher, doc_address, &field_queries)?;
+            if let Some(hit) = self.hit_from_doc(&doc, score, matched_fields, snippet_generator.as_ref()) {
+                hits.push(hit);
+            }
+        }
+
+        tracing::debug!("Fuzzy search completed. Found {} hits", hits.len());
+        Ok(hits)
+    }
+
+    /// Build one fuzzy query per searchable field from the query terms
+    ///
+    /// Content and patterns are matched with the code tokenizer's terms;
+    /// tags and frameworks also try each whitespace-separated word as a whole
+    /// keyword. Closer matches score higher because a term within one edit
+    /// also matches the two-edit automaton.
+    fn fuzzy_field_queries(&self, query_str: &str) -> Vec<(Field, Box<dyn Query>)> {
+        let tokens = self.query_tokens(query_str);
+        let mut keywords = tokens.clone();
+        keywords.extend(query_str.split_whitespace().map(normalize_keyword));
+
+        let fields = [
+            (self.fields.content, &tokens),
+            (self.fields.patterns, &tokens),
+            (self.fields.tags, &keywords),
+            (self.fields.framework, &keywords),
+        ];
+
+        fields.into_iter()
+            .filter_map(|(field, terms)| {
+                let clauses: Vec<(Occur, Box<dyn Query>)> = terms.iter()
+                    // Edit distance means little for CJK text, which is handled by the exact bigram query
+                    .filter(|text| TextScript::detect(text) == TextScript::Default)
+                    .flat_map(|text| {
+                        let term = Term::from_field_text(field, text);
+                        (1..=fuzzy_distance(text)).map(move |distance| -> (Occur, Box<dyn Query>) {
+                            (Occur::Should, Box::new(FuzzyTermQuery::new(term.clone(), distance, true)))
+                        })
+                    })
+                    .collect();
+
+                if clauses.is_empty() {
+                    None
+                } else {
+                    Some((field, Box::new(BooleanQuery::new(clauses)) as Box<dyn Query>))
                 }
+            })
+            .collect()
+    }
+
+    /// Terms of a fuzzy query as the code tokenizer splits them
+    fn query_tokens(&self, query_str: &str) -> BTreeSet<String> {
+        let mut analyzer = code_analyzer(&CodeTokenizerOptions { keep_original: false, ..self.config.tokenizer.clone() });
+        let mut tokens = BTreeSet::new();
+        let mut stream = analyzer.token_stream(query_str);
+        while let Some(token) = stream.next() {
+            tokens.insert(token.text.clone());
+        }
+        tokens
+    }
+
+    /// Snippet generator highlighting the content words a fuzzy hit matched
+    ///
+    /// Fuzzy queries report no terms to highlight, so the document's own
+    /// tokens within the edit distance of a query term are highlighted instead.
+    fn fuzzy_snippet_generator(
+        &self,
+        searcher: &Searcher,
+        content: &str,
+        query_tokens: &BTreeSet<String>,
+    ) -> Result<Option<SnippetGenerator>> {
+        let automata: Vec<Levenshtein> = query_tokens.iter()
+            .filter(|text| TextScript::detect(text) == TextScript::Default)
+            .filter_map(|text| Levenshtein::new(text, u32::from(fuzzy_distance(text))).ok())
+            .collect();
+
+        let mut matched = BTreeSet::new();
+        let mut analyzer = code_analyzer(&self.config.tokenizer);
+        let mut stream = analyzer.token_stream(content);
+        while let Some(token) = stream.next() {
+            if automata.iter().any(|automaton| accepts(automaton, &token.text)) {
+                matched.insert(token.text.clone());
+            }
+        }
+        if matched.is_empty() {
+            return Ok(None);
+        }
+
+        let query = BooleanQuery::new(
+            matched.iter()
+                .map(|text| -> (Occur, Box<dyn Query>) {
+                    let term = Term::from_field_text(self.fields.content, text);
+                    (Occur::Should, Box::new(TermQuery::new(term, IndexRecordOption::WithFreqsAndPositions)))
+                })
+                .collect(),
+        );
+        let mut generator = SnippetGenerator::create(searcher, &query, self.fields.content)
+            .context("Failed to create snippet generator")?;
+        generator.set_max_num_chars(SNIPPET_MAX_CHARS);
+        Ok(Some(generator))
+    }
+
+    /// Turn a retrieved document into a hit, highlighting its content with the given generator
+    fn hit_from_doc(
+        &self,
+        doc: &TantivyDocument,
+        score: f32,
+        matched_fields: Vec<String>,
+        snippet_generator: Option<&SnippetGenerator>,
+    ) -> Option<IndexHit> {
+        let chunk_id = doc.get_first(self.fields.chunk_id).and_then(|v| v.as_str())?;
+
+        let content = doc.get_first(self.fields.content).and_then(|v| v.as_str()).unwrap_or("");
+        let snippet = snippet_generator
+            .map(|generator| generator.snippet(content))
+            .filter(|snippet| !snippet.is_empty())
+            .map(|snippet| snippet.to_html());
+
+        Some(IndexHit {
+            chunk_id: ChunkId::new(chunk_id),
+            score,
+            matched_fields,
+            snippet,
+            explanation: None,
+        })
+    }
+
+    /// Determine which fields of a document satisfy their fuzzy query
+    fn fuzzy_matched_fields(
+        &self,
+        searcher: &Searcher,
+        doc_address: DocAddress,
+        field_queries: &[(Field, Box<dyn Query>)],
+    ) -> Result<Vec<String>> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+        let mut fields = BTreeSet::new();
+
+        for (field, query) in field_queries {
+            let weight = query.weight(EnableScoring::disabled_from_searcher(searcher))
+                .context("Failed to build fuzzy query weight")?;
+            let mut scorer = weight.scorer(segment_reader, 1.0)
+                .context("Failed to build fuzzy query scorer")?;
+            if scorer.seek(doc_address.doc_id) == doc_address.doc_id {
+                fields.insert(self.schema.get_field_name(*field));
             }
         }
 
-        Ok(results)
+        Ok(fields.into_iter().map(str::to_string).collect())
     }
 
     /// Commit pending changes to the index
     pub async fn commit(&self) -> Result<()> {
         tracing::debug!("Committing search index changes");
 
+        // Cleared first so that writes racing with the commit stay pending
+        self.pending.store(false, Ordering::SeqCst);
+        *self.last_commit.lock() = Instant::now();
+        let state = self.state();
         {
-            let mut writer = self.writer.write().await;
+            let mut writer = state.writer.write().await;
             writer.commit()
                 .context("Failed to commit search index")?;
         }
 
         // Refresh the reader
-        self.reader.reload()
+        state.reader.reload()
             .context("Failed to reload search index reader")?;
 
         tracing::debug!("Search index committed successfully");
         Ok(())
     }
 
+    /// Commit pending changes if the commit interval has passed since the last commit
+    ///
+    /// Lets frequent single-chunk writes share commits; returns whether a commit ran.
+    pub async fn commit_if_due(&self) -> Result<bool> {
+        let interval = std::time::Duration::from_secs(self.config.commit_interval_secs);
+        if !self.pending.load(Ordering::SeqCst) || self.last_commit.lock().elapsed() < interval {
+            return Ok(false);
+        }
+        self.commit().await?;
+        Ok(true)
+    }
+
+    /// Commit pending changes so that a search sees every write made before it
+    async fn commit_pending(&self) -> Result<()> {
+        if self.pending.load(Ordering::SeqCst) {
+            self.commit().await?;
+        }
+        Ok(())
+    }
+
     /// Optimize the search index
     pub async fn optimize(&self) -> Result<()> {
         tracing::info!("Optimizing search index");
 
         {
-            let mut writer = self.writer.write().await;
+            let state = self.state();
+            let mut writer = state.writer.write().await;
             writer.wait_merging_threads()
                 .context("Failed to wait for merging threads")?;
         }
@@ -387,67 +956,400 @@ impl SearchEngine {
 
     /// Get search index statistics
     pub fn get_stats(&self) -> SearchStats {
-        let searcher = self.reader.searcher();
+        let searcher = self.searcher();
         let total_docs = searcher.num_docs() as u64;
         
         SearchStats {
             total_documents: total_docs,
             index_size_bytes: self.estimate_index_size(),
+            schema_version: SEARCH_SCHEMA_VERSION,
+            generation: self.state.read().generation,
         }
     }
 
-    /// Create a chunk from search result document (simplified version)
-    fn create_search_result_chunk(&self, chunk_id: &str, doc: &tantivy::Document) -> Result<LearningChunk> {
-        // This is a simplified implementation for search results
-        // In a full implementation, you'd load the complete chunk from storage
-        
-        let chunk_type = doc.get_first(self.fields.chunk_type)
-            .and_then(|v| v.as_text())
-            .unwrap_or("Pattern");
+    /// Parse free text with the parser matching its script
+    fn parse_text(&self, text: &str) -> Result<Box<dyn Query>> {
+        let parser = match TextScript::detect(text) {
+            TextScript::Cjk => &self.cjk_query_parser,
+            TextScript::Default => &self.query_parser,
+        };
 
-        let content_text = doc.get_first(self.fields.content)
-            .and_then(|v| v.as_text())
-            .unwrap_or("");
+        let parsed = parser.parse_query(text)
+            .context("Failed to parse search query")?;
 
-        let language = doc.get_first(self.fields.language)
-            .and_then(|v| v.as_text())
-            .unwrap_or("text");
+        // Words naming a known framework also match chunks of that framework
+        // under any alias. A quoted phrase naming one, such as the query
+        // syntax's `"react native"`, counts as a single word.
+        let words: Vec<String> = text.split('"')
+            .enumerate()
+            .flat_map(|(i, part)| {
+                let phrase = part.trim();
+                if i % 2 == 1 && self.config.aliases.canonicalize(phrase).is_some() {
+                    vec![phrase]
+                } else {
+                    part.split_whitespace().collect()
+                }
+            })
+            .filter(|word| self.config.aliases.canonicalize(word).is_some())
+            .map(str::to_string)
+            .collect();
+        if words.is_empty() {
+            return Ok(parsed);
+        }
 
-        let framework = doc.get_first(self.fields.framework)
-            .and_then(|v| v.as_text())
-            .map(|s| s.to_string());
+        let mut clauses = vec![(Occur::Should, parsed)];
+        for term in self.framework_terms(&words) {
+            clauses.push((Occur::Should, Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>));
+        }
+        Ok(Box::new(BooleanQuery::new(clauses)))
+    }
 
-        let quality_score = doc.get_first(self.fields.quality_score)
-            .and_then(|v| v.as_f64())
-            .unwrap_or(0.5) as f32;
+    /// Framework field terms for every alias of the given frameworks
+    fn framework_terms(&self, frameworks: &[String]) -> Vec<Term> {
+        let spellings: BTreeSet<String> = frameworks.iter()
+            .flat_map(|framework| self.config.aliases.expand(framework))
+            .chain(frameworks.iter().map(|framework| normalize_keyword(framework)))
+            .map(|spelling| normalize_keyword(&spelling))
+            .collect();
+
+        spellings.into_iter()
+            .map(|spelling| Term::from_field_text(self.fields.framework, &spelling))
+            .collect()
+    }
 
-        let file_path = doc.get_first(self.fields.file_path)
-            .and_then(|v| v.as_text())
-            .map(|s| s.to_string());
+    /// Build the exact filter clauses of a structured query
+    fn filter_clauses(&self, query: &SearchQuery) -> Vec<FilterClause> {
+        let filter = &query.filter;
+        let mut clauses = Vec::new();
 
-        let source = doc.get_first(self.fields.source)
-            .and_then(|v| v.as_text())
-            .unwrap_or("search");
+        let keyword = |field: Field, value: &str| Term::from_field_text(field, &normalize_keyword(value));
+        let facet_term = |dimension: &str, value: &str| {
+            Term::from_facet(self.fields.facets, &Facet::from_path([dimension, value]))
+        };
+        let type_term = |chunk_type: &ChunkType| Term::from_field_text(self.fields.chunk_type, &chunk_type_keyword(chunk_type));
+
+        // Each group matches any of its terms
+        let mut term_groups: Vec<(Occur, String, Vec<Term>)> = Vec::new();
+
+        let any_of = [
+            ("framework", filter.frameworks.clone(), self.framework_terms(&filter.frameworks)),
+            (
+                "language",
+                query.languages.clone(),
+                query.languages.iter().map(|lang| keyword(self.fields.language, lang)).collect(),
+            ),
+            (
+                "chunk_type",
+                filter.chunk_types.iter().map(chunk_type_keyword).collect(),
+                filter.chunk_types.iter().map(type_term).collect(),
+            ),
+            (
+                "spike_complexity",
+                query.spike_complexity.iter().map(|c| c.as_str().to_string()).collect(),
+                query.spike_complexity.iter().map(|c| facet_term(facet::SPIKE_COMPLEXITY, c.as_str())).collect(),
+            ),
+        ];
+        for (name, values, terms) in any_of.into_iter().filter(|(_, _, terms)| !terms.is_empty()) {
+            term_groups.push((Occur::Must, format!("{}: {}", name, values.join(", ")), terms));
+        }
 
-        Ok(LearningChunk {
-            id: ChunkId::new(chunk_id),
-            chunk_type: ChunkType::Pattern, // Default, would be parsed properly in full implementation
-            content: ChunkContent::Code {
-                language: language.to_string(),
-                code: content_text.to_string(),
-                framework,
-            },
-            metadata: crate::chunk::ChunkMetadata {
-                source: source.to_string(),
-                file_path,
-                ..Default::default()
-            },
-            embedding: None,
-            relationships: vec![],
-            quality_score,
+        for tag in &filter.tags {
+            term_groups.push((Occur::Must, format!("tag: {}", tag), vec![keyword(self.fields.tags, tag)]));
+        }
+        if let Some(source) = &filter.source {
+            term_groups.push((Occur::Must, format!("source: {}", source), vec![keyword(self.fields.source, source)]));
+        }
+        for dep in &query.spike_dependencies {
+            term_groups.push((
+                Occur::Must,
+                format!("spike_dependency: {}", dep),
+                vec![facet_term(facet::SPIKE_DEPENDENCY, &normalize_keyword(dep))],
+            ));
+        }
+
+        for framework in &filter.excluded_frameworks {
+            term_groups.push((
+                Occur::MustNot,
+                format!("framework: {}", framework),
+                self.framework_terms(std::slice::from_ref(framework)),
+            ));
+        }
+        for lang in &query.excluded_languages {
+            term_groups.push((Occur::MustNot, format!("language: {}", lang), vec![keyword(self.fields.language, lang)]));
+        }
+        for chunk_type in &filter.excluded_chunk_types {
+            term_groups.push((Occur::MustNot, format!("chunk_type: {}", chunk_type_keyword(chunk_type)), vec![type_term(chunk_type)]));
+        }
+        for tag in &filter.excluded_tags {
+            term_groups.push((Occur::MustNot, format!("tag: {}", tag), vec![keyword(self.fields.tags, tag)]));
+        }
+        for source in &filter.excluded_sources {
+            term_groups.push((Occur::MustNot, format!("source: {}", source), vec![keyword(self.fields.source, source)]));
+        }
+        for complexity in &query.excluded_spike_complexity {
+            term_groups.push((
+                Occur::MustNot,
+                format!("spike_complexity: {}", complexity.as_str()),
+                vec![facet_term(facet::SPIKE_COMPLEXITY, complexity.as_str())],
+            ));
+        }
+        for dep in &query.excluded_spike_dependencies {
+            term_groups.push((
+                Occur::MustNot,
+                format!("spike_dependency: {}", dep),
+                vec![facet_term(facet::SPIKE_DEPENDENCY, &normalize_keyword(dep))],
+            ));
+        }
+
+        for (occur, description, mut terms) in term_groups {
+            let query: Box<dyn Query> = if terms.len() == 1 {
+                Box::new(TermQuery::new(terms.remove(0), IndexRecordOption::Basic))
+            } else {
+                Box::new(BooleanQuery::new(
+                    terms.into_iter()
+                        .map(|term| (Occur::Should, Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>))
+                        .collect(),
+                ))
+            };
+            clauses.push(FilterClause { occur, description, query });
+        }
+
+        if filter.min_quality.is_some() || filter.max_quality.is_some() {
+            let bound = |value: Option<f32>| value.map(|v| v.to_string()).unwrap_or_default();
+            clauses.push(FilterClause {
+                occur: Occur::Must,
+                description: format!("quality: {}..{}", bound(filter.min_quality), bound(filter.max_quality)),
+                query: Box::new(RangeQuery::new_f64_bounds(
+                    "quality_score".to_string(),
+                    filter.min_quality.map_or(Bound::Unbounded, |min| Bound::Included(min as f64)),
+                    filter.max_quality.map_or(Bound::Unbounded, |max| Bound::Included(max as f64)),
+                )),
+            });
+        }
+
+        if filter.created_after.is_some() || filter.created_before.is_some() {
+            let to_date = |dt: &chrono::DateTime<chrono::Utc>| tantivy::DateTime::from_timestamp_secs(dt.timestamp());
+            let bound = |value: Option<&chrono::DateTime<chrono::Utc>>| value.map(|v| v.to_rfc3339()).unwrap_or_default();
+            clauses.push(FilterClause {
+                occur: Occur::Must,
+                description: format!(
+                    "created: {}..{}",
+                    bound(filter.created_after.as_ref()),
+                    bound(filter.created_before.as_ref()),
+                ),
+                query: Box::new(RangeQuery::new_date_bounds(
+                    "created_at".to_string(),
+                    filter.created_after.as_ref().map_or(Bound::Unbounded, |after| Bound::Included(to_date(after))),
+                    filter.created_before.as_ref().map_or(Bound::Unbounded, |before| Bound::Excluded(to_date(before))),
+                )),
+            });
+        }
+
+        clauses
+    }
+
+    /// Execute a query and turn the top documents into index hits
+    ///
+    /// Matched fields and snippets are derived from `text_query` only, so that
+    /// filter clauses do not show up as matches. `filters` are the exact clauses
+    /// already contained in `query`, used to describe them in explanations.
+    fn execute(
+        &self,
+        query: &dyn Query,
+        text_query: Option<&dyn Query>,
+        filters: &[FilterClause],
+        limit: usize,
+        with_facets: bool,
+        explain: bool,
+    ) -> Result<SearchResults<IndexHit>> {
+        let searcher = self.searcher();
+
+        let mut facet_collector = FacetCollector::for_field("facets");
+        if with_facets {
+            for dimension in facet::ALL {
+                facet_collector.add_facet(Facet::from_path([*dimension]));
+            }
+        }
+
+        let (top_docs, total_hits, facet_counts) = searcher
+            .search(query, &(TopDocs::with_limit(limit), Count, facet_collector))
+            .context("Failed to execute search")?;
+
+        let mut facets = FacetCounts::new();
+        if with_facets {
+            for dimension in facet::ALL {
+                let values: std::collections::BTreeMap<String, u64> = facet_counts
+                    .get(Facet::from_path([*dimension]))
+                    .filter_map(|(facet, count)| facet.to_path().last().map(|v| (v.to_string(), count)))
+                    .collect();
+                if !values.is_empty() {
+                    facets.insert(dimension.to_string(), values);
+                }
+            }
+        }
+
+        let mut terms = Vec::new();
+        if let Some(text_query) = text_query {
+            text_query.query_terms(&mut |term, _| terms.push(term.clone()));
+        }
+
+        // CJK queries only produce terms on the bigram field, so highlight through its analyzer
+        let highlight_field = if terms.iter().any(|term| term.field() == self.fields.content_cjk) {
+            self.fields.content_cjk
+        } else {
+            self.fields.content
+        };
+
+        let snippet_generator = match text_query {
+            Some(text_query) => {
+                let mut generator = SnippetGenerator::create(&searcher, text_query, highlight_field)
+                    .context("Failed to create snippet generator")?;
+                generator.set_max_num_chars(SNIPPET_MAX_CHARS);
+                Some(generator)
+            }
+            None => None,
+        };
+
+        let mut hits = Vec::with_capacity(top_docs.len());
+        let mut addresses = Vec::with_capacity(top_docs.len());
+        for (score, doc_address) in top_docs {
+            let doc: TantivyDocument = searcher.doc(doc_address)
+                .context("Failed to retrieve document")?;
+
+            let matched_fields = self.matched_fields(&searcher, doc_address, &terms)?;
+            if let Some(hit) = self.hit_from_doc(&doc, score, matched_fields, snippet_generator.as_ref()) {
+                hits.push(hit);
+                addresses.push(doc_address);
+            }
+        }
+
+        if explain {
+            let base: Box<dyn Query> = match text_query {
+                Some(text_query) => text_query.box_clone(),
+                None => Box::new(AllQuery),
+            };
+            let filter_effects = self.filter_effects(&searcher, base.as_ref(), filters)?;
+
+            let mut leaves = Vec::new();
+            if let Some(text_query) = text_query {
+                scoring_leaves(text_query, &mut leaves);
+            }
+
+            for (rank, (hit, doc_address)) in hits.iter_mut().zip(addresses).enumerate() {
+                let mut explanation = self.explain_hit(&searcher, query, &leaves, doc_address)?;
+                explanation.score = hit.score;
+                explanation.filters = filter_effects.clone();
+                explanation.stages.push(RankingStage::Lexical { rank: rank + 1, score: hit.score });
+                hit.explanation = Some(explanation);
+            }
+        }
+
+        tracing::debug!("Search completed. Found {} of {} hits", hits.len(), total_hits);
+        Ok(SearchResults { hits, total_hits, facets })
+    }
+
+    /// Count, for each filter, the candidates of `base` it rejects on its own
+    fn filter_effects(&self, searcher: &Searcher, base: &dyn Query, filters: &[FilterClause]) -> Result<Vec<FilterEffect>> {
+        let mut effects = Vec::with_capacity(filters.len());
+
+        for filter in filters {
+            let excluded = filter.occur == Occur::MustNot;
+            // A required filter rejects what it does not match, an exclusion what it does
+            let rejected = BooleanQuery::new(vec![
+                (Occur::Must, base.box_clone()),
+                (if excluded { Occur::Must } else { Occur::MustNot }, filter.query.box_clone()),
+            ]);
+            let removed = searcher.search(&rejected, &Count)
+                .context("Failed to count filtered candidates")?;
+
+            effects.push(FilterEffect {
+                filter: filter.description.clone(),
+                excluded,
+                removed: removed as u64,
+            });
+        }
+
+        Ok(effects)
+    }
+
+    /// Break a document's score down into per-field BM25 contributions
+    ///
+    /// `leaves` are the scoring sub-queries of the text query, whose explained
+    /// values sum up to its score.
+    fn explain_hit(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        leaves: &[Box<dyn Query>],
+        doc_address: DocAddress,
+    ) -> Result<HitExplanation> {
+        let mut fields: BTreeMap<String, FieldContribution> = BTreeMap::new();
+
+        for leaf in leaves {
+            // Leaves that do not match this document contribute nothing
+            let Ok(leaf_explanation) = leaf.explain(searcher, doc_address) else {
+                continue;
+            };
+
+            let mut terms = Vec::new();
+            leaf.query_terms(&mut |term, _| terms.push(term.clone()));
+            let field = terms.first()
+                .map_or("other", |term| self.schema.get_field_name(term.field()));
+            let text = terms.iter()
+                .filter_map(|term| term.value().as_str().map(str::to_string))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let contribution = fields.entry(field.to_string()).or_insert_with(|| FieldContribution {
+                field: field.to_string(),
+                score: 0.0,
+                terms: Vec::new(),
+            });
+            contribution.score += leaf_explanation.value();
+            contribution.terms.push(TermContribution { term: text, score: leaf_explanation.value() });
+        }
+
+        let mut fields: Vec<FieldContribution> = fields.into_values().collect();
+        for field in &mut fields {
+            field.terms.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.term.cmp(&b.term)));
+        }
+        fields.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.field.cmp(&b.field)));
+
+        let tantivy = query.explain(searcher, doc_address)
+            .context("Failed to explain search hit")?;
+
+        Ok(HitExplanation {
+            fields,
+            tantivy: Some(serde_json::to_value(&tantivy).context("Failed to serialize explanation")?),
+            ..Default::default()
         })
     }
 
+    /// Determine which fields of a document contain any of the query terms
+    fn matched_fields(&self, searcher: &Searcher, doc_address: DocAddress, terms: &[Term]) -> Result<Vec<String>> {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+        let mut fields = BTreeSet::new();
+
+        for term in terms {
+            let field_name = self.schema.get_field_name(term.field());
+            if fields.contains(field_name) {
+                continue;
+            }
+
+            let inverted_index = segment_reader.inverted_index(term.field())
+                .context("Failed to open inverted index")?;
+            if let Some(mut postings) = inverted_index.read_postings(term, IndexRecordOption::Basic)
+                .context("Failed to read postings")?
+            {
+                if postings.seek(doc_address.doc_id) == doc_address.doc_id {
+                    fields.insert(field_name);
+                }
+            }
+        }
+
+        Ok(fields.into_iter().map(str::to_string).collect())
+    }
+
     /// Estimate index size (simplified)
     fn estimate_index_size(&self) -> u64 {
         // This is a rough estimate - tantivy doesn't expose detailed size info easily
@@ -460,17 +1362,222 @@ impl SearchEngine {
     }
 }
 
+/// Collect the scoring sub-queries of a query, descending into boolean clauses
+///
+/// Excluded clauses are skipped since they never contribute to a score.
+fn scoring_leaves(query: &dyn Query, leaves: &mut Vec<Box<dyn Query>>) {
+    match query.downcast_ref::<BooleanQuery>() {
+        Some(boolean) => {
+            for (occur, clause) in boolean.clauses() {
+                if *occur != Occur::MustNot {
+                    scoring_leaves(clause.as_ref(), leaves);
+                }
+            }
+        }
+        None => leaves.push(query.box_clone()),
+    }
+}
+
+/// Maximum edit distance tolerated for a query term
+///
+/// Terms of up to two characters must match exactly, terms of three to five
+/// characters allow one edit and longer terms allow two. Transpositions count
+/// as a single edit.
+pub fn fuzzy_distance(term: &str) -> u8 {
+    match term.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Whether a Levenshtein automaton accepts the whole of `text`
+fn accepts(automaton: &Levenshtein, text: &str) -> bool {
+    let mut state = automaton.start();
+    for byte in text.bytes() {
+        if !automaton.can_match(&state) {
+            return false;
+        }
+        state = automaton.accept(&state, byte);
+    }
+    automaton.is_match(&state)
+}
+
+/// Build the index schema for the current [`SEARCH_SCHEMA_VERSION`]
+fn build_schema() -> (Schema, IndexSchema) {
+    let mut schema_builder = Schema::builder();
+    let code_text = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(CODE_TOKENIZER)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    );
+
+    // Raw keyword so that deletes by term match the whole ID
+    let chunk_id = schema_builder.add_text_field("chunk_id", STRING | STORED);
+    // Keyword fields hold normalized raw values so exact filters are not split by the tokenizer
+    let chunk_type = schema_builder.add_text_field("chunk_type", STRING | FAST);
+    // Stored so that highlight snippets can be generated from it
+    let content = schema_builder.add_text_field("content", code_text.clone() | STORED);
+    // Bigram copy of the content for documents containing CJK text
+    let content_cjk = schema_builder.add_text_field(
+        "content_cjk",
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(CJK_TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        ),
+    );
+    let language = schema_builder.add_text_field("language", STRING | FAST);
+    let framework = schema_builder.add_text_field("framework", STRING | FAST);
+    let tags = schema_builder.add_text_field("tags", STRING);
+    let patterns = schema_builder.add_text_field("patterns", code_text.clone());
+    let file_path = schema_builder.add_text_field("file_path", code_text | STORED);
+    let source = schema_builder.add_text_field("source", STRING | FAST);
+    let quality_score = schema_builder.add_f64_field("quality_score", INDEXED | FAST);
+    let created_at = schema_builder.add_date_field("created_at", INDEXED | FAST);
+    let facets = schema_builder.add_facet_field("facets", FacetOptions::default());
+
+    let fields = IndexSchema {
+        chunk_id,
+        chunk_type,
+        content,
+        content_cjk,
+        language,
+        framework,
+        tags,
+        patterns,
+        file_path,
+        source,
+        quality_score,
+        created_at,
+        facets,
+    };
+
+    (schema_builder.build(), fields)
+}
+
+/// Directory holding one index generation
+fn generation_dir(index_path: &Path, generation: u64) -> PathBuf {
+    index_path.join(format!("gen-{}", generation))
+}
+
+/// Open an index generation, creating it if missing or when `fresh` is set
+fn open_generation(config: &SearchConfig, schema: &Schema, generation: u64, fresh: bool) -> Result<IndexState> {
+    let dir = generation_dir(&config.index_path, generation);
+
+    if fresh && dir.exists() {
+        // Leftover from an interrupted rebuild
+        std::fs::remove_dir_all(&dir)
+            .context("Failed to clear stale search index generation")?;
+    }
+    std::fs::create_dir_all(&dir)
+        .context("Failed to create search index generation directory")?;
+
+    let index = if dir.join("meta.json").exists() {
+        let index = Index::open_in_dir(&dir)
+            .context("Failed to open existing search index")?;
+        if index.schema() != *schema {
+            return Err(anyhow::anyhow!("Search index schema does not match generation {}", generation));
+        }
+        index
+    } else {
+        Index::create_in_dir(&dir, schema.clone())
+            .context("Failed to create search index")?
+    };
+    index.tokenizers().register(CODE_TOKENIZER, code_analyzer(&config.tokenizer));
+    index.tokenizers().register(CJK_TOKENIZER, cjk_analyzer());
+
+    let writer = index.writer(WRITER_HEAP_BYTES)
+        .context("Failed to create index writer")?;
+
+    // Readers are reloaded explicitly after each commit
+    let reader = index.reader_builder()
+        .reload_policy(tantivy::ReloadPolicy::Manual)
+        .try_into()
+        .context("Failed to create index reader")?;
+
+    Ok(IndexState {
+        generation,
+        writer: AsyncRwLock::new(writer),
+        reader,
+    })
+}
+
+async fn read_manifest(index_path: &Path) -> Result<Option<IndexManifest>> {
+    let path = index_path.join(MANIFEST_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let data = tokio::fs::read(&path).await
+        .context("Failed to read search index manifest")?;
+
+    // An unreadable manifest is treated like a schema mismatch
+    match serde_json::from_slice(&data) {
+        Ok(manifest) => Ok(Some(manifest)),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid search index manifest: {}", e);
+            Ok(Some(IndexManifest {
+                schema_version: 0,
+                generation: 0,
+                tokenizer: CodeTokenizerOptions::default(),
+            }))
+        }
+    }
+}
+
+/// Atomically replace the manifest
+async fn write_manifest(index_path: &Path, manifest: &IndexManifest) -> Result<()> {
+    let data = serde_json::to_vec_pretty(manifest)
+        .context("Failed to serialize search index manifest")?;
+
+    let tmp_path = index_path.join(format!("{}.tmp", MANIFEST_FILE));
+    tokio::fs::write(&tmp_path, data).await
+        .context("Failed to write search index manifest")?;
+    tokio::fs::rename(&tmp_path, index_path.join(MANIFEST_FILE)).await
+        .context("Failed to replace search index manifest")?;
+
+    Ok(())
+}
+
+/// Remove older generations and pre-generation index files, keeping the active one
+fn remove_stale_generations(index_path: &Path, active: u64) {
+    let active_dir = generation_dir(index_path, active);
+    let Ok(entries) = std::fs::read_dir(index_path) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path == active_dir || entry.file_name() == MANIFEST_FILE {
+            continue;
+        }
+
+        let result = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to remove stale search index data {:?}: {}", path, e);
+        }
+    }
+}
+
 /// Search statistics
 #[derive(Debug, Clone)]
 pub struct SearchStats {
     pub total_documents: u64,
     pub index_size_bytes: u64,
+    pub schema_version: u32,
+    pub generation: u64,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::chunk::{ChunkContent, ChunkMetadata, ChunkType};
+    use crate::filter::ChunkFilter;
     use tempfile::TempDir;
 
     async fn create_test_search_engine() -> (SearchEngine, TempDir) {
@@ -509,12 +1616,309 @@ mod tests {
         search_engine.commit().await.unwrap();
 
         // Search for it
-        let results = search_engine.search("testFunction", 10).await.unwrap();
-        assert!(!results.is_empty());
-        
-        // The first result should contain our content
-        assert!(results.iter().any(|c| c.id.as_str().contains("test1") || 
-                                    matches!(&c.content, ChunkContent::Code { code, .. } if code.contains("testFunction"))));
+        let hits = search_engine.search("testFunction", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk_id, chunk.id);
+    }
+
+    #[tokio::test]
+    async fn test_searches_commit_pending_writes() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        search_engine.index_chunk(&create_test_chunk("first", "export const firstHandler = 1;", "react")).await.unwrap();
+        search_engine.commit().await.unwrap();
+
+        // Writes within the commit interval are left for the next search to commit
+        let second = create_test_chunk("second", "export const secondHandler = 2;", "react");
+        search_engine.index_chunk(&second).await.unwrap();
+        assert!(!search_engine.commit_if_due().await.unwrap());
+        assert_eq!(search_engine.get_stats().total_documents, 1);
+
+        let hits = search_engine.search_hits("secondHandler", 10).await.unwrap();
+        assert_eq!(hits[0].chunk_id, second.id);
+        assert_eq!(search_engine.get_stats().total_documents, 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_hits_with_snippets() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        let chunk = create_test_chunk(
+            "hits1",
+            "export async function fetchInvoices() { return db.invoices.findMany(); }",
+            "nextjs",
+        );
+        search_engine.index_chunk(&chunk).await.unwrap();
+        search_engine.commit().await.unwrap();
+
+        let hits = search_engine.search_hits("invoices", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk_id, chunk.id);
+        assert!(hits[0].score > 0.0);
+        assert_eq!(hits[0].matched_fields, vec!["content".to_string()]);
+        assert!(hits[0].snippet.as_ref().unwrap().contains("<b>invoices</b>"));
+
+        let hits = search_engine.search_hits("nextjs", 10).await.unwrap();
+        assert_eq!(hits[0].matched_fields, vec!["framework".to_string()]);
+        assert!(hits[0].snippet.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_structured_query_with_facets() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        let mut auth = create_test_chunk("facet1", "export async function signIn() {}", "Next.js");
+        auth.metadata.tags = vec!["auth".to_string(), "server".to_string()];
+        auth.quality_score = 0.9;
+        let mut form = create_test_chunk("facet2", "export function SignInForm() {}", "next.js");
+        form.chunk_type = ChunkType::Component;
+        form.quality_score = 0.6;
+        let laravel = create_test_chunk("facet3", "Route::post('/sign-in', SignInController::class);", "laravel");
+
+        for chunk in [&auth, &form, &laravel] {
+            search_engine.index_chunk(chunk).await.unwrap();
+        }
+        search_engine.commit().await.unwrap();
+
+        // Keyword filters are exact and case-insensitive
+        let results = search_engine.query(&SearchQuery::new().framework("NEXT.JS")).await.unwrap();
+        assert_eq!(results.total_hits, 2);
+        assert_eq!(results.facet_count(facet::FRAMEWORK, "next.js"), 2);
+        assert_eq!(results.facet_count(facet::CHUNK_TYPE, "Component"), 1);
+        assert_eq!(results.facet_count(facet::TAG, "auth"), 1);
+
+        let results = search_engine.query(
+            &SearchQuery::new().text("signin").framework("next.js").tag("auth").quality_range(Some(0.8), None),
+        ).await.unwrap();
+        assert_eq!(results.hits.len(), 1);
+        assert_eq!(results.hits[0].chunk_id, auth.id);
+        assert_eq!(results.hits[0].matched_fields, vec!["content".to_string()]);
+
+        let results = search_engine.query(&SearchQuery::new().chunk_type(ChunkType::Component)).await.unwrap();
+        assert_eq!(results.hits.len(), 1);
+        assert_eq!(results.hits[0].chunk_id, form.id);
+        assert!(results.hits[0].snippet.is_none());
+
+        // Facets cover all matches even when hits are limited
+        let results = search_engine.query(&SearchQuery::new().limit(1)).await.unwrap();
+        assert_eq!(results.hits.len(), 1);
+        assert_eq!(results.total_hits, 3);
+        assert_eq!(results.facet_count(facet::LANGUAGE, "typescript"), 3);
+        assert_eq!(results.facet_count(facet::FRAMEWORK, "laravel"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_string_expands_aliases() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        let page = create_test_chunk("alias1", "export default function Page() {}", "nextjs");
+        let screen = create_test_chunk("alias2", "export function HomeScreen() {}", "react-native");
+        let component = create_test_chunk("alias3", "export function Home() {}", "react");
+        for chunk in [&page, &screen, &component] {
+            search_engine.index_chunk(chunk).await.unwrap();
+        }
+        search_engine.commit().await.unwrap();
+
+        for (query_str, expected) in [("next", &page.id), (r#""react native""#, &screen.id), ("reactjs", &component.id)] {
+            let query = crate::query_syntax::parse_query(query_str).unwrap();
+            let results = search_engine.query(&query).await.unwrap();
+            let ids: Vec<&ChunkId> = results.hits.iter().map(|hit| &hit.chunk_id).collect();
+            assert_eq!(ids, [expected], "{}", query_str);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_explain_breaks_down_scores() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        let sign_in = create_test_chunk("explain1", "export function signIn() {}", "nextjs");
+        let mut sign_out = create_test_chunk("explain2", "export function signOut() {}", "nextjs");
+        sign_out.metadata.tags.push("deprecated".to_string());
+        let laravel = create_test_chunk("explain3", "Route::post('/sign-in', SignInController::class);", "laravel");
+
+        for chunk in [&sign_in, &sign_out, &laravel] {
+            search_engine.index_chunk(chunk).await.unwrap();
+        }
+        search_engine.commit().await.unwrap();
+
+        let query = SearchQuery::new()
+            .text("signin function")
+            .framework("nextjs")
+            .exclude_tag("deprecated")
+            .explain(true);
+        let results = search_engine.query(&query).await.unwrap();
+        assert_eq!(results.hits.len(), 1);
+
+        let hit = &results.hits[0];
+        let explanation = hit.explanation.as_ref().unwrap();
+        assert_eq!(explanation.score, hit.score);
+        assert!((explanation.bm25_total() - hit.score).abs() < 1e-4);
+        assert!(explanation.field("content").unwrap().terms.iter().any(|t| t.term == "signin"));
+        assert!(explanation.field("patterns").is_some());
+        assert_eq!(explanation.stages, vec![RankingStage::Lexical { rank: 1, score: hit.score }]);
+        assert!(explanation.tantivy.is_some());
+
+        // Every text match is rejected by exactly one of the filters
+        let removed: Vec<(&str, bool, u64)> = explanation.filters.iter()
+            .map(|f| (f.filter.as_str(), f.excluded, f.removed))
+            .collect();
+        assert_eq!(removed, vec![("framework: nextjs", false, 1), ("tag: deprecated", true, 1)]);
+
+        // Explanations are only computed on request
+        let results = search_engine.query(&query.explain(false)).await.unwrap();
+        assert!(results.hits[0].explanation.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_spike_facet_filters() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        let mut properties = std::collections::HashMap::new();
+        properties.insert("complexity".to_string(), serde_json::json!(0.2));
+        let spike = LearningChunk {
+            id: ChunkId::new("spike-nextjs-auth"),
+            chunk_type: ChunkType::SpikeTemplate,
+            content: ChunkContent::Data {
+                format: "json".to_string(),
+                data: serde_json::json!({ "name": "nextjs-auth", "dependencies": [{ "name": "next-auth" }] }),
+            },
+            metadata: ChunkMetadata {
+                source: "spike-template".to_string(),
+                frameworks: vec!["nextjs".to_string()],
+                properties,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        search_engine.index_chunk(&spike).await.unwrap();
+        search_engine.index_chunk(&create_test_chunk("plain", "const x = 1;", "nextjs")).await.unwrap();
+        search_engine.commit().await.unwrap();
+
+        let results = search_engine.query(
+            &SearchQuery::new().spike_complexity(SpikeComplexity::Low).spike_dependency("next-auth"),
+        ).await.unwrap();
+        assert_eq!(results.total_hits, 1);
+        assert_eq!(results.hits[0].chunk_id, spike.id);
+        assert_eq!(results.facet_count(facet::SPIKE_DEPENDENCY, "next-auth"), 1);
+        assert_eq!(results.facet_count(facet::SOURCE, "spike-template"), 1);
+
+        let results = search_engine.query(&SearchQuery::new().spike_complexity(SpikeComplexity::High)).await.unwrap();
+        assert_eq!(results.total_hits, 0);
+    }
+
+    #[tokio::test]
+    async fn test_code_aware_tokenization() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        let mut chunk = create_test_chunk(
+            "code1",
+            "export async function getServerSideProps() { const [user, setUser] = use_state(null); }",
+            "nextjs",
+        );
+        chunk.metadata.file_path = Some("app/Http/Controllers/UserController.php".to_string());
+        search_engine.index_chunk(&chunk).await.unwrap();
+        search_engine.commit().await.unwrap();
+
+        for query in ["\"server side props\"", "getServerSideProps", "state", "controllers", "UserController"] {
+            let hits = search_engine.search_hits(query, 10).await.unwrap();
+            assert_eq!(hits.len(), 1, "no hit for {}", query);
+        }
+
+        let hits = search_engine.search_hits("\"server side props\"", 10).await.unwrap();
+        assert!(hits[0].snippet.as_ref().unwrap().contains("<b>Server</b><b>Side</b><b>Props</b>"));
+    }
+
+    #[tokio::test]
+    async fn test_japanese_search() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        let spike = LearningChunk {
+            id: ChunkId::new("spike-agenda-mongodb"),
+            chunk_type: ChunkType::SpikeTemplate,
+            content: ChunkContent::Data {
+                format: "json".to_string(),
+                data: serde_json::json!({
+                    "name": "agenda-mongodb",
+                    "description": "AgendaでMongoDBを使ったジョブ管理",
+                }),
+            },
+            ..Default::default()
+        };
+        let docs = LearningChunk {
+            id: ChunkId::new("adonis-docs"),
+            chunk_type: ChunkType::BestPractice,
+            content: ChunkContent::Documentation {
+                format: "markdown".to_string(),
+                language: Some("ja".to_string()),
+                content: "AdonisJS ルート 最小構成".to_string(),
+            },
+            ..Default::default()
+        };
+        let english = create_test_chunk("english", "job scheduling with agenda", "node");
+
+        for chunk in [&spike, &docs, &english] {
+            search_engine.index_chunk(chunk).await.unwrap();
+        }
+        search_engine.commit().await.unwrap();
+
+        let hits = search_engine.search_hits("ジョブ管理", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk_id, spike.id);
+        assert_eq!(hits[0].matched_fields, vec!["content_cjk".to_string()]);
+        assert!(hits[0].snippet.as_ref().unwrap().contains("<b>ジョブ管理</b>"));
+
+        let hits = search_engine.search_hits("ルート", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk_id, docs.id);
+
+        // Latin queries still use the code analyzer
+        let hits = search_engine.search_hits("agenda", 10).await.unwrap();
+        assert_eq!(hits.len(), 2);
+    }
+
+    #[test]
+    fn test_fuzzy_distance_scales_with_length() {
+        assert_eq!(fuzzy_distance("js"), 0);
+        assert_eq!(fuzzy_distance("auth"), 1);
+        assert_eq!(fuzzy_distance("nextjs"), 2);
+        assert_eq!(fuzzy_distance("認証"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fuzzy_search_across_fields() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        let mut tagged = create_test_chunk("tagged", "export const handler = () => null;", "laravel");
+        tagged.metadata.tags = vec!["authentication".to_string()];
+        let exact = create_test_chunk("exact", "export function authenticate(user) {}", "express");
+        let typo = create_test_chunk("typo", "export function authentcate(user) {}", "express");
+        let framework = create_test_chunk("framework", "export default function Page() {}", "nextjs");
+
+        for chunk in [&tagged, &exact, &typo, &framework] {
+            search_engine.index_chunk(chunk).await.unwrap();
+        }
+        search_engine.commit().await.unwrap();
+
+        // Exact matches rank above fuzzy ones, which report the fields they matched
+        let hits = search_engine.fuzzy_search_hits("authenticate", 10).await.unwrap();
+        let ids: Vec<&str> = hits.iter().map(|h| h.chunk_id.as_str()).collect();
+        assert_eq!(ids[0], "exact");
+        assert!(ids.contains(&"typo"));
+        assert!(hits.windows(2).all(|w| w[0].score >= w[1].score));
+
+        let typo_hit = hits.iter().find(|h| h.chunk_id.as_str() == "typo").unwrap();
+        assert_eq!(typo_hit.matched_fields, vec!["content".to_string()]);
+        assert!(typo_hit.snippet.as_deref().unwrap().contains("<b>authentcate</b>"));
+
+        // Multi-word queries match tags and frameworks with typos
+        let hits = search_engine.fuzzy_search_hits("Authentcation nextj", 10).await.unwrap();
+        let tagged_hit = hits.iter().find(|h| h.chunk_id.as_str() == "tagged").unwrap();
+        assert_eq!(tagged_hit.matched_fields, vec!["tags".to_string()]);
+        let framework_hit = hits.iter().find(|h| h.chunk_id.as_str() == "framework").unwrap();
+        assert_eq!(framework_hit.matched_fields, vec!["framework".to_string()]);
+
+        // Short terms must match exactly
+        assert!(search_engine.fuzzy_search("jx", 10).await.unwrap().is_empty());
     }
 
     #[tokio::test]
@@ -529,12 +1933,20 @@ mod tests {
         search_engine.index_chunk(&vue_chunk).await.unwrap();
         search_engine.commit().await.unwrap();
 
-        // Search by framework
-        let react_results = search_engine.search_by_framework("react", 10).await.unwrap();
-        assert!(!react_results.is_empty());
-        
-        let vue_results = search_engine.search_by_framework("vue", 10).await.unwrap();
-        assert!(!vue_results.is_empty());
+        // Search and filter by framework
+        for (framework, expected) in [("react", &react_chunk.id), ("vue", &vue_chunk.id)] {
+            let hits = search_engine.search_by_framework(framework, 10).await.unwrap();
+            let ids: Vec<&ChunkId> = hits.iter().map(|hit| &hit.chunk_id).collect();
+            assert_eq!(ids, [expected]);
+
+            let query = SearchQuery {
+                filter: ChunkFilter { frameworks: vec![framework.to_string()], ..Default::default() },
+                ..Default::default()
+            };
+            let results = search_engine.query(&query).await.unwrap();
+            let ids: Vec<&ChunkId> = results.hits.iter().map(|hit| &hit.chunk_id).collect();
+            assert_eq!(ids, [expected]);
+        }
     }
 
     #[tokio::test]
@@ -548,8 +1960,14 @@ mod tests {
         search_engine.commit().await.unwrap();
 
         // Search by pattern
-        let results = search_engine.search_by_pattern("function", 10).await.unwrap();
-        assert!(!results.is_empty());
+        let hits = search_engine.search_by_pattern("function", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk_id, chunk.id);
+
+        // Search the pattern field
+        let hits = search_engine.search_hits("patterns:function", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].matched_fields, vec!["patterns".to_string()]);
     }
 
     #[tokio::test]
@@ -562,16 +1980,27 @@ mod tests {
         search_engine.index_chunk(&chunk).await.unwrap();
         search_engine.commit().await.unwrap();
 
-        let results_before = search_engine.search("test content", 10).await.unwrap();
+        let results_before = search_engine.search_hits("test content", 10).await.unwrap();
         assert!(!results_before.is_empty());
 
         // Remove and search again
         search_engine.remove_chunk(&chunk.id).await.unwrap();
         search_engine.commit().await.unwrap();
 
-        let results_after = search_engine.search("test content", 10).await.unwrap();
-        // Results might still contain the chunk due to search result reconstruction
-        // In a full implementation, the chunk would be absent
+        let results_after = search_engine.search_hits("test content", 10).await.unwrap();
+        assert!(results_after.iter().all(|hit| hit.chunk_id != chunk.id));
+    }
+
+    #[tokio::test]
+    async fn test_reindexing_replaces_document() {
+        let (search_engine, _temp_dir) = create_test_search_engine().await;
+
+        let chunk = create_test_chunk("upsert-test", "first version", "react");
+        search_engine.index_chunk(&chunk).await.unwrap();
+        search_engine.index_chunk(&chunk).await.unwrap();
+        search_engine.commit().await.unwrap();
+
+        assert_eq!(search_engine.get_stats().total_documents, 1);
     }
 
     #[tokio::test]
@@ -588,4 +2017,88 @@ mod tests {
         let stats = search_engine.get_stats();
         assert_eq!(stats.total_documents, 2);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_schema_version_mismatch_requires_rebuild() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks = vec![
+            create_test_chunk("persisted1", "export function loadUser() {}", "nextjs"),
+            create_test_chunk("persisted2", "export function saveUser() {}", "nextjs"),
+        ];
+
+        {
+            let search_engine = SearchEngine::new(temp_dir.path()).await.unwrap();
+            assert!(!search_engine.needs_rebuild());
+            for chunk in &chunks {
+                search_engine.index_chunk(chunk).await.unwrap();
+            }
+            search_engine.commit().await.unwrap();
+        }
+
+        // Reopening with the same schema keeps the index
+        {
+            let search_engine = SearchEngine::new(temp_dir.path()).await.unwrap();
+            assert!(!search_engine.needs_rebuild());
+            assert_eq!(search_engine.get_stats().total_documents, 2);
+        }
+
+        // Simulate an index written by an older schema version
+        let manifest_path = temp_dir.path().join(MANIFEST_FILE);
+        let mut manifest: IndexManifest =
+            serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+        manifest.schema_version = SEARCH_SCHEMA_VERSION + 1;
+        std::fs::write(&manifest_path, serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+        let search_engine = SearchEngine::new(temp_dir.path()).await.unwrap();
+        assert!(search_engine.needs_rebuild());
+        assert_eq!(search_engine.get_stats().total_documents, 0);
+
+        let indexed = search_engine.rebuild(move || chunks.into_iter().map(Ok)).await.unwrap();
+        assert_eq!(indexed, 2);
+        assert!(!search_engine.needs_rebuild());
+
+        let stats = search_engine.get_stats();
+        assert_eq!(stats.total_documents, 2);
+        assert_eq!(stats.schema_version, SEARCH_SCHEMA_VERSION);
+        assert_eq!(stats.generation, manifest.generation + 2);
+
+        let hits = search_engine.search_hits("saveUser", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk_id.as_str(), "persisted2");
+
+        let manifest: IndexManifest =
+            serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+        assert_eq!(manifest.schema_version, SEARCH_SCHEMA_VERSION);
+        assert_eq!(manifest.generation, stats.generation);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_swaps_generation() {
+        let (search_engine, temp_dir) = create_test_search_engine().await;
+
+        search_engine.index_chunk(&create_test_chunk("stale", "removed from storage", "react")).await.unwrap();
+        search_engine.commit().await.unwrap();
+
+        let chunks = vec![create_test_chunk("fresh", "rebuilt from storage", "react")];
+        search_engine.rebuild(move || chunks.into_iter().map(Ok)).await.unwrap();
+
+        // Only the rebuilt data is visible and the previous generation is gone
+        let hits = search_engine.search_hits("storage", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].chunk_id.as_str(), "fresh");
+        assert!(!generation_dir(temp_dir.path(), 0).exists());
+        assert!(generation_dir(temp_dir.path(), 1).exists());
+
+        // Writes go to the new generation
+        search_engine.index_chunk(&create_test_chunk("later", "added after rebuild", "react")).await.unwrap();
+        search_engine.commit().await.unwrap();
+        assert_eq!(search_engine.get_stats().total_documents, 2);
+
+        // A failing source leaves the active generation untouched
+        let failing = vec![Err(anyhow::anyhow!("corrupt chunk"))];
+        assert!(search_engine.rebuild(move || failing).await.is_err());
+        assert!(!search_engine.is_rebuilding());
+        assert_eq!(search_engine.get_stats().generation, 1);
+        assert_eq!(search_engine.get_stats().total_documents, 2);
+    }
+}
@@ -0,0 +1,379 @@
+//! Similarity weights and their offline fitting
+//!
+//! `find_similar` and relationship rebuilds score a candidate by a weighted
+//! sum of four component similarities (content, structural, semantic,
+//! framework). The weights default to 0.3/0.3/0.25/0.15 and can be fitted
+//! from Helpful/NotHelpful feedback on suggested pairs with a logistic
+//! regression over the components. Fitted coefficients are kept
+//! non-negative and normalized to sum to 1, so combined scores stay in the
+//! 0.0 - 1.0 range the similarity threshold is expressed in.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Component similarities of a chunk pair, each in 0.0 - 1.0
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityComponents {
+    pub content: f32,
+    pub structural: f32,
+    pub semantic: f32,
+    pub framework: f32,
+}
+
+impl SimilarityComponents {
+    fn features(&self) -> [f64; 4] {
+        [self.content as f64, self.structural as f64, self.semantic as f64, self.framework as f64]
+    }
+}
+
+/// Weights combining component similarities into one score
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityWeights {
+    pub content: f32,
+    pub structural: f32,
+    pub semantic: f32,
+    pub framework: f32,
+}
+
+impl Default for SimilarityWeights {
+    fn default() -> Self {
+        Self {
+            content: 0.3,
+            structural: 0.3,
+            semantic: 0.25,
+            framework: 0.15,
+        }
+    }
+}
+
+impl SimilarityWeights {
+    /// Weighted sum of the components
+    pub fn combine(&self, components: &SimilarityComponents) -> f32 {
+        components.content * self.content
+            + components.structural * self.structural
+            + components.semantic * self.semantic
+            + components.framework * self.framework
+    }
+
+    fn from_coefficients(coefficients: [f64; 4]) -> Option<Self> {
+        let total: f64 = coefficients.iter().sum();
+        if total <= f64::EPSILON {
+            return None;
+        }
+        Some(Self {
+            content: (coefficients[0] / total) as f32,
+            structural: (coefficients[1] / total) as f32,
+            semantic: (coefficients[2] / total) as f32,
+            framework: (coefficients[3] / total) as f32,
+        })
+    }
+}
+
+/// Number of replaced weight sets kept for rollback
+pub const MAX_WEIGHT_HISTORY: usize = 10;
+
+/// Active similarity weights and the ones they replaced, newest last
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityWeightState {
+    pub current: SimilarityWeights,
+    pub previous: Vec<SimilarityWeights>,
+}
+
+impl SimilarityWeightState {
+    /// Start from configured weights
+    pub fn new(current: SimilarityWeights) -> Self {
+        Self { current, previous: Vec::new() }
+    }
+
+    /// Replace the active weights, remembering the old ones
+    pub fn apply(&mut self, weights: SimilarityWeights) {
+        self.previous.push(std::mem::replace(&mut self.current, weights));
+        if self.previous.len() > MAX_WEIGHT_HISTORY {
+            self.previous.remove(0);
+        }
+    }
+
+    /// Restore the weights active before the last `apply`
+    pub fn rollback(&mut self) -> Option<SimilarityWeights> {
+        let previous = self.previous.pop()?;
+        self.current = previous;
+        Some(previous)
+    }
+}
+
+/// Component similarities of a suggested pair and whether it was helpful
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityExample {
+    pub components: SimilarityComponents,
+    pub helpful: bool,
+}
+
+/// Settings of the weight fitting routine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightFitOptions {
+    /// Gradient descent step size
+    pub learning_rate: f64,
+    /// Passes over the training examples
+    pub epochs: usize,
+    /// L2 penalty on the coefficients
+    pub l2: f64,
+    /// Share of the examples held out for evaluation
+    pub validation_fraction: f64,
+    /// Score at which a pair counts as similar when measuring accuracy
+    pub threshold: f32,
+    /// Apply the fitted weights unless they rank the held-out pairs worse
+    pub apply: bool,
+}
+
+impl Default for WeightFitOptions {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.5,
+            epochs: 500,
+            l2: 0.001,
+            validation_fraction: 0.2,
+            threshold: 0.6,
+            apply: true,
+        }
+    }
+}
+
+/// How well a set of weights separates helpful from unhelpful pairs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityMetrics {
+    /// Number of evaluated pairs
+    pub examples: usize,
+    /// Probability that a helpful pair outscores an unhelpful one
+    pub auc: f32,
+    /// Share of pairs on the right side of the threshold
+    pub accuracy: f32,
+    /// Mean combined score of helpful pairs
+    pub mean_helpful: f32,
+    /// Mean combined score of unhelpful pairs
+    pub mean_not_helpful: f32,
+}
+
+/// Outcome of fitting similarity weights
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightFitReport {
+    /// Weights in use before fitting
+    pub previous: SimilarityWeights,
+    /// Fitted weights
+    pub fitted: SimilarityWeights,
+    /// Metrics of the previous weights on the held-out pairs
+    pub before: SimilarityMetrics,
+    /// Metrics of the fitted weights on the held-out pairs
+    pub after: SimilarityMetrics,
+    /// Number of pairs used for fitting
+    pub training_examples: usize,
+    /// Whether the fitted weights replaced the previous ones
+    pub applied: bool,
+}
+
+impl WeightFitReport {
+    /// Check whether the fitted weights rank held-out pairs at least as well
+    pub fn improved(&self) -> bool {
+        self.after.auc >= self.before.auc
+    }
+}
+
+/// Score every example with `weights` and measure the separation
+pub fn evaluate_weights(
+    weights: &SimilarityWeights,
+    examples: &[SimilarityExample],
+    threshold: f32,
+) -> SimilarityMetrics {
+    let scored: Vec<(f32, bool)> = examples.iter()
+        .map(|example| (weights.combine(&example.components), example.helpful))
+        .collect();
+    let helpful: Vec<f32> = scored.iter().filter(|(_, helpful)| *helpful).map(|(score, _)| *score).collect();
+    let not_helpful: Vec<f32> = scored.iter().filter(|(_, helpful)| !*helpful).map(|(score, _)| *score).collect();
+
+    let mean = |scores: &[f32]| if scores.is_empty() { 0.0 } else { scores.iter().sum::<f32>() / scores.len() as f32 };
+
+    // Pairwise AUC, counting ties as half
+    let mut wins = 0.0;
+    for positive in &helpful {
+        for negative in &not_helpful {
+            wins += match positive.total_cmp(negative) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 0.0,
+            };
+        }
+    }
+    let pairs = helpful.len() * not_helpful.len();
+    let correct = scored.iter().filter(|(score, helpful)| (*score >= threshold) == *helpful).count();
+
+    SimilarityMetrics {
+        examples: scored.len(),
+        auc: if pairs == 0 { 0.5 } else { wins / pairs as f32 },
+        accuracy: if scored.is_empty() { 0.0 } else { correct as f32 / scored.len() as f32 },
+        mean_helpful: mean(&helpful),
+        mean_not_helpful: mean(&not_helpful),
+    }
+}
+
+/// Fit similarity weights to labelled pairs with a logistic regression
+///
+/// Every `1 / validation_fraction`-th example is held out, and both the
+/// current and the fitted weights are evaluated on those (on all examples
+/// when too few are held out to contain both labels). `applied` is set when
+/// `options.apply` is on and the fitted weights do not lower the AUC;
+/// installing them is left to the caller.
+pub fn fit_similarity_weights(
+    current: &SimilarityWeights,
+    examples: &[SimilarityExample],
+    options: &WeightFitOptions,
+) -> Result<WeightFitReport> {
+    if !examples.iter().any(|example| example.helpful) || examples.iter().all(|example| example.helpful) {
+        return Err(anyhow::anyhow!("Fitting similarity weights needs both Helpful and NotHelpful pairs"));
+    }
+
+    let stride = if options.validation_fraction > 0.0 {
+        (1.0 / options.validation_fraction).round().max(2.0) as usize
+    } else {
+        usize::MAX
+    };
+    let (mut training, mut validation) = (Vec::new(), Vec::new());
+    for (index, example) in examples.iter().enumerate() {
+        if index % stride == stride - 1 {
+            validation.push(*example);
+        } else {
+            training.push(*example);
+        }
+    }
+    let has_both = |set: &[SimilarityExample]| {
+        set.iter().any(|example| example.helpful) && set.iter().any(|example| !example.helpful)
+    };
+    if !has_both(&training) {
+        training = examples.to_vec();
+    }
+    if !has_both(&validation) {
+        validation = examples.to_vec();
+    }
+
+    let coefficients = logistic_regression(&training, options);
+    let fitted = SimilarityWeights::from_coefficients(coefficients).unwrap_or(*current);
+
+    let before = evaluate_weights(current, &validation, options.threshold);
+    let after = evaluate_weights(&fitted, &validation, options.threshold);
+    let mut report = WeightFitReport {
+        previous: *current,
+        fitted,
+        before,
+        after,
+        training_examples: training.len(),
+        applied: false,
+    };
+    report.applied = options.apply && fitted != *current && report.improved();
+
+    tracing::info!(
+        "Fitted similarity weights {:?} on {} pairs: AUC {:.3} -> {:.3}",
+        fitted,
+        report.training_examples,
+        before.auc,
+        after.auc
+    );
+    Ok(report)
+}
+
+/// Non-negative logistic regression coefficients by projected gradient descent
+fn logistic_regression(examples: &[SimilarityExample], options: &WeightFitOptions) -> [f64; 4] {
+    let mut coefficients = [0.25; 4];
+    let mut bias = 0.0;
+    let n = examples.len().max(1) as f64;
+
+    for _ in 0..options.epochs {
+        let mut gradient = [0.0; 4];
+        let mut bias_gradient = 0.0;
+
+        for example in examples {
+            let features = example.components.features();
+            let z = bias + coefficients.iter().zip(&features).map(|(w, x)| w * x).sum::<f64>();
+            let error = 1.0 / (1.0 + (-z).exp()) - if example.helpful { 1.0 } else { 0.0 };
+            for (g, x) in gradient.iter_mut().zip(&features) {
+                *g += error * x;
+            }
+            bias_gradient += error;
+        }
+
+        for (w, g) in coefficients.iter_mut().zip(&gradient) {
+            *w = (*w - options.learning_rate * (g / n + options.l2 * *w)).max(0.0);
+        }
+        bias -= options.learning_rate * bias_gradient / n;
+    }
+
+    coefficients
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example(content: f32, framework: f32, helpful: bool) -> SimilarityExample {
+        SimilarityExample {
+            components: SimilarityComponents { content, structural: 0.5, semantic: 0.5, framework },
+            helpful,
+        }
+    }
+
+    #[test]
+    fn test_default_weights_match_legacy_scoring() {
+        let weights = SimilarityWeights::default();
+        let components = SimilarityComponents { content: 1.0, structural: 0.5, semantic: 0.4, framework: 1.0 };
+        assert!((weights.combine(&components) - (0.3 + 0.15 + 0.1 + 0.15)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fit_learns_informative_component() {
+        // Helpfulness follows the framework match, content is noise
+        let examples: Vec<SimilarityExample> = (0..40)
+            .map(|i| {
+                let helpful = i % 2 == 0;
+                let content = ((i * 7) % 10) as f32 / 10.0;
+                let framework = if helpful { 0.9 } else { 0.1 };
+                example(content, framework, helpful)
+            })
+            .collect();
+
+        let current = SimilarityWeights::default();
+        let report = fit_similarity_weights(&current, &examples, &WeightFitOptions::default()).unwrap();
+
+        assert!(report.fitted.framework > report.fitted.content);
+        let total = report.fitted.content + report.fitted.structural + report.fitted.semantic + report.fitted.framework;
+        assert!((total - 1.0).abs() < 1e-4);
+        assert!(report.after.auc >= report.before.auc);
+        assert_eq!(report.after.auc, 1.0);
+        assert!(report.applied);
+        assert_eq!(report.after.examples + report.training_examples, examples.len());
+    }
+
+    #[test]
+    fn test_fit_requires_both_labels() {
+        let examples = vec![example(0.9, 0.9, true), example(0.8, 0.7, true)];
+        assert!(fit_similarity_weights(&SimilarityWeights::default(), &examples, &WeightFitOptions::default()).is_err());
+
+        let metrics = evaluate_weights(&SimilarityWeights::default(), &examples, 0.6);
+        assert_eq!(metrics.auc, 0.5);
+        assert_eq!(metrics.examples, 2);
+    }
+
+    #[test]
+    fn test_weight_state_rolls_back() {
+        let mut state = SimilarityWeightState::new(SimilarityWeights::default());
+        assert!(state.rollback().is_none());
+
+        let learned = SimilarityWeights { content: 0.1, structural: 0.1, semantic: 0.1, framework: 0.7 };
+        for _ in 0..=MAX_WEIGHT_HISTORY {
+            state.apply(learned);
+        }
+        assert_eq!(state.previous.len(), MAX_WEIGHT_HISTORY);
+        assert_eq!(state.current, learned);
+
+        let mut state = SimilarityWeightState::new(SimilarityWeights::default());
+        state.apply(learned);
+        assert_eq!(state.rollback(), Some(SimilarityWeights::default()));
+        assert_eq!(state.current, SimilarityWeights::default());
+    }
+}
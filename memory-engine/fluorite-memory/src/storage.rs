@@ -4,7 +4,7 @@
 //! indexing, and efficient retrieval. Uses sled for ACID transactions and
 //! lz4 compression for space efficiency.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -15,7 +15,15 @@ use serde::{Deserialize, Serialize};
 use sled::{Db, Tree};
 use tokio::sync::RwLock as AsyncRwLock;
 
-use crate::chunk::{ChunkId, LearningChunk, ChunkType};
+use crate::alias::{alias_key, spelling, AliasTable};
+use crate::cache::HotSetEntry;
+use crate::chunk::{
+    ChunkContent, ChunkId, ChunkMetadata, ChunkRelation, ChunkType, LearningChunk,
+    RelationProvenance, RelationType,
+};
+use crate::feedback::FeedbackStore;
+use crate::filter::ChunkFilter;
+use crate::pattern_store::PatternStore;
 
 /// Prefix for different data types in the database
 const CHUNK_PREFIX: &[u8] = b"chunk:";
@@ -24,6 +32,25 @@ const META_PREFIX: &[u8] = b"meta:";
 const FRAMEWORK_PREFIX: &[u8] = b"framework:";
 const PATTERN_PREFIX: &[u8] = b"pattern:";
 
+/// Layout version of stored chunks
+///
+/// Bump whenever `LearningChunk` changes shape and add a migration from the
+/// previous layout to `migrate_chunks`.
+const STORAGE_FORMAT_VERSION: &str = "1.1.0";
+
+/// Layout written before relationships carried provenance
+const LEGACY_FORMAT_VERSION: &str = "1.0.0";
+
+/// Metadata key holding the last persisted cache hot set
+const HOT_SET_KEY: &str = "hot_set_snapshot";
+
+/// Metadata key marking that framework index keys use lowercase spellings
+const FRAMEWORK_INDEX_LAYOUT_KEY: &str = "framework_index_spellings";
+
+/// Attempts and delay between them when opening a database that is still locked
+const OPEN_LOCK_RETRIES: u32 = 50;
+const OPEN_LOCK_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(20);
+
 /// Database metadata for tracking statistics and versions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct DatabaseMetadata {
@@ -62,6 +89,8 @@ pub struct HybridStorage {
     framework_index: Tree,
     pattern_index: Tree,
     metadata_tree: Tree,
+    // Resolves framework aliases in framework lookups
+    aliases: Arc<AliasTable>,
 }
 
 impl HybridStorage {
@@ -69,8 +98,12 @@ impl HybridStorage {
     pub async fn new(storage_path: &Path, compression_level: u32) -> Result<Self> {
         tracing::info!("Initializing hybrid storage at: {:?}", storage_path);
 
-        let db = sled::open(storage_path)
-            .context("Failed to open sled database")?;
+        let db = {
+            let storage_path = storage_path.to_path_buf();
+            tokio::task::spawn_blocking(move || open_db(&storage_path))
+                .await
+                .context("Database open task failed")??
+        };
 
         // Open different trees for organized data storage
         let chunks_tree = db.open_tree("chunks")
@@ -98,13 +131,105 @@ impl HybridStorage {
             framework_index,
             pattern_index,
             metadata_tree,
+            aliases: Arc::new(AliasTable::builtin()),
         };
 
+        storage.migrate_chunks().await?;
+        storage.migrate_framework_index()?;
+
         tracing::info!("Hybrid storage initialized successfully");
         Ok(storage)
     }
 
+    /// Rewrite chunks stored with an older layout
+    async fn migrate_chunks(&self) -> Result<()> {
+        let version = self.metadata.read().version.clone();
+        if version == STORAGE_FORMAT_VERSION {
+            return Ok(());
+        }
+        if version != LEGACY_FORMAT_VERSION {
+            return Err(anyhow::anyhow!("Unsupported storage format version: {}", version));
+        }
+
+        tracing::info!("Migrating stored chunks from format {} to {}", version, STORAGE_FORMAT_VERSION);
+
+        let mut batch = sled::Batch::default();
+        let mut migrated = 0;
+        for result in self.chunks_tree.scan_prefix(CHUNK_PREFIX) {
+            let (key, compressed_data) = result.context("Failed to iterate chunks")?;
+            let decompressed = lz4_flex::decompress_size_prepended(&compressed_data)
+                .context("Failed to decompress chunk data")?;
+            let legacy: LegacyChunk = bincode::deserialize(&decompressed)
+                .context("Failed to deserialize legacy chunk")?;
+
+            let serialized = bincode::serialize(&LearningChunk::from(legacy))
+                .context("Failed to serialize chunk")?;
+            batch.insert(key, lz4_flex::compress_prepend_size(&serialized));
+            migrated += 1;
+        }
+
+        self.chunks_tree.apply_batch(batch)
+            .context("Failed to write migrated chunks")?;
+        self.metadata.write().version = STORAGE_FORMAT_VERSION.to_string();
+        self.save_metadata().await?;
+
+        tracing::info!("Migrated {} chunks", migrated);
+        Ok(())
+    }
+
+    /// Rekey framework index entries written with their original spelling
+    ///
+    /// Lookups fetch the lowercase spellings the alias table knows, so entries
+    /// stored as `Next.js` are merged into `next.js`.
+    fn migrate_framework_index(&self) -> Result<()> {
+        if self.metadata_tree.contains_key(FRAMEWORK_INDEX_LAYOUT_KEY)
+            .context("Failed to query database")? {
+            return Ok(());
+        }
+
+        let mut rekeyed: HashMap<String, Vec<ChunkId>> = HashMap::new();
+        let mut stale = Vec::new();
+        for entry in self.framework_index.scan_prefix(FRAMEWORK_PREFIX) {
+            let (key, chunk_ids_data) = entry.context("Failed to iterate framework index")?;
+            let name = String::from_utf8_lossy(&key[FRAMEWORK_PREFIX.len()..]).into_owned();
+            let framework_key = framework_index_key(&name);
+            if framework_key.as_bytes() == key.as_ref() {
+                continue;
+            }
+
+            let chunk_ids: Vec<ChunkId> = bincode::deserialize(&chunk_ids_data).unwrap_or_default();
+            rekeyed.entry(framework_key).or_default().extend(chunk_ids);
+            stale.push(key);
+        }
+
+        for key in &stale {
+            self.framework_index.remove(key)
+                .context("Failed to update framework index")?;
+        }
+        for (framework_key, chunk_ids) in &rekeyed {
+            Self::append_to_index(&self.framework_index, framework_key, chunk_ids)?;
+        }
+        self.metadata_tree.insert(FRAMEWORK_INDEX_LAYOUT_KEY, &[])
+            .context("Failed to save framework index layout")?;
+
+        if !stale.is_empty() {
+            tracing::info!("Rekeyed {} framework index entries", stale.len());
+        }
+        Ok(())
+    }
+
+    /// Use an alias table to resolve framework names in lookups
+    pub fn with_aliases(mut self, aliases: Arc<AliasTable>) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
     /// Store a learning chunk with compression
+    ///
+    /// Replacing a chunk only touches the framework and pattern index entries
+    /// it gained or lost, so rewriting a chunk with unchanged metadata leaves
+    /// the indexes alone. Those indexes live in separate trees and are updated
+    /// after the chunk itself is written, so only the chunk write is atomic.
     pub async fn store_chunk(&self, chunk: &LearningChunk) -> Result<()> {
         tracing::debug!("Storing chunk: {}", chunk.id);
 
@@ -116,23 +241,39 @@ impl HybridStorage {
         
         // Store in chunks tree
         let key = self.make_chunk_key(&chunk.id);
-        self.chunks_tree.insert(&key, compressed.as_slice())
+        let previous = self.chunks_tree.insert(&key, compressed.as_slice())
             .context("Failed to insert chunk into database")?;
 
+        let previous_metadata = match &previous {
+            Some(bytes) => self.indexed_metadata(&chunk.id, bytes).await?,
+            None => ChunkMetadata::default(),
+        };
+        let removed = HashSet::from([chunk.id.clone()]);
+
         // Update framework index
-        for framework in &chunk.metadata.frameworks {
-            self.add_to_framework_index(framework, &chunk.id).await?;
+        let previous_frameworks = framework_index_keys(&previous_metadata.frameworks);
+        let frameworks = framework_index_keys(&chunk.metadata.frameworks);
+        for framework_key in index_changes(&previous_frameworks, &frameworks) {
+            Self::remove_from_index(&self.framework_index, framework_key, &removed)?;
+        }
+        for framework_key in index_changes(&frameworks, &previous_frameworks) {
+            Self::append_to_index(&self.framework_index, framework_key, std::slice::from_ref(&chunk.id))?;
         }
 
         // Update pattern index
-        for pattern in &chunk.metadata.patterns {
+        for pattern in index_changes(&previous_metadata.patterns, &chunk.metadata.patterns) {
+            Self::remove_from_index(&self.pattern_index, &format!("pattern:{}", pattern), &removed)?;
+        }
+        for pattern in index_changes(&chunk.metadata.patterns, &previous_metadata.patterns) {
             self.add_to_pattern_index(pattern, &chunk.id).await?;
         }
 
         // Update metadata and statistics
         {
             let mut metadata = self.metadata.write();
-            metadata.total_chunks += 1;
+            if previous.is_none() {
+                metadata.total_chunks += 1;
+            }
             metadata.compression_stats.total_uncompressed_bytes += serialized.len() as u64;
             metadata.compression_stats.total_compressed_bytes += compressed.len() as u64;
             metadata.compression_stats.compression_ratio = 
@@ -151,51 +292,230 @@ impl HybridStorage {
     pub async fn get_chunk(&self, chunk_id: &ChunkId) -> Result<Option<LearningChunk>> {
         tracing::debug!("Retrieving chunk: {}", chunk_id);
 
-        let key = self.make_chunk_key(chunk_id);
-        
-        if let Some(compressed_data) = self.chunks_tree.get(&key)
-            .context("Failed to query database")? {
-            
-            // Decompress the data
-            let decompressed = lz4_flex::decompress_size_prepended(&compressed_data)
-                .context("Failed to decompress chunk data")?;
-            
-            // Deserialize the chunk
-            let chunk: LearningChunk = bincode::deserialize(&decompressed)
-                .context("Failed to deserialize chunk")?;
-            
+        let chunk = self.read_chunk(chunk_id)?;
+
+        if chunk.is_some() {
             tracing::debug!("Chunk retrieved successfully: {}", chunk_id);
-            Ok(Some(chunk))
         } else {
             tracing::debug!("Chunk not found: {}", chunk_id);
-            Ok(None)
         }
+        Ok(chunk)
+    }
+
+    /// Store several chunks with a single atomic write to the chunks tree
+    ///
+    /// The framework and pattern indexes are updated once the batch has been
+    /// applied and are not part of it: a crash in between leaves the chunks
+    /// written but their index entries stale.
+    pub async fn store_chunks_batch(&self, chunks: &[LearningChunk]) -> Result<()> {
+        tracing::debug!("Storing batch of {} chunks", chunks.len());
+
+        let mut batch = sled::Batch::default();
+        let mut new_chunks = 0u64;
+        let mut uncompressed_bytes = 0u64;
+        let mut compressed_bytes = 0u64;
+        // Last version of each chunk in the batch, which is the one that ends up stored
+        let mut latest: HashMap<&ChunkId, &LearningChunk> = HashMap::new();
+
+        for chunk in chunks {
+            let serialized = bincode::serialize(chunk)
+                .context("Failed to serialize chunk")?;
+            let compressed = lz4_flex::compress_prepend_size(&serialized);
+
+            batch.insert(self.make_chunk_key(&chunk.id), compressed.as_slice());
+            latest.insert(&chunk.id, chunk);
+
+            uncompressed_bytes += serialized.len() as u64;
+            compressed_bytes += compressed.len() as u64;
+        }
+
+        // Index entries each chunk gains or loses relative to its stored version
+        let mut framework_added: HashMap<String, Vec<ChunkId>> = HashMap::new();
+        let mut framework_removed: HashMap<String, HashSet<ChunkId>> = HashMap::new();
+        let mut pattern_added: HashMap<&str, Vec<ChunkId>> = HashMap::new();
+        let mut pattern_removed: HashMap<&str, HashSet<ChunkId>> = HashMap::new();
+        let mut previous_versions = Vec::with_capacity(latest.len());
+
+        for (chunk_id, chunk) in &latest {
+            let previous = self.chunks_tree.get(self.make_chunk_key(chunk_id))
+                .context("Failed to query database")?;
+            let previous_metadata = match &previous {
+                Some(bytes) => self.indexed_metadata(chunk_id, bytes).await?,
+                None => {
+                    new_chunks += 1;
+                    ChunkMetadata::default()
+                }
+            };
+            previous_versions.push((*chunk_id, *chunk, previous_metadata));
+        }
+
+        for (chunk_id, chunk, previous) in &previous_versions {
+            let frameworks = framework_index_keys(&chunk.metadata.frameworks);
+            let previous_frameworks = framework_index_keys(&previous.frameworks);
+            for framework_key in index_changes(&frameworks, &previous_frameworks) {
+                framework_added.entry(framework_key.to_string()).or_default().push((*chunk_id).clone());
+            }
+            for framework_key in index_changes(&previous_frameworks, &frameworks) {
+                framework_removed.entry(framework_key.to_string()).or_default().insert((*chunk_id).clone());
+            }
+            for pattern in index_changes(&chunk.metadata.patterns, &previous.patterns) {
+                pattern_added.entry(pattern).or_default().push((*chunk_id).clone());
+            }
+            for pattern in index_changes(&previous.patterns, &chunk.metadata.patterns) {
+                pattern_removed.entry(pattern).or_default().insert((*chunk_id).clone());
+            }
+        }
+
+        self.chunks_tree.apply_batch(batch)
+            .context("Failed to apply chunk batch")?;
+
+        for (framework_key, chunk_ids) in framework_removed {
+            Self::remove_from_index(&self.framework_index, &framework_key, &chunk_ids)?;
+        }
+        for (framework_key, chunk_ids) in framework_added {
+            Self::append_to_index(&self.framework_index, &framework_key, &chunk_ids)?;
+        }
+        for (pattern, chunk_ids) in pattern_removed {
+            Self::remove_from_index(&self.pattern_index, &format!("pattern:{}", pattern), &chunk_ids)?;
+        }
+        for (pattern, chunk_ids) in pattern_added {
+            Self::append_to_index(&self.pattern_index, &format!("pattern:{}", pattern), &chunk_ids)?;
+        }
+
+        {
+            let mut metadata = self.metadata.write();
+            metadata.total_chunks += new_chunks;
+            metadata.compression_stats.total_uncompressed_bytes += uncompressed_bytes;
+            metadata.compression_stats.total_compressed_bytes += compressed_bytes;
+            metadata.compression_stats.compression_ratio =
+                metadata.compression_stats.total_compressed_bytes as f64 /
+                metadata.compression_stats.total_uncompressed_bytes.max(1) as f64;
+        }
+        self.save_metadata().await?;
+
+        Ok(())
+    }
+
+    /// Check whether a chunk exists without decoding it
+    pub fn contains_chunk(&self, chunk_id: &ChunkId) -> Result<bool> {
+        self.chunks_tree.contains_key(self.make_chunk_key(chunk_id))
+            .context("Failed to query database")
+    }
+
+    /// Open the trees holding persisted pattern analyzer state
+    pub fn pattern_store(&self) -> Result<PatternStore> {
+        PatternStore::open(&self.db, self.metadata_tree.clone())
+    }
+
+    /// Open the tree holding per-chunk feedback
+    pub fn feedback_store(&self) -> Result<FeedbackStore> {
+        FeedbackStore::open(&self.db)
+    }
+
+    /// Iterate over every stored chunk in key order
+    ///
+    /// The iterator does not borrow the storage, so it can be moved to a
+    /// blocking thread; it reads from disk as it advances.
+    pub fn scan_chunks(&self) -> impl Iterator<Item = Result<LearningChunk>> + Send + 'static {
+        self.chunks_tree.scan_prefix(CHUNK_PREFIX).map(|result| {
+            let (_, compressed_data) = result.context("Failed to iterate chunks")?;
+            Self::decode_chunk(&compressed_data)
+        })
+    }
+
+    /// Get all chunks matching a filter
+    ///
+    /// Framework criteria are resolved through the framework index; other
+    /// criteria fall back to a full scan.
+    pub async fn find_chunks(&self, filter: &ChunkFilter) -> Result<Vec<LearningChunk>> {
+        let mut chunks = Vec::new();
+
+        // Stored chunks carry canonical framework names
+        let filter = &ChunkFilter {
+            excluded_frameworks: self.aliases.normalize_all(&filter.excluded_frameworks),
+            ..filter.clone()
+        };
+
+        if filter.frameworks.is_empty() {
+            for chunk in self.scan_chunks() {
+                let chunk = chunk?;
+                if filter.matches(&chunk) {
+                    chunks.push(chunk);
+                }
+            }
+        } else {
+            // Framework membership is resolved through aliases by the index lookup
+            let rest = ChunkFilter { frameworks: Vec::new(), ..filter.clone() };
+            let mut seen = HashSet::new();
+            for framework in &filter.frameworks {
+                for chunk in self.get_chunks_by_framework(framework).await? {
+                    if seen.insert(chunk.id.clone()) && rest.matches(&chunk) {
+                        chunks.push(chunk);
+                    }
+                }
+            }
+        }
+
+        tracing::debug!("Filter matched {} chunks", chunks.len());
+        Ok(chunks)
+    }
+
+    /// Retrieve several chunks synchronously, skipping missing IDs
+    ///
+    /// Intended for `spawn_blocking` workers that decompress batches in parallel.
+    pub fn get_chunks_blocking(&self, chunk_ids: &[ChunkId]) -> Result<Vec<LearningChunk>> {
+        let mut chunks = Vec::with_capacity(chunk_ids.len());
+        for chunk_id in chunk_ids {
+            if let Some(chunk) = self.read_chunk(chunk_id)? {
+                chunks.push(chunk);
+            }
+        }
+        Ok(chunks)
     }
 
     /// Get all chunks associated with a specific framework
+    ///
+    /// The framework is matched through the alias table, so `next`, `Next.js`
+    /// and `nextjs` return the same chunks regardless of how they were stored.
     pub async fn get_chunks_by_framework(&self, framework: &str) -> Result<Vec<LearningChunk>> {
         tracing::debug!("Getting chunks for framework: {}", framework);
 
-        let framework_key = format!("framework:{}", framework);
-        
-        if let Some(chunk_ids_data) = self.framework_index.get(&framework_key)
-            .context("Failed to query framework index")? {
-            
+        let mut seen = HashSet::new();
+        let mut chunks = Vec::new();
+        for framework_key in self.framework_keys(framework) {
+            let Some(chunk_ids_data) = self.framework_index.get(&framework_key)
+                .context("Failed to query framework index")? else {
+                continue;
+            };
+
             let chunk_ids: Vec<ChunkId> = bincode::deserialize(&chunk_ids_data)
                 .context("Failed to deserialize framework index")?;
-            
-            let mut chunks = Vec::new();
+
             for chunk_id in chunk_ids {
+                if !seen.insert(chunk_id.clone()) {
+                    continue;
+                }
                 if let Some(chunk) = self.get_chunk(&chunk_id).await? {
                     chunks.push(chunk);
                 }
             }
-            
-            tracing::debug!("Retrieved {} chunks for framework: {}", chunks.len(), framework);
-            Ok(chunks)
-        } else {
-            Ok(vec![])
         }
+
+        tracing::debug!("Retrieved {} chunks for framework: {}", chunks.len(), framework);
+        Ok(chunks)
+    }
+
+    /// Framework index keys of every known spelling of the given framework
+    fn framework_keys(&self, framework: &str) -> BTreeSet<String> {
+        let target = self.aliases.normalize(framework);
+
+        let mut keys: BTreeSet<String> = self.aliases.expand(&target).iter()
+            .map(|name| framework_index_key(name))
+            .collect();
+        keys.insert(framework_index_key(framework));
+        keys.insert(framework_index_key(&target));
+        keys.insert(framework_index_key(&alias_key(&target)));
+        keys
     }
 
     /// Get chunks by pattern
@@ -259,38 +579,51 @@ impl HybridStorage {
 
     /// Update an existing chunk
     pub async fn update_chunk(&self, chunk: &LearningChunk) -> Result<()> {
-        // Remove old indexes first
-        self.remove_from_indexes(&chunk.id).await?;
-        
-        // Store the updated chunk
+        // Storing replaces the chunk together with its index entries
         self.store_chunk(chunk).await
     }
 
     /// Delete a chunk and its indexes
     pub async fn delete_chunk(&self, chunk_id: &ChunkId) -> Result<bool> {
-        tracing::debug!("Deleting chunk: {}", chunk_id);
+        let deleted = self.delete_chunks_batch(std::slice::from_ref(chunk_id)).await?;
+        Ok(!deleted.is_empty())
+    }
 
-        let key = self.make_chunk_key(chunk_id);
-        
-        // Remove from main storage
-        let existed = self.chunks_tree.remove(&key)
-            .context("Failed to remove chunk from database")?
-            .is_some();
-
-        if existed {
-            // Remove from indexes
-            self.remove_from_indexes(chunk_id).await?;
-
-            // Update metadata
-            {
-                let mut metadata = self.metadata.write();
-                metadata.total_chunks = metadata.total_chunks.saturating_sub(1);
+    /// Delete several chunks and their indexes, returning the IDs that existed
+    pub async fn delete_chunks_batch(&self, chunk_ids: &[ChunkId]) -> Result<Vec<ChunkId>> {
+        tracing::debug!("Deleting {} chunks", chunk_ids.len());
+
+        let mut deleted = Vec::new();
+        let mut batch = sled::Batch::default();
+        for chunk_id in chunk_ids {
+            let key = self.make_chunk_key(chunk_id);
+            if self.chunks_tree.contains_key(&key).context("Failed to query database")? {
+                batch.remove(key);
+                deleted.push(chunk_id.clone());
             }
-            self.save_metadata().await?;
         }
 
-        tracing::debug!("Chunk deletion result: {}, existed: {}", chunk_id, existed);
-        Ok(existed)
+        if deleted.is_empty() {
+            return Ok(deleted);
+        }
+
+        // Remove from main storage
+        self.chunks_tree.apply_batch(batch)
+            .context("Failed to remove chunks from database")?;
+
+        // Remove from indexes
+        let deleted_set: HashSet<ChunkId> = deleted.iter().cloned().collect();
+        self.remove_from_indexes(&deleted_set).await?;
+
+        // Update metadata
+        {
+            let mut metadata = self.metadata.write();
+            metadata.total_chunks = metadata.total_chunks.saturating_sub(deleted.len() as u64);
+        }
+        self.save_metadata().await?;
+
+        tracing::debug!("Deleted {} of {} chunks", deleted.len(), chunk_ids.len());
+        Ok(deleted)
     }
 
     /// Compact the database to reclaim space
@@ -322,6 +655,39 @@ impl HybridStorage {
         Ok(())
     }
 
+    /// Persist the cache hot set so it can be restored on the next startup
+    pub async fn save_hot_set(&self, entries: &[HotSetEntry]) -> Result<()> {
+        let serialized = bincode::serialize(entries)
+            .context("Failed to serialize hot set snapshot")?;
+
+        self.metadata_tree.insert(HOT_SET_KEY, serialized)
+            .context("Failed to save hot set snapshot")?;
+
+        tracing::debug!("Saved hot set snapshot with {} entries", entries.len());
+        Ok(())
+    }
+
+    /// Remove and return the last persisted cache hot set, if any
+    ///
+    /// A snapshot is only valid for the startup following the shutdown that
+    /// wrote it, so it is consumed rather than left for later restarts.
+    pub async fn take_hot_set(&self) -> Result<Option<Vec<HotSetEntry>>> {
+        match self.metadata_tree.remove(HOT_SET_KEY).context("Failed to take hot set snapshot")? {
+            Some(bytes) => {
+                let entries = bincode::deserialize(&bytes)
+                    .context("Failed to deserialize hot set snapshot")?;
+                Ok(Some(entries))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Flush all pending writes to disk
+    pub async fn flush(&self) -> Result<()> {
+        self.db.flush_async().await.context("Failed to flush database")?;
+        Ok(())
+    }
+
     /// Get storage statistics
     pub fn get_stats(&self) -> StorageStats {
         let metadata = self.metadata.read();
@@ -337,6 +703,25 @@ impl HybridStorage {
         }
     }
 
+    /// Read and decode a chunk from the chunks tree
+    fn read_chunk(&self, chunk_id: &ChunkId) -> Result<Option<LearningChunk>> {
+        let key = self.make_chunk_key(chunk_id);
+
+        match self.chunks_tree.get(&key).context("Failed to query database")? {
+            Some(compressed_data) => Ok(Some(Self::decode_chunk(&compressed_data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Decompress and deserialize a stored chunk
+    fn decode_chunk(compressed_data: &[u8]) -> Result<LearningChunk> {
+        let decompressed = lz4_flex::decompress_size_prepended(compressed_data)
+            .context("Failed to decompress chunk data")?;
+
+        bincode::deserialize(&decompressed)
+            .context("Failed to deserialize chunk")
+    }
+
     /// Create a storage key for a chunk
     fn make_chunk_key(&self, chunk_id: &ChunkId) -> Vec<u8> {
         let mut key = Vec::with_capacity(CHUNK_PREFIX.len() + chunk_id.as_str().len());
@@ -345,11 +730,11 @@ impl HybridStorage {
         key
     }
 
-    /// Add chunk ID to framework index
-    async fn add_to_framework_index(&self, framework: &str, chunk_id: &ChunkId) -> Result<()> {
-        let framework_key = format!("framework:{}", framework);
+    /// Add chunk ID to pattern index
+    async fn add_to_pattern_index(&self, pattern: &str, chunk_id: &ChunkId) -> Result<()> {
+        let pattern_key = format!("pattern:{}", pattern);
         
-        let mut chunk_ids: Vec<ChunkId> = if let Some(existing) = self.framework_index.get(&framework_key)? {
+        let mut chunk_ids: Vec<ChunkId> = if let Some(existing) = self.pattern_index.get(&pattern_key)? {
             bincode::deserialize(&existing).unwrap_or_default()
         } else {
             Vec::new()
@@ -358,66 +743,94 @@ impl HybridStorage {
         if !chunk_ids.contains(chunk_id) {
             chunk_ids.push(chunk_id.clone());
             let serialized = bincode::serialize(&chunk_ids)
-                .context("Failed to serialize framework index")?;
+                .context("Failed to serialize pattern index")?;
             
-            self.framework_index.insert(&framework_key, serialized)
-                .context("Failed to update framework index")?;
+            self.pattern_index.insert(&pattern_key, serialized)
+                .context("Failed to update pattern index")?;
         }
 
         Ok(())
     }
 
-    /// Add chunk ID to pattern index
-    async fn add_to_pattern_index(&self, pattern: &str, chunk_id: &ChunkId) -> Result<()> {
-        let pattern_key = format!("pattern:{}", pattern);
-        
-        let mut chunk_ids: Vec<ChunkId> = if let Some(existing) = self.pattern_index.get(&pattern_key)? {
+    /// Append chunk IDs to an index entry, skipping IDs already present
+    fn append_to_index(index: &Tree, key: &str, new_ids: &[ChunkId]) -> Result<()> {
+        let mut chunk_ids: Vec<ChunkId> = if let Some(existing) = index.get(key)? {
             bincode::deserialize(&existing).unwrap_or_default()
         } else {
             Vec::new()
         };
 
-        if !chunk_ids.contains(chunk_id) {
-            chunk_ids.push(chunk_id.clone());
+        let before = chunk_ids.len();
+        for chunk_id in new_ids {
+            if !chunk_ids.contains(chunk_id) {
+                chunk_ids.push(chunk_id.clone());
+            }
+        }
+
+        if chunk_ids.len() != before {
             let serialized = bincode::serialize(&chunk_ids)
-                .context("Failed to serialize pattern index")?;
-            
-            self.pattern_index.insert(&pattern_key, serialized)
-                .context("Failed to update pattern index")?;
+                .context("Failed to serialize index entry")?;
+            index.insert(key, serialized)
+                .context("Failed to update index entry")?;
         }
 
         Ok(())
     }
 
-    /// Remove chunk from all indexes
-    async fn remove_from_indexes(&self, chunk_id: &ChunkId) -> Result<()> {
-        // This is a simplified version - in a full implementation,
-        // you'd need to track which indexes a chunk belongs to
-        // For now, we'll iterate through all framework and pattern indexes
-        
-        // Remove from framework indexes
-        for result in self.framework_index.iter() {
-            let (key, value) = result.context("Failed to iterate framework index")?;
-            let mut chunk_ids: Vec<ChunkId> = bincode::deserialize(&value)
-                .unwrap_or_default();
-            
-            if let Some(pos) = chunk_ids.iter().position(|id| id == chunk_id) {
-                chunk_ids.remove(pos);
-                let serialized = bincode::serialize(&chunk_ids)?;
-                self.framework_index.insert(&key, serialized)?;
+    /// Remove chunk IDs from an index entry, dropping the entry once it is empty
+    fn remove_from_index(index: &Tree, key: &str, removed: &HashSet<ChunkId>) -> Result<()> {
+        let Some(existing) = index.get(key)? else {
+            return Ok(());
+        };
+        let mut chunk_ids: Vec<ChunkId> = bincode::deserialize(&existing).unwrap_or_default();
+
+        let before = chunk_ids.len();
+        chunk_ids.retain(|chunk_id| !removed.contains(chunk_id));
+
+        if chunk_ids.is_empty() {
+            index.remove(key).context("Failed to update index entry")?;
+        } else if chunk_ids.len() != before {
+            let serialized = bincode::serialize(&chunk_ids)
+                .context("Failed to serialize index entry")?;
+            index.insert(key, serialized)
+                .context("Failed to update index entry")?;
+        }
+
+        Ok(())
+    }
+
+    /// Metadata a replaced chunk was indexed under
+    ///
+    /// A stored version that cannot be decoded is dropped from every index
+    /// entry, so the new version is indexed from scratch.
+    async fn indexed_metadata(&self, chunk_id: &ChunkId, stored: &[u8]) -> Result<ChunkMetadata> {
+        match Self::decode_chunk(stored) {
+            Ok(previous) => Ok(previous.metadata),
+            Err(e) => {
+                tracing::warn!("Failed to decode previous version of chunk {}: {:#}", chunk_id, e);
+                self.remove_from_indexes(&HashSet::from([chunk_id.clone()])).await?;
+                Ok(ChunkMetadata::default())
             }
         }
+    }
 
-        // Remove from pattern indexes
-        for result in self.pattern_index.iter() {
-            let (key, value) = result.context("Failed to iterate pattern index")?;
-            let mut chunk_ids: Vec<ChunkId> = bincode::deserialize(&value)
-                .unwrap_or_default();
-            
-            if let Some(pos) = chunk_ids.iter().position(|id| id == chunk_id) {
-                chunk_ids.remove(pos);
-                let serialized = bincode::serialize(&chunk_ids)?;
-                self.pattern_index.insert(&key, serialized)?;
+    /// Remove chunks from all indexes
+    async fn remove_from_indexes(&self, chunk_ids: &HashSet<ChunkId>) -> Result<()> {
+        // Indexes are keyed by framework/pattern rather than by chunk, so a
+        // single pass over both trees handles any number of removed chunks
+        for index in [&self.framework_index, &self.pattern_index] {
+            for result in index.iter() {
+                let (key, value) = result.context("Failed to iterate index")?;
+                let mut entries: Vec<ChunkId> = bincode::deserialize(&value)
+                    .unwrap_or_default();
+
+                let before = entries.len();
+                entries.retain(|id| !chunk_ids.contains(id));
+
+                if entries.len() != before {
+                    let serialized = bincode::serialize(&entries)?;
+                    index.insert(&key, serialized)?;
+                }
             }
         }
 
@@ -431,7 +844,7 @@ impl HybridStorage {
                 .context("Failed to deserialize metadata")
         } else {
             Ok(DatabaseMetadata {
-                version: "1.0.0".to_string(),
+                version: STORAGE_FORMAT_VERSION.to_string(),
                 created_at: Utc::now(),
                 last_optimized: None,
                 total_chunks: 0,
@@ -453,6 +866,93 @@ impl HybridStorage {
     }
 }
 
+/// Open a sled database, waiting for a handle closed moments ago to release its lock
+///
+/// sled keeps the database file locked until its background writers finish,
+/// which can briefly outlive the last handle. Reopening the same path in one
+/// process, as after [`MemoryEngine::close`](crate::MemoryEngine::close),
+/// retries for up to a second instead of failing.
+pub(crate) fn open_db(path: &Path) -> Result<Db> {
+    // sled reports lock contention as a generic I/O error identified only by its message
+    let is_locked = |e: &std::io::Error| e.to_string().starts_with("could not acquire lock");
+
+    let mut attempts = 0;
+    loop {
+        match sled::open(path) {
+            Err(sled::Error::Io(e)) if is_locked(&e) && attempts < OPEN_LOCK_RETRIES => {
+                attempts += 1;
+                std::thread::sleep(OPEN_LOCK_RETRY_DELAY);
+            }
+            result => return result.context("Failed to open sled database"),
+        }
+    }
+}
+
+/// Framework index key of a framework, which uses its lowercase spelling
+fn framework_index_key(framework: &str) -> String {
+    format!("framework:{}", spelling(framework))
+}
+
+/// Distinct framework index keys of a chunk's frameworks
+fn framework_index_keys(frameworks: &[String]) -> Vec<String> {
+    let mut keys: Vec<String> = frameworks.iter().map(|framework| framework_index_key(framework)).collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+/// Entries of `current` missing from `other`
+fn index_changes<'a>(current: &'a [String], other: &'a [String]) -> impl Iterator<Item = &'a str> {
+    current.iter()
+        .filter(move |value| !other.contains(value))
+        .map(String::as_str)
+}
+
+/// Chunk as stored by format 1.0.0
+#[derive(Deserialize)]
+struct LegacyChunk {
+    id: ChunkId,
+    chunk_type: ChunkType,
+    content: ChunkContent,
+    metadata: ChunkMetadata,
+    embedding: Option<Vec<f32>>,
+    relationships: Vec<LegacyRelation>,
+    quality_score: f32,
+}
+
+/// Relationship as stored by format 1.0.0, always declared
+#[derive(Deserialize)]
+struct LegacyRelation {
+    target: ChunkId,
+    relation_type: RelationType,
+    strength: f32,
+    metadata: HashMap<String, serde_json::Value>,
+}
+
+impl From<LegacyChunk> for LearningChunk {
+    fn from(legacy: LegacyChunk) -> Self {
+        Self {
+            id: legacy.id,
+            chunk_type: legacy.chunk_type,
+            content: legacy.content,
+            metadata: legacy.metadata,
+            embedding: legacy.embedding,
+            relationships: legacy.relationships.into_iter()
+                .map(|relation| ChunkRelation {
+                    target: relation.target,
+                    relation_type: relation.relation_type,
+                    strength: relation.strength,
+                    metadata: relation.metadata,
+                    provenance: RelationProvenance::Declared,
+                    confidence: 1.0,
+                    inverse: false,
+                })
+                .collect(),
+            quality_score: legacy.quality_score,
+        }
+    }
+}
+
 /// Storage statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageStats {
@@ -532,6 +1032,43 @@ mod tests {
         assert_eq!(vue_chunks.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_framework_lookup_resolves_aliases() {
+        let (storage, _temp_dir) = create_test_storage().await;
+
+        // Chunks stored before normalization keep their original spellings
+        storage.store_chunk(&create_test_chunk("next-1", "Next.js")).await.unwrap();
+        storage.store_chunk(&create_test_chunk("next-2", "nextjs")).await.unwrap();
+        storage.store_chunk(&create_test_chunk("htmx-1", "HTMX")).await.unwrap();
+
+        for framework in ["next", "next.js", "Next 14"] {
+            assert_eq!(storage.get_chunks_by_framework(framework).await.unwrap().len(), 2, "{}", framework);
+        }
+        assert_eq!(storage.get_chunks_by_framework("htmx").await.unwrap().len(), 1);
+
+        let filter = ChunkFilter { frameworks: vec!["next".to_string()], ..Default::default() };
+        assert_eq!(storage.find_chunks(&filter).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_framework_index_keys_are_migrated_to_spellings() {
+        let (storage, _temp_dir) = create_test_storage().await;
+        storage.store_chunk(&create_test_chunk("next-1", "next.js")).await.unwrap();
+        storage.store_chunk(&create_test_chunk("next-2", "Next.js")).await.unwrap();
+
+        // Entry written before keys were lowercased
+        let legacy_ids = bincode::serialize(&vec![ChunkId::new("next-2")]).unwrap();
+        storage.framework_index.remove("framework:next.js").unwrap();
+        storage.framework_index.insert("framework:next.js", bincode::serialize(&vec![ChunkId::new("next-1")]).unwrap()).unwrap();
+        storage.framework_index.insert("framework:Next.js", legacy_ids).unwrap();
+        storage.metadata_tree.remove(FRAMEWORK_INDEX_LAYOUT_KEY).unwrap();
+
+        storage.migrate_framework_index().unwrap();
+
+        assert!(storage.framework_index.get("framework:Next.js").unwrap().is_none());
+        assert_eq!(storage.get_chunks_by_framework("next").await.unwrap().len(), 2);
+    }
+
     #[tokio::test]
     async fn test_compression() {
         let (storage, _temp_dir) = create_test_storage().await;
@@ -563,4 +1100,129 @@ mod tests {
         assert!(deleted);
         assert!(storage.get_chunk(&chunk.id).await.unwrap().is_none());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_batch_store_and_delete() {
+        let (storage, _temp_dir) = create_test_storage().await;
+        let chunks = vec![
+            create_test_chunk("batch-1", "react"),
+            create_test_chunk("batch-2", "react"),
+            create_test_chunk("batch-3", "vue"),
+        ];
+
+        storage.store_chunks_batch(&chunks).await.unwrap();
+        // Re-storing existing chunks must not inflate the count
+        storage.store_chunk(&chunks[0]).await.unwrap();
+        assert_eq!(storage.get_stats().total_chunks, 3);
+        assert_eq!(storage.get_chunks_by_framework("react").await.unwrap().len(), 2);
+
+        let deleted = storage.delete_chunks_batch(&[
+            ChunkId::new("batch-1"),
+            ChunkId::new("batch-3"),
+            ChunkId::new("missing"),
+        ]).await.unwrap();
+        assert_eq!(deleted.len(), 2);
+        assert_eq!(storage.get_stats().total_chunks, 1);
+        assert_eq!(storage.get_chunks_by_framework("react").await.unwrap().len(), 1);
+        assert!(storage.get_chunks_by_framework("vue").await.unwrap().is_empty());
+        assert!(storage.get_chunks_by_pattern("export-function").await.unwrap().len() == 1);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_replaces_index_entries() {
+        let (storage, _temp_dir) = create_test_storage().await;
+        storage.store_chunk(&create_test_chunk("moved", "react")).await.unwrap();
+
+        let mut moved = create_test_chunk("moved", "vue");
+        moved.metadata.patterns = vec!["sfc".to_string()];
+        storage.store_chunk(&moved).await.unwrap();
+        assert!(storage.get_chunks_by_framework("react").await.unwrap().is_empty());
+        assert!(storage.get_chunks_by_pattern("export-function").await.unwrap().is_empty());
+        assert_eq!(storage.get_chunks_by_framework("vue").await.unwrap().len(), 1);
+
+        storage.store_chunks_batch(&[create_test_chunk("moved", "svelte")]).await.unwrap();
+        assert!(storage.get_chunks_by_framework("vue").await.unwrap().is_empty());
+        assert!(storage.get_chunks_by_pattern("sfc").await.unwrap().is_empty());
+        assert_eq!(storage.get_chunks_by_framework("svelte").await.unwrap().len(), 1);
+
+        // Only the last version of a chunk repeated within a batch is indexed
+        storage.store_chunks_batch(&[create_test_chunk("moved", "react"), create_test_chunk("moved", "vue")]).await.unwrap();
+        assert!(storage.get_chunks_by_framework("svelte").await.unwrap().is_empty());
+        assert!(storage.get_chunks_by_framework("react").await.unwrap().is_empty());
+        assert_eq!(storage.get_chunks_by_framework("vue").await.unwrap().len(), 1);
+        assert_eq!(storage.get_stats().total_chunks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_find_chunks_with_filter() {
+        let (storage, _temp_dir) = create_test_storage().await;
+        let mut high = create_test_chunk("high", "react");
+        high.quality_score = 0.9;
+        let low = create_test_chunk("low", "react");
+        let other = create_test_chunk("other", "vue");
+
+        storage.store_chunks_batch(&[high, low, other]).await.unwrap();
+
+        let filter = ChunkFilter { min_quality: Some(0.8), ..Default::default() };
+        let found = storage.find_chunks(&filter).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id.as_str(), "high");
+
+        let filter = ChunkFilter { frameworks: vec!["react".to_string()], ..Default::default() };
+        assert_eq!(storage.find_chunks(&filter).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_hot_set_snapshot_roundtrip() {
+        let (storage, _temp_dir) = create_test_storage().await;
+        assert!(storage.take_hot_set().await.unwrap().is_none());
+
+        let entries = vec![
+            HotSetEntry { chunk_id: ChunkId::new("hot-1"), access_count: 7, size_bytes: 512 },
+            HotSetEntry { chunk_id: ChunkId::new("hot-2"), access_count: 2, size_bytes: 128 },
+        ];
+        storage.save_hot_set(&entries).await.unwrap();
+
+        assert_eq!(storage.take_hot_set().await.unwrap(), Some(entries));
+        assert!(storage.take_hot_set().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_chunks_are_migrated() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunk = create_test_chunk("legacy", "react");
+
+        {
+            let storage = HybridStorage::new(temp_dir.path(), 6).await.unwrap();
+            storage.store_chunk(&chunk).await.unwrap();
+
+            // Rewrite the chunk and version as format 1.0.0 stored them
+            let relations = vec![(ChunkId::new("dep"), RelationType::DependsOn, 0.9f32, HashMap::<String, serde_json::Value>::new())];
+            let legacy = (
+                &chunk.id,
+                &chunk.chunk_type,
+                &chunk.content,
+                &chunk.metadata,
+                &chunk.embedding,
+                relations,
+                chunk.quality_score,
+            );
+            let serialized = bincode::serialize(&legacy).unwrap();
+            storage.chunks_tree
+                .insert(storage.make_chunk_key(&chunk.id), lz4_flex::compress_prepend_size(&serialized))
+                .unwrap();
+            storage.metadata.write().version = LEGACY_FORMAT_VERSION.to_string();
+            storage.save_metadata().await.unwrap();
+        }
+
+        let storage = HybridStorage::new(temp_dir.path(), 6).await.unwrap();
+        assert_eq!(storage.metadata.read().version, STORAGE_FORMAT_VERSION);
+
+        let migrated = storage.get_chunk(&chunk.id).await.unwrap().unwrap();
+        assert_eq!(migrated.content, chunk.content);
+        let relation = migrated.relation(&ChunkId::new("dep"), &RelationType::DependsOn).unwrap();
+        assert_eq!(relation.provenance, RelationProvenance::Declared);
+        assert_eq!(relation.confidence, 1.0);
+        assert!(!relation.inverse);
+    }
+}
@@ -0,0 +1,419 @@
+//! Type-ahead and spelling suggestions
+//!
+//! Collects spike names, tags, frameworks and pattern names from stored
+//! chunks, weighted by chunk usage and quality, and compiles them into an FST
+//! on commit for prefix completion and "did you mean" corrections.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use fst::automaton::{Levenshtein, Str};
+use fst::{Automaton, IntoStreamer, Map, MapBuilder, Streamer};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{ChunkId, LearningChunk};
+use crate::query::spike_name;
+use crate::search::fuzzy_distance;
+
+/// Source of a suggested term
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SuggestionKind {
+    /// Spike template name
+    Spike,
+    /// Chunk tag
+    Tag,
+    /// Framework name
+    Framework,
+    /// Pattern name
+    Pattern,
+}
+
+/// Suggested term
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suggestion {
+    /// Term as first seen in the chunks
+    pub text: String,
+    /// Where the term comes from
+    pub kind: SuggestionKind,
+    /// Sum of the weights of chunks containing the term
+    pub weight: f32,
+    /// Number of chunks containing the term
+    pub chunk_count: usize,
+}
+
+/// Live term statistics, keyed by kind and normalized term
+#[derive(Debug, Default)]
+struct TermStats {
+    text: String,
+    weight: f64,
+    chunk_count: usize,
+}
+
+/// Terms contributed by one chunk, kept so updates can subtract them
+#[derive(Debug)]
+struct Contribution {
+    terms: Vec<(SuggestionKind, String)>,
+    weight: f64,
+}
+
+#[derive(Debug, Default)]
+struct LiveTerms {
+    terms: HashMap<(SuggestionKind, String), TermStats>,
+    contributions: HashMap<ChunkId, Contribution>,
+    dirty: bool,
+}
+
+/// Compiled suggestions served to readers until the next commit
+struct Snapshot {
+    // Normalized term -> index into `entries`
+    fst: Map<Vec<u8>>,
+    // Suggestions per normalized term, heaviest first
+    entries: Vec<Vec<Suggestion>>,
+}
+
+impl Snapshot {
+    fn empty() -> Self {
+        Self {
+            fst: Map::default(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// FST-backed suggestion index over chunk terms
+pub struct SuggestionIndex {
+    live: RwLock<LiveTerms>,
+    snapshot: RwLock<Arc<Snapshot>>,
+}
+
+impl std::fmt::Debug for SuggestionIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SuggestionIndex")
+            .field("terms", &self.snapshot.read().fst.len())
+            .finish()
+    }
+}
+
+impl Default for SuggestionIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SuggestionIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self {
+            live: RwLock::new(LiveTerms::default()),
+            snapshot: RwLock::new(Arc::new(Snapshot::empty())),
+        }
+    }
+
+    /// Number of distinct normalized terms visible to readers
+    pub fn len(&self) -> usize {
+        self.snapshot.read().fst.len()
+    }
+
+    /// Check whether no terms are visible to readers
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add or replace the terms of a chunk; visible after the next commit
+    pub fn upsert(&self, chunk: &LearningChunk) {
+        let mut live = self.live.write();
+        live.subtract(&chunk.id);
+
+        let weight = chunk_weight(chunk);
+        let mut terms = Vec::new();
+        for (kind, text) in chunk_terms(chunk) {
+            let key = normalize_term(&text);
+            if key.is_empty() || terms.iter().any(|(k, t)| *k == kind && *t == key) {
+                continue;
+            }
+
+            let stats = live.terms.entry((kind, key.clone())).or_default();
+            if stats.text.is_empty() {
+                stats.text = text.trim().to_string();
+            }
+            stats.weight += weight;
+            stats.chunk_count += 1;
+            terms.push((kind, key));
+        }
+
+        live.contributions.insert(chunk.id.clone(), Contribution { terms, weight });
+        live.dirty = true;
+    }
+
+    /// Remove the terms of a chunk; visible after the next commit
+    pub fn remove(&self, chunk_id: &ChunkId) {
+        let mut live = self.live.write();
+        if live.subtract(chunk_id) {
+            live.dirty = true;
+        }
+    }
+
+    /// Compile pending changes into a new FST and swap it in for readers
+    pub fn commit(&self) -> Result<()> {
+        let grouped = {
+            let mut live = self.live.write();
+            if !live.dirty {
+                return Ok(());
+            }
+            live.dirty = false;
+
+            let mut grouped: BTreeMap<String, Vec<Suggestion>> = BTreeMap::new();
+            for ((kind, key), stats) in &live.terms {
+                grouped.entry(key.clone()).or_default().push(Suggestion {
+                    text: stats.text.clone(),
+                    kind: *kind,
+                    weight: stats.weight as f32,
+                    chunk_count: stats.chunk_count,
+                });
+            }
+            grouped
+        };
+
+        let mut builder = MapBuilder::memory();
+        let mut entries = Vec::with_capacity(grouped.len());
+        for (key, mut suggestions) in grouped {
+            suggestions.sort_by(|a, b| b.weight.total_cmp(&a.weight).then(a.kind.cmp(&b.kind)));
+            builder.insert(&key, entries.len() as u64)
+                .context("Failed to add suggestion term")?;
+            entries.push(suggestions);
+        }
+        let fst = Map::new(builder.into_inner().context("Failed to build suggestion index")?)
+            .context("Failed to load suggestion index")?;
+
+        tracing::debug!("Suggestion index committed with {} terms", entries.len());
+        *self.snapshot.write() = Arc::new(Snapshot { fst, entries });
+        Ok(())
+    }
+
+    /// Complete a prefix, heaviest terms first
+    ///
+    /// Pass `kinds` to restrict suggestions to some sources; an empty slice
+    /// accepts all of them.
+    pub fn complete(&self, prefix: &str, kinds: &[SuggestionKind], limit: usize) -> Vec<Suggestion> {
+        let prefix = normalize_term(prefix);
+        if prefix.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let snapshot = self.snapshot.read().clone();
+        let automaton = Str::new(&prefix).starts_with();
+        let mut stream = snapshot.fst.search(automaton).into_stream();
+
+        let mut suggestions = Vec::new();
+        while let Some((_, index)) = stream.next() {
+            suggestions.extend(
+                snapshot.entries[index as usize].iter()
+                    .filter(|s| kinds.is_empty() || kinds.contains(&s.kind))
+                    .cloned(),
+            );
+        }
+
+        rank(&mut suggestions, limit);
+        suggestions
+    }
+
+    /// Known terms within the term's edit distance, closest then heaviest first
+    pub fn corrections(&self, term: &str, limit: usize) -> Vec<Suggestion> {
+        let term = normalize_term(term);
+        let max_distance = fuzzy_distance(&term) as u32;
+        if max_distance == 0 || limit == 0 {
+            return Vec::new();
+        }
+
+        let snapshot = self.snapshot.read().clone();
+        let mut suggestions = Vec::new();
+
+        // Closer corrections win outright, so stop at the first distance with matches
+        for distance in 1..=max_distance {
+            let Ok(automaton) = Levenshtein::new(&term, distance) else {
+                break;
+            };
+
+            let mut stream = snapshot.fst.search(automaton).into_stream();
+            while let Some((key, index)) = stream.next() {
+                if key != term.as_bytes() {
+                    suggestions.extend(snapshot.entries[index as usize].iter().cloned());
+                }
+            }
+
+            if !suggestions.is_empty() {
+                break;
+            }
+        }
+
+        rank(&mut suggestions, limit);
+        suggestions
+    }
+
+    /// Suggest a corrected query by replacing unknown words with their best correction
+    ///
+    /// Returns `None` when the query is a known term or no word can be corrected.
+    pub fn did_you_mean(&self, query: &str) -> Option<String> {
+        let normalized = normalize_term(query);
+        if normalized.is_empty() || self.contains(&normalized) {
+            return None;
+        }
+
+        // Multi-word terms such as tags with spaces are corrected as a whole first
+        if normalized.contains(' ') {
+            if let Some(best) = self.corrections(&normalized, 1).into_iter().next() {
+                return Some(best.text);
+            }
+        }
+
+        let mut corrected = false;
+        let words: Vec<String> = query.split_whitespace()
+            .map(|word| {
+                if self.contains(&normalize_term(word)) {
+                    return word.to_string();
+                }
+                match self.corrections(word, 1).into_iter().next() {
+                    Some(best) => {
+                        corrected = true;
+                        best.text
+                    }
+                    None => word.to_string(),
+                }
+            })
+            .collect();
+
+        corrected.then(|| words.join(" "))
+    }
+
+    fn contains(&self, normalized: &str) -> bool {
+        self.snapshot.read().fst.contains_key(normalized)
+    }
+}
+
+impl LiveTerms {
+    /// Remove a chunk's previous contribution, returning whether it had one
+    fn subtract(&mut self, chunk_id: &ChunkId) -> bool {
+        let Some(contribution) = self.contributions.remove(chunk_id) else {
+            return false;
+        };
+
+        for key in contribution.terms {
+            if let Some(stats) = self.terms.get_mut(&key) {
+                stats.weight -= contribution.weight;
+                stats.chunk_count -= 1;
+                if stats.chunk_count == 0 {
+                    self.terms.remove(&key);
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Weight of a chunk's terms, favoring frequently used, high-quality chunks
+pub fn chunk_weight(chunk: &LearningChunk) -> f64 {
+    let quality = chunk.quality_score.clamp(0.0, 1.0).max(0.01) as f64;
+    quality * (1.0 + (chunk.metadata.usage_count as f64).ln_1p())
+}
+
+/// Terms a chunk contributes to suggestions
+fn chunk_terms(chunk: &LearningChunk) -> Vec<(SuggestionKind, String)> {
+    let metadata = &chunk.metadata;
+    spike_name(chunk).into_iter().map(|name| (SuggestionKind::Spike, name))
+        .chain(metadata.tags.iter().map(|tag| (SuggestionKind::Tag, tag.clone())))
+        .chain(metadata.frameworks.iter().map(|fw| (SuggestionKind::Framework, fw.clone())))
+        .chain(metadata.patterns.iter().map(|pattern| (SuggestionKind::Pattern, pattern.clone())))
+        .collect()
+}
+
+fn normalize_term(term: &str) -> String {
+    term.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn rank(suggestions: &mut Vec<Suggestion>, limit: usize) {
+    suggestions.sort_by(|a, b| {
+        b.weight.total_cmp(&a.weight)
+            .then_with(|| a.text.cmp(&b.text))
+            .then(a.kind.cmp(&b.kind))
+    });
+    suggestions.truncate(limit);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::{ChunkContent, ChunkType};
+
+    fn chunk(id: &str, tags: &[&str], frameworks: &[&str], usage_count: u64) -> LearningChunk {
+        let mut chunk = LearningChunk {
+            id: ChunkId::new(id),
+            quality_score: 0.8,
+            ..Default::default()
+        };
+        chunk.metadata.tags = tags.iter().map(|t| t.to_string()).collect();
+        chunk.metadata.frameworks = frameworks.iter().map(|f| f.to_string()).collect();
+        chunk.metadata.usage_count = usage_count;
+        chunk
+    }
+
+    #[test]
+    fn test_prefix_completion_weighted_by_usage() {
+        let index = SuggestionIndex::new();
+        index.upsert(&chunk("a", &["authentication"], &["nextjs"], 0));
+        index.upsert(&chunk("b", &["authorization"], &["nextjs"], 50));
+
+        let mut spike = LearningChunk::new(
+            ChunkId::new("spike-auth-starter"),
+            ChunkType::SpikeTemplate,
+            ChunkContent::Data {
+                format: "json".to_string(),
+                data: serde_json::json!({ "name": "Auth-Starter" }),
+            },
+        );
+        spike.quality_score = 0.9;
+        index.upsert(&spike);
+
+        // Nothing is visible before commit
+        assert!(index.complete("auth", &[], 10).is_empty());
+        index.commit().unwrap();
+
+        let suggestions = index.complete("AUTH", &[], 10);
+        let texts: Vec<&str> = suggestions.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts[0], "authorization");
+        assert!(texts.contains(&"authentication"));
+        assert!(texts.contains(&"Auth-Starter"));
+
+        let spikes = index.complete("auth", &[SuggestionKind::Spike], 10);
+        assert_eq!(spikes.len(), 1);
+        assert_eq!(spikes[0].kind, SuggestionKind::Spike);
+
+        let frameworks = index.complete("ne", &[], 10);
+        assert_eq!(frameworks.len(), 1);
+        assert_eq!(frameworks[0].chunk_count, 2);
+    }
+
+    #[test]
+    fn test_corrections_and_updates() {
+        let index = SuggestionIndex::new();
+        index.upsert(&chunk("a", &["middleware", "server action"], &["laravel"], 3));
+        index.commit().unwrap();
+
+        assert_eq!(index.corrections("midleware", 5)[0].text, "middleware");
+        assert_eq!(index.did_you_mean("laravl midleware").as_deref(), Some("laravel middleware"));
+        assert_eq!(index.did_you_mean("server acton").as_deref(), Some("server action"));
+        assert_eq!(index.did_you_mean("middleware"), None);
+        assert_eq!(index.did_you_mean("zzzzzz"), None);
+
+        // Replacing and removing chunks drops their terms on commit
+        index.upsert(&chunk("a", &["routing"], &["laravel"], 3));
+        index.commit().unwrap();
+        assert!(index.complete("mid", &[], 5).is_empty());
+        assert_eq!(index.complete("rout", &[], 5).len(), 1);
+
+        index.remove(&ChunkId::new("a"));
+        index.commit().unwrap();
+        assert!(index.is_empty());
+    }
+}
@@ -0,0 +1,456 @@
+//! Code-aware and CJK tokenization for the search index
+//!
+//! Splits identifiers the way developers read them: `getServerSideProps`
+//! becomes `get`, `server`, `side`, `props` while the original identifier is
+//! kept at the same position so exact lookups still match. Japanese, Chinese
+//! and Korean text has no word separators and is indexed as character bigrams
+//! in a separate field selected by script detection.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{LowerCaser, RemoveLongFilter, TextAnalyzer, Token, TokenStream, Tokenizer};
+
+/// Name under which the code analyzer is registered with the index
+pub const CODE_TOKENIZER: &str = "code";
+
+/// Name under which the CJK bigram analyzer is registered with the index
+pub const CJK_TOKENIZER: &str = "cjk";
+
+/// Tokens longer than this are dropped (minified code, base64 blobs)
+const MAX_TOKEN_LEN: usize = 100;
+
+/// Characters that separate the parts of a compound identifier or path
+const PART_SEPARATORS: &[char] = &['_', '-', '$', '@', '.', '/', '\\', ':'];
+
+/// Common English stopwords
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "in", "into",
+    "is", "it", "of", "on", "or", "such", "that", "the", "their", "then", "there",
+    "these", "they", "this", "to", "was", "will", "with",
+];
+
+/// Keywords shared by the languages found in Spike templates
+const LANGUAGE_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "break", "case", "catch", "class", "const",
+    "continue", "def", "default", "do", "else", "enum", "export", "extends", "false",
+    "final", "fn", "for", "from", "function", "if", "impl", "implements", "import",
+    "in", "interface", "let", "match", "mod", "namespace", "new", "null", "private",
+    "protected", "pub", "public", "return", "self", "static", "struct", "super",
+    "switch", "this", "throw", "true", "try", "type", "use", "var", "void", "while",
+];
+
+/// Options for the code tokenizer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeTokenizerOptions {
+    /// Also emit the unsplit compound token
+    pub keep_original: bool,
+    /// Drop programming language keywords
+    pub remove_keywords: bool,
+    /// Drop common English stopwords
+    pub remove_stopwords: bool,
+}
+
+impl Default for CodeTokenizerOptions {
+    fn default() -> Self {
+        Self {
+            keep_original: true,
+            remove_keywords: false,
+            remove_stopwords: false,
+        }
+    }
+}
+
+/// Build the analyzer registered as [`CODE_TOKENIZER`]
+pub fn code_analyzer(options: &CodeTokenizerOptions) -> TextAnalyzer {
+    TextAnalyzer::builder(CodeTokenizer::new(options.clone()))
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+        .filter(LowerCaser)
+        .build()
+}
+
+/// Tokenizer splitting camelCase, snake_case, kebab-case, paths and namespaces
+#[derive(Debug, Clone)]
+pub struct CodeTokenizer {
+    options: CodeTokenizerOptions,
+    ignored: Arc<HashSet<&'static str>>,
+}
+
+impl CodeTokenizer {
+    /// Create a tokenizer with the given options
+    pub fn new(options: CodeTokenizerOptions) -> Self {
+        let mut ignored = HashSet::new();
+        if options.remove_stopwords {
+            ignored.extend(STOPWORDS);
+        }
+        if options.remove_keywords {
+            ignored.extend(LANGUAGE_KEYWORDS);
+        }
+
+        Self {
+            options,
+            ignored: Arc::new(ignored),
+        }
+    }
+
+    /// Split text into tokens with byte offsets and positions
+    pub fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut position = 0;
+
+        for (start, compound) in compounds(text) {
+            let parts: Vec<(usize, &str)> = split_parts(compound)
+                .into_iter()
+                .filter(|(_, part)| !self.is_ignored(part))
+                .collect();
+
+            if parts.is_empty() {
+                continue;
+            }
+
+            // The original shares the position of the first part so phrase queries still line up
+            if self.options.keep_original && parts.len() > 1 {
+                tokens.push(Token {
+                    offset_from: start,
+                    offset_to: start + compound.len(),
+                    position,
+                    text: compound.to_string(),
+                    position_length: parts.len(),
+                });
+            }
+
+            for (offset, part) in parts {
+                tokens.push(Token {
+                    offset_from: start + offset,
+                    offset_to: start + offset + part.len(),
+                    position,
+                    text: part.to_string(),
+                    position_length: 1,
+                });
+                position += 1;
+            }
+        }
+
+        tokens
+    }
+
+    fn is_ignored(&self, word: &str) -> bool {
+        !self.ignored.is_empty() && self.ignored.contains(word.to_lowercase().as_str())
+    }
+}
+
+impl Tokenizer for CodeTokenizer {
+    type TokenStream<'a> = CodeTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        CodeTokenStream {
+            tokens: self.tokenize(text),
+            index: None,
+        }
+    }
+}
+
+/// Token stream over pre-computed tokens
+pub struct CodeTokenStream {
+    tokens: Vec<Token>,
+    index: Option<usize>,
+}
+
+impl TokenStream for CodeTokenStream {
+    fn advance(&mut self) -> bool {
+        let next = self.index.map_or(0, |i| i + 1);
+        self.index = Some(next);
+        next < self.tokens.len()
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index.unwrap_or(0)]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index.unwrap_or(0)]
+    }
+}
+
+/// Find compound words (identifiers joined by separators) with their byte offsets
+fn compounds(text: &str) -> Vec<(usize, &str)> {
+    // CJK text is left to the bigram analyzer
+    let is_compound_char = |c: char| is_word_char(c) || PART_SEPARATORS.contains(&c);
+    let mut result = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (start, is_compound_char(c)) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let raw = &text[s..i];
+                let trimmed = raw.trim_start_matches(PART_SEPARATORS);
+                let offset = s + raw.len() - trimmed.len();
+                let trimmed = trimmed.trim_end_matches(PART_SEPARATORS);
+                if !trimmed.is_empty() {
+                    result.push((offset, trimmed));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    result
+}
+
+/// Split a compound word at separators and case boundaries, returning offsets relative to it
+fn split_parts(compound: &str) -> Vec<(usize, &str)> {
+    let mut parts = Vec::new();
+    let mut offset = 0;
+
+    for segment in compound.split(PART_SEPARATORS) {
+        if !segment.is_empty() {
+            for (start, end) in case_boundaries(segment) {
+                parts.push((offset + start, &segment[start..end]));
+            }
+        }
+        offset += segment.len() + 1;
+    }
+
+    parts
+}
+
+/// Byte ranges of the camelCase / PascalCase words within a segment
+fn case_boundaries(segment: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = segment.char_indices().collect();
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    for i in 1..chars.len() {
+        let (offset, c) = chars[i];
+        let prev = chars[i - 1].1;
+        let next_is_lower = chars.get(i + 1).is_some_and(|(_, n)| n.is_lowercase());
+
+        // `fooBar` / `foo2Bar` split before the capital, `HTTPServer` before the last capital
+        let boundary = c.is_uppercase()
+            && (prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower));
+
+        if boundary {
+            ranges.push((start, offset));
+            start = offset;
+        }
+    }
+
+    ranges.push((start, segment.len()));
+    ranges
+}
+
+/// Script family of a text, used to route it to a matching analyzer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextScript {
+    /// Space-separated scripts (Latin, Cyrillic, ...) and code
+    Default,
+    /// Japanese, Chinese or Korean text
+    Cjk,
+}
+
+impl TextScript {
+    /// Detect the script of a text by looking for CJK characters
+    pub fn detect(text: &str) -> Self {
+        if text.chars().any(is_cjk) {
+            Self::Cjk
+        } else {
+            Self::Default
+        }
+    }
+
+    /// Map a language code such as `ja`, `zh-CN` or `ko` to its script
+    pub fn from_language(language: &str) -> Option<Self> {
+        let primary = language.split(['-', '_']).next()?.to_lowercase();
+        match primary.as_str() {
+            "ja" | "jp" | "zh" | "ko" | "japanese" | "chinese" | "korean" => Some(Self::Cjk),
+            _ => None,
+        }
+    }
+}
+
+/// Check whether a character belongs to a CJK script
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'   // Hiragana, Katakana
+        | '\u{31F0}'..='\u{31FF}' // Katakana phonetic extensions
+        | '\u{3400}'..='\u{4DBF}' // CJK extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK unified ideographs
+        | '\u{AC00}'..='\u{D7AF}' // Hangul syllables
+        | '\u{F900}'..='\u{FAFF}' // CJK compatibility ideographs
+        | '\u{FF66}'..='\u{FF9F}' // Halfwidth Katakana
+    )
+}
+
+/// Alphanumeric characters outside CJK scripts
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() && !is_cjk(c)
+}
+
+/// Build the analyzer registered as [`CJK_TOKENIZER`]
+pub fn cjk_analyzer() -> TextAnalyzer {
+    TextAnalyzer::builder(CjkBigramTokenizer)
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+        .filter(LowerCaser)
+        .build()
+}
+
+/// Tokenizer emitting overlapping bigrams for CJK runs and whole words otherwise
+///
+/// A CJK run of a single character is emitted as a unigram.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CjkBigramTokenizer;
+
+impl CjkBigramTokenizer {
+    /// Split text into tokens with byte offsets and positions
+    pub fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut push = |from: usize, to: usize| {
+            let position = tokens.len();
+            tokens.push(Token {
+                offset_from: from,
+                offset_to: to,
+                position,
+                text: text[from..to].to_string(),
+                position_length: 1,
+            });
+        };
+
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let end_of = |i: usize| chars.get(i).map_or(text.len(), |(offset, _)| *offset);
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i].1;
+            let in_run = |other: char| if is_cjk(c) { is_cjk(other) } else { is_word_char(other) };
+            let run_end = (i..chars.len())
+                .find(|&j| !in_run(chars[j].1))
+                .unwrap_or(chars.len());
+
+            if is_cjk(c) {
+                if run_end - i == 1 {
+                    push(chars[i].0, end_of(i + 1));
+                }
+                for (offset, &(start, _)) in chars[i..run_end.saturating_sub(1)].iter().enumerate() {
+                    push(start, end_of(i + offset + 2));
+                }
+                i = run_end;
+            } else if is_word_char(c) {
+                push(chars[i].0, end_of(run_end));
+                i = run_end;
+            } else {
+                i += 1;
+            }
+        }
+
+        tokens
+    }
+}
+
+impl Tokenizer for CjkBigramTokenizer {
+    type TokenStream<'a> = CodeTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        CodeTokenStream {
+            tokens: self.tokenize(text),
+            index: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(options: CodeTokenizerOptions, text: &str) -> Vec<String> {
+        let mut analyzer = code_analyzer(&options);
+        let mut stream = analyzer.token_stream(text);
+        let mut result = Vec::new();
+        while stream.advance() {
+            result.push(stream.token().text.clone());
+        }
+        result
+    }
+
+    #[test]
+    fn test_splits_identifier_styles() {
+        let options = CodeTokenizerOptions::default();
+
+        assert_eq!(
+            texts(options.clone(), "getServerSideProps"),
+            vec!["getserversideprops", "get", "server", "side", "props"],
+        );
+        assert_eq!(texts(options.clone(), "use_state"), vec!["use_state", "use", "state"]);
+        assert_eq!(texts(options.clone(), "server-side"), vec!["server-side", "server", "side"]);
+        assert_eq!(
+            texts(options.clone(), "App\\Http\\Controllers"),
+            vec!["app\\http\\controllers", "app", "http", "controllers"],
+        );
+        assert_eq!(texts(options.clone(), "HTTPServer"), vec!["httpserver", "http", "server"]);
+        assert_eq!(texts(options, "src/app/page.tsx"), vec!["src/app/page.tsx", "src", "app", "page", "tsx"]);
+    }
+
+    #[test]
+    fn test_offsets_and_positions() {
+        let tokenizer = CodeTokenizer::new(CodeTokenizerOptions::default());
+        let text = "call $fooBar();";
+        let tokens = tokenizer.tokenize(text);
+
+        let summary: Vec<(&str, usize, &str)> = tokens.iter()
+            .map(|t| (t.text.as_str(), t.position, &text[t.offset_from..t.offset_to]))
+            .collect();
+        assert_eq!(summary, vec![
+            ("call", 0, "call"),
+            ("fooBar", 1, "fooBar"),
+            ("foo", 1, "foo"),
+            ("Bar", 2, "Bar"),
+        ]);
+    }
+
+    #[test]
+    fn test_keyword_and_stopword_removal() {
+        let options = CodeTokenizerOptions {
+            keep_original: false,
+            remove_keywords: true,
+            remove_stopwords: true,
+        };
+
+        assert_eq!(
+            texts(options, "export const the_user = await fetchUser()"),
+            vec!["user", "fetch", "user"],
+        );
+    }
+
+    #[test]
+    fn test_cjk_bigrams() {
+        let tokens: Vec<String> = CjkBigramTokenizer.tokenize("AgendaでMongoDBを使ったジョブ管理")
+            .into_iter()
+            .map(|t| t.text)
+            .collect();
+
+        assert_eq!(tokens, vec![
+            "Agenda", "で", "MongoDB", "を使", "使っ", "った", "たジ", "ジョ", "ョブ", "ブ管", "管理",
+        ]);
+
+        let text = "AdonisJS ルート 最小";
+        let tokens = CjkBigramTokenizer.tokenize(text);
+        let spans: Vec<&str> = tokens.iter().map(|t| &text[t.offset_from..t.offset_to]).collect();
+        assert_eq!(spans, vec!["AdonisJS", "ルー", "ート", "最小"]);
+        assert_eq!(tokens.iter().map(|t| t.position).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_script_detection() {
+        assert_eq!(TextScript::detect("AdonisJS ルート 最小"), TextScript::Cjk);
+        assert_eq!(TextScript::detect("getServerSideProps"), TextScript::Default);
+        assert_eq!(TextScript::from_language("zh-CN"), Some(TextScript::Cjk));
+        assert_eq!(TextScript::from_language("en"), None);
+
+        // The code analyzer leaves CJK text to the bigram analyzer
+        let tokenizer = CodeTokenizer::new(CodeTokenizerOptions::default());
+        let texts: Vec<String> = tokenizer.tokenize("ユーザー認証 fetchUser").into_iter().map(|t| t.text).collect();
+        assert_eq!(texts, vec!["fetchUser", "fetch", "User"]);
+    }
+}
@@ -0,0 +1,145 @@
+//! In-memory nearest-neighbor index over chunk embeddings
+//!
+//! Keeps unit-normalized copies of `LearningChunk.embedding` so that cosine
+//! similarity reduces to a dot product during query-time retrieval.
+
+use std::collections::HashMap;
+
+use parking_lot::RwLock;
+
+use crate::chunk::{ChunkId, LearningChunk};
+
+/// Exact cosine-similarity index over chunk embeddings
+#[derive(Debug)]
+pub struct VectorIndex {
+    dimension: usize,
+    vectors: RwLock<HashMap<ChunkId, Vec<f32>>>,
+}
+
+impl VectorIndex {
+    /// Create an empty index for embeddings of the given dimension
+    pub fn new(dimension: usize) -> Self {
+        Self {
+            dimension,
+            vectors: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Embedding dimension accepted by the index
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Number of indexed embeddings
+    pub fn len(&self) -> usize {
+        self.vectors.read().len()
+    }
+
+    /// Check whether the index holds no embeddings
+    pub fn is_empty(&self) -> bool {
+        self.vectors.read().is_empty()
+    }
+
+    /// Add or replace an embedding, returning false if it cannot be indexed
+    pub fn insert(&self, chunk_id: ChunkId, embedding: &[f32]) -> bool {
+        match self.normalize(embedding) {
+            Some(vector) => {
+                self.vectors.write().insert(chunk_id, vector);
+                true
+            }
+            None => {
+                tracing::warn!(
+                    "Skipping embedding for chunk {}: expected {} non-zero dimensions, got {}",
+                    chunk_id,
+                    self.dimension,
+                    embedding.len()
+                );
+                false
+            }
+        }
+    }
+
+    /// Sync the index with a chunk's current embedding
+    pub fn upsert(&self, chunk: &LearningChunk) {
+        let indexed = chunk.embedding.as_ref()
+            .is_some_and(|embedding| self.insert(chunk.id.clone(), embedding));
+        if !indexed {
+            self.remove(&chunk.id);
+        }
+    }
+
+    /// Remove a chunk's embedding
+    pub fn remove(&self, chunk_id: &ChunkId) -> bool {
+        self.vectors.write().remove(chunk_id).is_some()
+    }
+
+    /// Get the `k` chunks most similar to a query embedding, best first
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(ChunkId, f32)> {
+        let Some(query) = self.normalize(query) else {
+            return Vec::new();
+        };
+
+        let vectors = self.vectors.read();
+        let mut scored: Vec<(ChunkId, f32)> = vectors.iter()
+            .map(|(chunk_id, vector)| (chunk_id.clone(), dot(&query, vector)))
+            .collect();
+        drop(vectors);
+
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.as_str().cmp(b.0.as_str())));
+        scored.truncate(k);
+        scored
+    }
+
+    fn normalize(&self, embedding: &[f32]) -> Option<Vec<f32>> {
+        if embedding.len() != self.dimension {
+            return None;
+        }
+
+        let norm = dot(embedding, embedding).sqrt();
+        if norm == 0.0 || !norm.is_finite() {
+            return None;
+        }
+
+        Some(embedding.iter().map(|v| v / norm).collect())
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_neighbors() {
+        let index = VectorIndex::new(3);
+        index.insert(ChunkId::new("x"), &[1.0, 0.0, 0.0]);
+        index.insert(ChunkId::new("xy"), &[1.0, 1.0, 0.0]);
+        index.insert(ChunkId::new("z"), &[0.0, 0.0, 5.0]);
+
+        let results = index.search(&[2.0, 0.1, 0.0], 2);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0.as_str(), "x");
+        assert_eq!(results[1].0.as_str(), "xy");
+        assert!(results[0].1 > 0.99);
+    }
+
+    #[test]
+    fn test_rejects_invalid_embeddings() {
+        let index = VectorIndex::new(2);
+        assert!(!index.insert(ChunkId::new("wrong-dim"), &[1.0, 2.0, 3.0]));
+        assert!(!index.insert(ChunkId::new("zero"), &[0.0, 0.0]));
+        assert!(index.is_empty());
+
+        let mut chunk = LearningChunk { embedding: Some(vec![0.5, 0.5]), ..Default::default() };
+        index.upsert(&chunk);
+        assert_eq!(index.len(), 1);
+
+        chunk.embedding = None;
+        index.upsert(&chunk);
+        assert!(index.is_empty());
+        assert!(index.search(&[1.0, 0.0], 5).is_empty());
+    }
+}